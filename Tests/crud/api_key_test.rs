@@ -1,6 +1,11 @@
+use axum::Extension;
+use axum::extract::Path;
+use axum::http::StatusCode;
 use payments_backend_dodo::datalayer::CRUD::accounts::AccountBuilder;
 use payments_backend_dodo::datalayer::CRUD::api_key::ApiKeyBuilder;
 use payments_backend_dodo::datalayer::db_ops::db_ops::initialize_database;
+use payments_backend_dodo::handlers::api_keys::revoke_api_key;
+use payments_backend_dodo::middleware::auth::AuthenticatedApiKey;
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
@@ -587,3 +592,109 @@ async fn test_api_key_with_expiration() {
 
     println!("\n=== ✅ TEST COMPLETED SUCCESSFULLY ===");
 }
+
+#[tokio::test]
+async fn test_restricted_key_cannot_revoke_broader_key() {
+    println!("\n=== TEST: Restricted key cannot revoke a broader key ===");
+
+    let _ = dotenvy::dotenv();
+
+    if std::env::var("DATABASE_URL").is_err() {
+        println!("⚠️  Skipping test: DATABASE_URL not set");
+        return;
+    }
+
+    let db_ops = initialize_database()
+        .await
+        .expect("Failed to initialize database");
+    let mut conn = db_ops
+        .tracker()
+        .get_connection()
+        .await
+        .expect("Failed to get connection");
+
+    let account = AccountBuilder::new()
+        .business_name(format!("API Key Test Account {}", Uuid::new_v4()))
+        .email(format!("apikey_test_{}@example.com", Uuid::new_v4()))
+        .currency("USD".to_string())
+        .status("active".to_string())
+        .expect_id()
+        .expect_business_name()
+        .expect_email()
+        .expect_balance()
+        .expect_currency()
+        .expect_status()
+        .create(Some(&mut conn))
+        .await
+        .expect("Failed to create account");
+
+    let mut keys = Vec::new();
+    for (name, permissions) in [
+        ("Test API Key Broad", serde_json::json!(["read", "write"])),
+        ("Test API Key Narrow", serde_json::json!(["api_keys:manage"])),
+    ] {
+        let api_key_value = format!("dodo_live_{}", Uuid::new_v4().simple());
+        let api_key = ApiKeyBuilder::new()
+            .account_id(account.id)
+            .key_hash(hash_api_key(&api_key_value))
+            .key_prefix(get_key_prefix(&api_key_value))
+            .name(name.to_string())
+            .status("active".to_string())
+            .permissions(permissions)
+            .expect_id()
+            .expect_account_id()
+            .expect_key_hash()
+            .expect_key_prefix()
+            .expect_name()
+            .expect_status()
+            .expect_permissions()
+            .expect_created_at()
+            .expect_last_used_at()
+            .expect_expires_at()
+            .expect_revoked_at()
+            .create(Some(&mut conn))
+            .await
+            .expect("Failed to create API key");
+        keys.push(api_key);
+    }
+    let (broad, narrow) = (&keys[0], &keys[1]);
+
+    // The narrow key authenticates as a caller holding only api_keys:manage
+    let caller = AuthenticatedApiKey {
+        api_key_id: narrow.id,
+        account_id: account.id,
+        key_prefix: narrow.key_prefix.clone(),
+        permissions: vec!["api_keys:manage".to_string()],
+        livemode: true,
+        signed: false,
+        require_signed_writes: false,
+        allowed_ips: Vec::new(),
+        user_id: None,
+        role: None,
+        client_ip: None,
+    };
+    let response = revoke_api_key(Extension(caller), Path(broad.id)).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let (status,): (String,) = sqlx::query_as("SELECT status FROM api_keys WHERE id = $1")
+        .bind(broad.id)
+        .fetch_one(&mut *conn)
+        .await
+        .expect("Failed to read API key");
+    assert_eq!(status, "active");
+
+    // Cleanup
+    let _ = sqlx::query("DELETE FROM api_keys WHERE account_id = $1")
+        .bind(account.id)
+        .execute(&mut *conn)
+        .await;
+    let _ = sqlx::query("DELETE FROM accounts WHERE id = $1")
+        .bind(account.id)
+        .execute(&mut *conn)
+        .await;
+
+    db_ops.tracker().return_connection(conn);
+    db_ops.shutdown().await;
+
+    println!("\n=== ✅ TEST COMPLETED SUCCESSFULLY ===");
+}
@@ -0,0 +1,116 @@
+use payments_backend_dodo::datalayer::CRUD::accounts::AccountBuilder;
+use payments_backend_dodo::datalayer::CRUD::events::{
+    EventFilter, create_event, get_events_for_account,
+};
+use payments_backend_dodo::datalayer::CRUD::types::Account;
+use payments_backend_dodo::datalayer::initialize_database;
+use payments_backend_dodo::errors::errors::ServiceError;
+use sqlx::{Postgres, pool::PoolConnection};
+use uuid::Uuid;
+
+/// Helper function to create an account for the test
+async fn create_test_account(conn: &mut PoolConnection<Postgres>) -> Account {
+    AccountBuilder::new()
+        .business_name(format!("Events Test Account {}", Uuid::new_v4()))
+        .email(format!("events_test_{}@example.com", Uuid::new_v4()))
+        .currency("USD".to_string())
+        .status("active".to_string())
+        .expect_id()
+        .expect_business_name()
+        .expect_email()
+        .expect_balance()
+        .expect_currency()
+        .expect_status()
+        .create(Some(conn))
+        .await
+        .expect("Failed to create account")
+}
+
+fn page_after(cursor: Option<Uuid>) -> EventFilter {
+    EventFilter {
+        event_type: None,
+        created_after: None,
+        created_before: None,
+        cursor,
+        limit: 10,
+    }
+}
+
+#[tokio::test]
+async fn test_event_cursor_must_belong_to_account() {
+    println!("\n=== TEST: Event list cursor is scoped to the account ===");
+
+    let _ = dotenvy::dotenv();
+
+    if std::env::var("DATABASE_URL").is_err() {
+        println!("⚠️  Skipping test: DATABASE_URL not set");
+        return;
+    }
+
+    let db_ops = initialize_database()
+        .await
+        .expect("Failed to initialize database");
+    let mut conn = db_ops
+        .tracker()
+        .get_connection()
+        .await
+        .expect("Failed to get connection");
+
+    let account = create_test_account(&mut conn).await;
+    let other_account = create_test_account(&mut conn).await;
+
+    let first = create_event(
+        account.id,
+        "account.updated",
+        serde_json::json!({ "step": 1 }),
+        &mut conn,
+    )
+    .await
+    .expect("Failed to create event");
+    let second = create_event(
+        account.id,
+        "account.updated",
+        serde_json::json!({ "step": 2 }),
+        &mut conn,
+    )
+    .await
+    .expect("Failed to create event");
+    let foreign = create_event(
+        other_account.id,
+        "account.updated",
+        serde_json::json!({ "step": 1 }),
+        &mut conn,
+    )
+    .await
+    .expect("Failed to create event");
+
+    // The account's own cursor pages through its events
+    let page = get_events_for_account(account.id, &page_after(Some(second.id)), &mut conn)
+        .await
+        .expect("Failed to list events");
+    assert_eq!(
+        page.iter().map(|e| e.id).collect::<Vec<_>>(),
+        vec![first.id]
+    );
+
+    // Another account's event id is rejected, as is an unknown one
+    let result = get_events_for_account(account.id, &page_after(Some(foreign.id)), &mut conn).await;
+    assert!(matches!(result, Err(ServiceError::InvalidInput(_))));
+
+    let result =
+        get_events_for_account(account.id, &page_after(Some(Uuid::new_v4())), &mut conn).await;
+    assert!(matches!(result, Err(ServiceError::InvalidInput(_))));
+
+    // Cleanup; events go with their accounts
+    for account_id in [account.id, other_account.id] {
+        let _ = sqlx::query("DELETE FROM accounts WHERE id = $1")
+            .bind(account_id)
+            .execute(&mut *conn)
+            .await;
+    }
+
+    db_ops.tracker().return_connection(conn);
+    db_ops.shutdown().await;
+
+    println!("\n=== ✅ TEST COMPLETED SUCCESSFULLY ===");
+}
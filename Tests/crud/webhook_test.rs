@@ -0,0 +1,198 @@
+use payments_backend_dodo::datalayer::CRUD::accounts::AccountBuilder;
+use payments_backend_dodo::datalayer::CRUD::types::Account;
+use payments_backend_dodo::datalayer::CRUD::webhook::{
+    DeadLetterFilter, WebhookUpdate, create_webhook, get_dead_letters_for_account, update_webhook,
+    update_webhook_status,
+};
+use payments_backend_dodo::datalayer::initialize_database;
+use payments_backend_dodo::errors::errors::ServiceError;
+use sqlx::{Postgres, pool::PoolConnection};
+use uuid::Uuid;
+
+/// Helper function to create an account for the test
+async fn create_test_account(conn: &mut PoolConnection<Postgres>) -> Account {
+    AccountBuilder::new()
+        .business_name(format!("Webhook Test Account {}", Uuid::new_v4()))
+        .email(format!("webhook_test_{}@example.com", Uuid::new_v4()))
+        .currency("USD".to_string())
+        .status("active".to_string())
+        .expect_id()
+        .expect_business_name()
+        .expect_email()
+        .expect_balance()
+        .expect_currency()
+        .expect_status()
+        .create(Some(conn))
+        .await
+        .expect("Failed to create account")
+}
+
+/// Helper function to insert a dead-lettered delivery for a webhook
+async fn create_dead_letter(webhook_id: Uuid, conn: &mut PoolConnection<Postgres>) -> Uuid {
+    let (id,) = sqlx::query_as::<_, (Uuid,)>(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event_type, payload, status, dead_lettered_at)
+        VALUES ($1, 'transaction.credited', '{}', 'dead_lettered', NOW())
+        RETURNING id
+        "#,
+    )
+    .bind(webhook_id)
+    .fetch_one(&mut **conn)
+    .await
+    .expect("Failed to create dead-lettered delivery");
+    id
+}
+
+fn all_dead_letters() -> DeadLetterFilter {
+    DeadLetterFilter {
+        webhook_id: None,
+        dead_lettered_after: None,
+        dead_lettered_before: None,
+    }
+}
+
+fn new_url(url: &str) -> WebhookUpdate {
+    WebhookUpdate {
+        url: Some(url.to_string()),
+        ..Default::default()
+    }
+}
+
+/// A new webhook URL must be verified again
+async fn check_url_change_requires_verification(conn: &mut PoolConnection<Postgres>) {
+    let account = create_test_account(conn).await;
+    let webhook = create_webhook(
+        account.id,
+        "https://example.com/hooks/a".to_string(),
+        "encrypted",
+        "test-key",
+        "active",
+        conn,
+    )
+    .await
+    .expect("Failed to create webhook");
+
+    // Other changes, and the same URL, keep the webhook active
+    let unchanged = update_webhook(
+        webhook.id,
+        account.id,
+        WebhookUpdate {
+            max_retries: Some(3),
+            ..new_url("https://example.com/hooks/a")
+        },
+        conn,
+    )
+    .await
+    .expect("Failed to update webhook");
+    assert_eq!(unchanged.status, "active");
+
+    // A new URL has to pass the handshake before it receives events
+    let moved = update_webhook(
+        webhook.id,
+        account.id,
+        new_url("https://example.com/hooks/b"),
+        conn,
+    )
+    .await
+    .expect("Failed to update webhook");
+    assert_eq!(moved.url, "https://example.com/hooks/b");
+    assert_eq!(moved.status, "pending_verification");
+
+    // A disabled webhook is not re-enabled by verifying a new URL
+    update_webhook_status(webhook.id, account.id, "disabled", conn)
+        .await
+        .expect("Failed to disable webhook");
+    let disabled = update_webhook(
+        webhook.id,
+        account.id,
+        new_url("https://example.com/hooks/c"),
+        conn,
+    )
+    .await
+    .expect("Failed to update webhook");
+    assert_eq!(disabled.status, "disabled");
+
+    // Cleanup; webhooks go with their accounts
+    let _ = sqlx::query("DELETE FROM accounts WHERE id = $1")
+        .bind(account.id)
+        .execute(&mut **conn)
+        .await;
+}
+
+/// The dead-letter list cursor is scoped to the account
+async fn check_dead_letter_cursor_must_belong_to_account(conn: &mut PoolConnection<Postgres>) {
+    let account = create_test_account(conn).await;
+    let other_account = create_test_account(conn).await;
+    let mut dead_letters = Vec::new();
+    for account_id in [account.id, other_account.id] {
+        let webhook = create_webhook(
+            account_id,
+            "https://example.com/hooks/dlq".to_string(),
+            "encrypted",
+            "test-key",
+            "active",
+            conn,
+        )
+        .await
+        .expect("Failed to create webhook");
+        dead_letters.push(create_dead_letter(webhook.id, conn).await);
+    }
+
+    // The account's own cursor pages past its dead letter
+    let page = get_dead_letters_for_account(
+        account.id,
+        &all_dead_letters(),
+        Some(dead_letters[0]),
+        10,
+        conn,
+    )
+    .await
+    .expect("Failed to list dead letters");
+    assert!(page.is_empty());
+
+    // Another account's delivery is rejected, as is an unknown id
+    for cursor in [dead_letters[1], Uuid::new_v4()] {
+        let result =
+            get_dead_letters_for_account(account.id, &all_dead_letters(), Some(cursor), 10, conn)
+                .await;
+        assert!(matches!(result, Err(ServiceError::InvalidInput(_))));
+    }
+
+    // Cleanup; webhooks and deliveries go with their accounts
+    for account_id in [account.id, other_account.id] {
+        let _ = sqlx::query("DELETE FROM accounts WHERE id = $1")
+            .bind(account_id)
+            .execute(&mut **conn)
+            .await;
+    }
+}
+
+// The checks share one runtime, since the connection pool is global
+#[tokio::test]
+async fn test_webhook_crud() {
+    println!("\n=== TEST: Webhook CRUD ===");
+
+    let _ = dotenvy::dotenv();
+
+    if std::env::var("DATABASE_URL").is_err() {
+        println!("⚠️  Skipping test: DATABASE_URL not set");
+        return;
+    }
+
+    let db_ops = initialize_database()
+        .await
+        .expect("Failed to initialize database");
+    let mut conn = db_ops
+        .tracker()
+        .get_connection()
+        .await
+        .expect("Failed to get connection");
+
+    check_url_change_requires_verification(&mut conn).await;
+    check_dead_letter_cursor_must_belong_to_account(&mut conn).await;
+
+    db_ops.tracker().return_connection(conn);
+    db_ops.shutdown().await;
+
+    println!("\n=== ✅ TEST COMPLETED SUCCESSFULLY ===");
+}
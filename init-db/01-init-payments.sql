@@ -21,8 +21,14 @@ CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 -- Stores business account information and balances
 CREATE TABLE IF NOT EXISTS accounts (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
-    business_name VARCHAR(255) UNIQUE NOT NULL,
-    email VARCHAR(255) NOT NULL UNIQUE,
+    business_name VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+
+    -- Test-mode accounts are sandboxes: reachable only with dodo_test_ keys, isolated from live ones
+    livemode BOOLEAN NOT NULL DEFAULT TRUE,
+
+    -- Requests needing a write scope must be signed rather than carry a bearer key
+    require_signed_writes BOOLEAN NOT NULL DEFAULT FALSE,
     
     -- Balance tracking (stored in smallest currency unit, e.g., cents)
     balance BIGINT NOT NULL DEFAULT 0 CHECK (balance >= 0),
@@ -36,7 +42,11 @@ CREATE TABLE IF NOT EXISTS accounts (
     
     -- Timestamps
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+
+    -- A business may hold one live and one test account
+    CONSTRAINT accounts_business_name_mode_key UNIQUE (business_name, livemode),
+    CONSTRAINT accounts_email_mode_key UNIQUE (email, livemode)
 );
 
 -- ============================================================================
@@ -57,7 +67,19 @@ CREATE TABLE IF NOT EXISTS api_keys (
     
     -- Permissions (for future extensibility)
     permissions JSONB DEFAULT '["read", "write"]',
+
+    -- CIDRs the key may be used from; NULL or empty allows any address
+    allowed_ips JSONB,
+
+    -- HMAC secret for signed requests, stored envelope-encrypted; NULL until one is issued
+    encrypted_signing_secret TEXT,
+    -- Master key that wrapped the signing secret's data key
+    signing_secret_key_id VARCHAR(100),
     
+    -- Last use, flushed in batches from the request path
+    last_used_ip VARCHAR(45),
+    last_user_agent VARCHAR(512),
+
     -- Timestamps
     last_used_at TIMESTAMP WITH TIME ZONE,
     expires_at TIMESTAMP WITH TIME ZONE,
@@ -65,6 +87,18 @@ CREATE TABLE IF NOT EXISTS api_keys (
     revoked_at TIMESTAMP WITH TIME ZONE
 );
 
+-- ============================================================================
+-- API KEY USAGE TABLE
+-- ============================================================================
+-- Requests authenticated by each API key per UTC day, and those refused by its IP allowlist
+CREATE TABLE IF NOT EXISTS api_key_usage_daily (
+    api_key_id UUID NOT NULL REFERENCES api_keys(id) ON DELETE CASCADE,
+    usage_date DATE NOT NULL,
+    request_count BIGINT NOT NULL DEFAULT 0,
+    ip_rejected_count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (api_key_id, usage_date)
+);
+
 -- ============================================================================
 -- TRANSACTIONS TABLE
 -- ============================================================================
@@ -91,6 +125,9 @@ CREATE TABLE IF NOT EXISTS transactions (
 
     -- Parent transaction key
     parent_tx_key VARCHAR(255) NOT NULL DEFAULT '',
+
+    -- Mode of the accounts involved; test and live transactions never mix
+    livemode BOOLEAN NOT NULL DEFAULT TRUE,
     
     -- Description and metadata
     description TEXT,
@@ -122,18 +159,35 @@ CREATE TABLE IF NOT EXISTS webhooks (
     
     -- Webhook configuration
     url TEXT NOT NULL,
-    secret VARCHAR(255) NOT NULL, -- For HMAC signature verification
+    -- HMAC secret, generated by the server and stored envelope-encrypted
+    encrypted_secret TEXT NOT NULL,
+    -- Master key that wrapped the secret's data key
+    secret_key_id VARCHAR(100) NOT NULL,
+    -- 'hmac-sha256' signs with secret; 'ed25519' signs with the platform key published as JWKS
+    signing_algorithm VARCHAR(20) NOT NULL DEFAULT 'hmac-sha256' CHECK (signing_algorithm IN ('hmac-sha256', 'ed25519')),
+    description TEXT,
+    headers JSONB NOT NULL DEFAULT '{}', -- Custom static headers sent with every delivery
     
-    -- Event subscriptions
-    events JSONB NOT NULL DEFAULT '["transaction.created", "transaction.completed", "transaction.failed"]',
+    -- Mode of the owning account
+    livemode BOOLEAN NOT NULL DEFAULT TRUE,
+
+    -- Event subscriptions ("*" subscribes to every event type)
+    events JSONB NOT NULL DEFAULT '["*"]',
     
     -- Status
-    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'disabled', 'failed')),
+    -- 'pending_verification' webhooks receive no events until the endpoint echoes the challenge
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'disabled', 'failed', 'pending_verification')),
     
     -- Retry configuration
     max_retries INTEGER DEFAULT 3,
     retry_backoff_seconds INTEGER DEFAULT 60,
     
+    -- Delivery ordering
+    -- At most max_concurrency attempts are in flight at once; 1 makes first attempts strictly in order
+    max_concurrency INTEGER NOT NULL DEFAULT 1 CHECK (max_concurrency BETWEEN 1 AND 20),
+    -- Last sequence number assigned to a delivery of this webhook
+    delivery_sequence BIGINT NOT NULL DEFAULT 0,
+    
     -- Failure tracking
     consecutive_failures INTEGER DEFAULT 0,
     last_failure_at TIMESTAMP WITH TIME ZONE,
@@ -143,6 +197,24 @@ CREATE TABLE IF NOT EXISTS webhooks (
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
 );
 
+-- ============================================================================
+-- EVENTS TABLE
+-- ============================================================================
+-- Append-only log of account domain events, served by the Events API and streams
+-- The event id is stable: webhooks and streams carry it so consumers can deduplicate
+
+CREATE TABLE IF NOT EXISTS events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- Monotonic position in the log; streams resume after a given event's sequence
+    sequence BIGSERIAL UNIQUE NOT NULL,
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    event_type VARCHAR(100) NOT NULL,
+    -- Version of the data schema for this event type
+    version INTEGER NOT NULL DEFAULT 1,
+    data JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
 -- ============================================================================
 -- WEBHOOK DELIVERIES TABLE
 -- ============================================================================
@@ -151,13 +223,19 @@ CREATE TABLE IF NOT EXISTS webhook_deliveries (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
     webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
     transaction_id UUID REFERENCES transactions(id) ON DELETE CASCADE,
+    -- Event this delivery carries; redeliveries of the same event share it
+    event_id UUID REFERENCES events(id) ON DELETE SET NULL,
     
     -- Event details
     event_type VARCHAR(50) NOT NULL,
     payload JSONB NOT NULL,
+    -- Per-webhook sequence number, also sent in the payload; NULL for pings
+    sequence BIGINT,
     
     -- Delivery status
-    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+    -- 'dead_lettered': every attempt failed; the delivery waits in the dead-letter queue
+    -- 'discarded': removed from the dead-letter queue without being redelivered
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed', 'dead_lettered', 'discarded')),
     
     -- Retry tracking
     attempt_count INTEGER DEFAULT 0,
@@ -172,7 +250,97 @@ CREATE TABLE IF NOT EXISTS webhook_deliveries (
     -- Timestamps
     created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
     delivered_at TIMESTAMP WITH TIME ZONE,
-    failed_at TIMESTAMP WITH TIME ZONE
+    failed_at TIMESTAMP WITH TIME ZONE,
+    dead_lettered_at TIMESTAMP WITH TIME ZONE
+);
+
+-- ============================================================================
+-- WEBHOOK BACKFILLS TABLE
+-- ============================================================================
+-- Replays of historical events to a webhook, with their progress
+-- last_sequence is the resume point: events up to it have been handed to the delivery queue
+CREATE TABLE IF NOT EXISTS webhook_backfills (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    event_types JSONB NOT NULL, -- JSONB array
+    start_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    end_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed')),
+    total_events INTEGER NOT NULL DEFAULT 0,
+    queued_events INTEGER NOT NULL DEFAULT 0,
+    last_sequence BIGINT NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    CHECK (start_at < end_at)
+);
+
+-- ============================================================================
+-- USERS TABLE
+-- ============================================================================
+-- People who sign in to manage accounts; an account's members are in account_memberships
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    email VARCHAR(255) NOT NULL UNIQUE, -- stored lowercase
+    password_hash TEXT NOT NULL, -- argon2id PHC string
+    name VARCHAR(255),
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'disabled')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+-- ============================================================================
+-- ACCOUNT MEMBERSHIPS TABLE
+-- ============================================================================
+-- Role of a user on an account; the role decides which scopes the user's sessions hold
+CREATE TABLE IF NOT EXISTS account_memberships (
+    account_id UUID NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(20) NOT NULL CHECK (role IN ('owner', 'admin', 'developer', 'viewer')),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, user_id)
+);
+
+-- ============================================================================
+-- USER SESSIONS TABLE
+-- ============================================================================
+-- Session tokens issued at login; only their SHA-256 is stored
+CREATE TABLE IF NOT EXISTS user_sessions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+-- ============================================================================
+-- AUDIT LOG TABLE
+-- ============================================================================
+-- Who changed what through the API, written in the transaction of the change
+-- Rows are never updated or deleted; see prevent_audit_log_changes below
+CREATE TABLE IF NOT EXISTS audit_log (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- NULL for operator actions that concern no single account
+    account_id UUID REFERENCES accounts(id),
+    -- 'api_key': an API key or access token; 'user': a user session; 'admin': an operator
+    actor_type VARCHAR(20) NOT NULL CHECK (actor_type IN ('api_key', 'user', 'admin')),
+    actor_id UUID, -- API key id or user id
+    actor_name VARCHAR(100), -- operator name from ADMIN_CREDENTIALS
+    action VARCHAR(100) NOT NULL,
+    target_type VARCHAR(50) NOT NULL,
+    target_id VARCHAR(255) NOT NULL,
+    -- Changed fields only, before and after the change; NULL before a creation or after a deletion
+    before JSONB,
+    after JSONB,
+    -- Why an operator took the action, as they gave it
+    reason TEXT,
+    request_id UUID,
+    ip_address VARCHAR(45),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
 
 
@@ -208,6 +376,23 @@ CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook_id ON webhook_deliveri
 CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_transaction_id ON webhook_deliveries(transaction_id);
 CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_status ON webhook_deliveries(status);
 CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_next_retry ON webhook_deliveries(next_retry_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_dead_letters ON webhook_deliveries(webhook_id, dead_lettered_at) WHERE status = 'dead_lettered';
+
+-- Events indexes
+CREATE INDEX IF NOT EXISTS idx_events_account_sequence ON events(account_id, sequence);
+CREATE INDEX IF NOT EXISTS idx_events_account_created ON events(account_id, created_at DESC, id DESC);
+
+-- Webhook backfills indexes
+-- At most one backfill runs per webhook at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_webhook_backfills_running ON webhook_backfills(webhook_id) WHERE status = 'running';
+
+-- Users indexes
+CREATE INDEX IF NOT EXISTS idx_account_memberships_user_id ON account_memberships(user_id);
+CREATE INDEX IF NOT EXISTS idx_user_sessions_user_id ON user_sessions(user_id);
+
+-- Audit log indexes
+CREATE INDEX IF NOT EXISTS idx_audit_log_account_created ON audit_log(account_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_target ON audit_log(target_type, target_id);
 
 -- Rate limit indexes
 
@@ -237,6 +422,51 @@ CREATE TRIGGER update_webhooks_updated_at
     FOR EACH ROW
     EXECUTE FUNCTION update_updated_at_column();
 
+-- Apply trigger to users
+CREATE TRIGGER update_users_updated_at
+    BEFORE UPDATE ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Apply trigger to account memberships
+CREATE TRIGGER update_account_memberships_updated_at
+    BEFORE UPDATE ON account_memberships
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- ============================================================================
+-- AUDIT LOG PROTECTION
+-- ============================================================================
+
+-- The audit log is append-only, even for code holding the table's privileges
+CREATE OR REPLACE FUNCTION prevent_audit_log_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW
+    EXECUTE FUNCTION prevent_audit_log_changes();
+
+CREATE TRIGGER audit_log_no_truncate
+    BEFORE TRUNCATE ON audit_log
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION prevent_audit_log_changes();
+
+-- ============================================================================
+-- VIEWS
+-- ============================================================================
+
+-- Dead-letter queue: deliveries that exhausted max_attempts, with the owning account
+CREATE OR REPLACE VIEW webhook_dead_letters AS
+SELECT d.*, w.account_id
+FROM webhook_deliveries d
+JOIN webhooks w ON w.id = d.webhook_id
+WHERE d.status = 'dead_lettered';
+
 -- ============================================================================
 -- SAMPLE DATA FOR TESTING
 -- ============================================================================
@@ -256,11 +486,7 @@ INSERT INTO api_keys (account_id, key_hash, key_prefix, name) VALUES
     ('33333333-3333-3333-3333-333333333333', 'hashed_key_global_789', 'sk_test_glob', 'Global Traders Key')
 ON CONFLICT DO NOTHING;
 
--- Insert sample webhooks
-INSERT INTO webhooks (account_id, url, secret) VALUES 
-    ('11111111-1111-1111-1111-111111111111', 'https://acme.example.com/webhooks', 'whsec_acme_secret_123'),
-    ('22222222-2222-2222-2222-222222222222', 'https://techstart.example.com/webhooks', 'whsec_tech_secret_456')
-ON CONFLICT DO NOTHING;
+-- No sample webhooks: their secrets are generated and encrypted by the API
 
 -- ============================================================================
 -- GRANT PRIVILEGES
@@ -277,7 +503,9 @@ GRANT USAGE ON SCHEMA public TO postgres;
 DO $$
 BEGIN
     RAISE NOTICE '✅ Payments database initialized successfully!';
-    RAISE NOTICE '📊 Tables created: accounts, api_keys, transactions, webhooks, webhook_deliveries';
+    RAISE NOTICE '📊 Tables created: accounts, api_keys, api_key_usage_daily, transactions, webhooks, events, webhook_deliveries, webhook_backfills, users, account_memberships, user_sessions, audit_log';
+    RAISE NOTICE '👁️ Views created: webhook_dead_letters';
+    RAISE NOTICE '🔒 audit_log is append-only: updates, deletes and truncates are rejected';
     RAISE NOTICE '🔍 Indexes created for optimal query performance';
     RAISE NOTICE '🧪 Sample data inserted for testing';
 END $$;
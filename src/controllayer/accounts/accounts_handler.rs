@@ -4,14 +4,19 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use sqlx::Acquire; // Required for begin() method on connections
+use sqlx::PgConnection;
 use tracing::instrument;
 use uuid::Uuid;
 
 use crate::{
     datalayer::{
         CRUD::{
-            accounts::AccountBuilder, api_key::ApiKeyBuilder,
+            accounts::{AccountBuilder, lock_account, update_account_status},
+            api_key::{ApiKeyBuilder, get_api_keys_for_account},
+            audit_log::{AuditActor, NewAuditEntry, create_audit_entry},
+            events::{Event, create_event},
             helper::apikey_generator::generate_api_key,
+            types::Account,
         },
         db_ops::constants::POOL_STATE_TRACKER,
     },
@@ -20,14 +25,21 @@ use crate::{
         AccountResponse, CreateAccountRequest, CreateAccountResponse, GetAccountRequest,
         PutBalanceRequest, UpdateAccountRequest,
     },
+    services::{
+        api_key_cache::invalidate_api_keys,
+        event_bus::{account_event_data, api_key_event_data, publish_events},
+    },
 };
 
+/// Balance a test-mode account opens with, in its own currency
+const TEST_MODE_STARTING_BALANCE: f64 = 10_000.0;
+
 /// Create a new account with an API key
 ///
 /// This handler:
 /// 1. Validates the request payload
 /// 2. Checks for duplicate accounts (by business_name or email)
-/// 3. Creates the account with balance = 0
+/// 3. Creates the account with balance = 0, or the free starting credit in test mode
 /// 4. Generates a secure API key
 /// 5. Hashes and stores the API key
 /// 6. Returns account details with the plain-text API key (only time it's shown)
@@ -35,10 +47,13 @@ use crate::{
 /// Note: Uses SQL-level transactions to ensure atomic creation of both account and API key
 #[instrument(fields(service = "/api/v1/accounts"))]
 pub async fn create_account(Json(payload): Json<CreateAccountRequest>) -> Response {
+    let livemode = payload.livemode.unwrap_or(true);
+
     tracing::info!(
         business_name = %payload.business_name,
         email = %payload.email,
         currency = %payload.currency,
+        livemode = livemode,
         "Creating new account"
     );
 
@@ -104,6 +119,7 @@ pub async fn create_account(Json(payload): Json<CreateAccountRequest>) -> Respon
         .email(payload.email.clone())
         .currency(payload.currency.clone())
         .status("active".to_string())
+        .livemode(livemode)
         .expect_id()
         .expect_business_name()
         .expect_email()
@@ -136,8 +152,41 @@ pub async fn create_account(Json(payload): Json<CreateAccountRequest>) -> Respon
         }
     };
 
-    // Generate API key
-    let (api_key, key_hash, key_prefix) = generate_api_key(true);
+    // Test-mode accounts open with free credit so integrations can move money right away
+    let account = if livemode {
+        account
+    } else {
+        match AccountBuilder::new()
+            .id(account.id)
+            .livemode(false)
+            .balance(TEST_MODE_STARTING_BALANCE)
+            .expect_id()
+            .expect_business_name()
+            .expect_email()
+            .expect_balance()
+            .expect_currency()
+            .expect_status()
+            .expect_created_at()
+            .expect_updated_at()
+            .update(Some(conn))
+            .await
+        {
+            Ok(acc) => acc,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to credit test-mode account");
+                let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+                return create_error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "account_creation_failed",
+                    &format!("Failed to create account: {}", e),
+                    None,
+                );
+            }
+        }
+    };
+
+    // Generate API key; its prefix selects the mode of every request made with it
+    let (api_key, key_hash, key_prefix) = generate_api_key(livemode);
 
     tracing::info!(
         account_id = %account.id,
@@ -146,7 +195,7 @@ pub async fn create_account(Json(payload): Json<CreateAccountRequest>) -> Respon
     );
 
     // Store the API key in the database within the same transaction using ApiKeyBuilder
-    match ApiKeyBuilder::new()
+    let api_key_record = match ApiKeyBuilder::new()
         .account_id(account.id)
         .key_hash(key_hash)
         .key_prefix(key_prefix)
@@ -173,6 +222,7 @@ pub async fn create_account(Json(payload): Json<CreateAccountRequest>) -> Respon
                 api_key_id = %api_key_record.id,
                 "API key created and stored successfully in transaction"
             );
+            api_key_record
         }
         Err(e) => {
             tracing::error!(
@@ -189,6 +239,31 @@ pub async fn create_account(Json(payload): Json<CreateAccountRequest>) -> Respon
                 None,
             );
         }
+    };
+
+    // Record the events in the same transaction so the log matches committed state
+    let mut events: Vec<Event> = Vec::new();
+    for (event_type, data) in [
+        ("account.created", account_event_data(&account)),
+        ("api_key.created", api_key_event_data(&api_key_record)),
+    ] {
+        match create_event(account.id, event_type, data, conn).await {
+            Ok(event) => events.push(event),
+            Err(e) => {
+                tracing::error!(
+                    error = %e,
+                    account_id = %account.id,
+                    "Failed to record account event, rolling back account creation"
+                );
+                let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+                return create_error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "event_creation_failed",
+                    "Failed to record account event, account creation rolled back",
+                    None,
+                );
+            }
+        }
     }
 
     // Commit the transaction - both account and API key are created atomically
@@ -216,12 +291,16 @@ pub async fn create_account(Json(payload): Json<CreateAccountRequest>) -> Respon
         }
     }
 
+    publish_events(&events).await;
+
     // Prepare response
     let response = CreateAccountResponse {
         account: AccountResponse {
             id: account.id,
             business_name: account.business_name,
             email: account.email,
+            livemode: account.livemode,
+            require_signed_writes: account.require_signed_writes,
             balance: Some(account.balance),
             currency: Some(account.currency),
             status: Some(account.status),
@@ -334,6 +413,7 @@ pub async fn update_balance(
     // First, verify the account exists by reading it
     let existing_account = match AccountBuilder::new()
         .id(payload.account_id)
+        .livemode(auth_info.livemode)
         .expect_id()
         .expect_business_name()
         .expect_email()
@@ -368,11 +448,27 @@ pub async fn update_balance(
         }
     };
 
+    // Update and its event are committed together
+    if let Err(e) = sqlx::query("BEGIN").execute(&mut **conn).await {
+        tracing::error!(error = %e, "Failed to begin transaction");
+        return create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            "Failed to begin transaction",
+            None,
+        );
+    }
+
     // Update the account with new balance and optionally currency
     let updated_account = match AccountBuilder::new()
         .id(payload.account_id)
+        .livemode(auth_info.livemode)
         .balance(payload.balance)
-        .currency(payload.currency.unwrap_or(existing_account.currency))
+        .currency(
+            payload
+                .currency
+                .unwrap_or_else(|| existing_account.currency.clone()),
+        )
         .expect_id()
         .expect_business_name()
         .expect_email()
@@ -400,6 +496,7 @@ pub async fn update_balance(
                 account_id = %payload.account_id,
                 "Failed to update account balance"
             );
+            let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
             return create_error_response(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "update_failed",
@@ -409,11 +506,28 @@ pub async fn update_balance(
         }
     };
 
+    let event = match commit_account_update(
+        &existing_account,
+        &updated_account,
+        "account.balance_set",
+        &auth_info.audit_actor(),
+        None,
+        conn,
+    )
+    .await
+    {
+        Ok(event) => event,
+        Err(response) => return response,
+    };
+    publish_events(&[event]).await;
+
     // Prepare response
     let response = AccountResponse {
         id: updated_account.id,
         business_name: updated_account.business_name,
         email: updated_account.email,
+        livemode: updated_account.livemode,
+        require_signed_writes: updated_account.require_signed_writes,
         balance: Some(updated_account.balance),
         currency: Some(updated_account.currency),
         status: Some(updated_account.status),
@@ -496,6 +610,7 @@ pub async fn get_account(
     // First, verify the account exists by reading it
     let existing_account = match AccountBuilder::new()
         .id(payload.account_id)
+        .livemode(auth_info.livemode)
         .expect_id()
         .expect_business_name()
         .expect_email()
@@ -535,6 +650,8 @@ pub async fn get_account(
         id: existing_account.id,
         business_name: existing_account.business_name,
         email: existing_account.email,
+        livemode: existing_account.livemode,
+        require_signed_writes: existing_account.require_signed_writes,
         balance: Some(existing_account.balance),
         currency: Some(existing_account.currency),
         status: Some(existing_account.status),
@@ -569,6 +686,7 @@ pub async fn update_account(
         email = ?payload.email,
         business_name = ?payload.business_name,
         status = ?payload.status,
+        require_signed_writes = ?payload.require_signed_writes,
         "Updating account details"
     );
 
@@ -587,6 +705,16 @@ pub async fn update_account(
         );
     }
 
+    // Only a working signed request proves the account will not lock itself out
+    if payload.require_signed_writes == Some(true) && !auth_info.signed {
+        return create_error_response(
+            StatusCode::FORBIDDEN,
+            "signature_required",
+            "require_signed_writes can only be enabled by a signed request",
+            None,
+        );
+    }
+
     // Get database connection pool
     let tracker = match POOL_STATE_TRACKER.get() {
         Some(t) => t,
@@ -631,7 +759,9 @@ pub async fn update_account(
     };
 
     // Build the update with only the fields that are provided
-    let mut builder = AccountBuilder::new().id(account_id);
+    let mut builder = AccountBuilder::new()
+        .id(account_id)
+        .livemode(auth_info.livemode);
 
     // Only set fields that are provided (Some)
     if let Some(business_name) = payload.business_name {
@@ -643,6 +773,48 @@ pub async fn update_account(
     if let Some(status) = payload.status {
         builder = builder.status(status);
     }
+    if let Some(require_signed_writes) = payload.require_signed_writes {
+        builder = builder.require_signed_writes(require_signed_writes);
+    }
+
+    // Update and its event are committed together
+    if let Err(e) = sqlx::query("BEGIN").execute(&mut **conn).await {
+        tracing::error!(error = %e, "Failed to begin transaction");
+        return create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            "Failed to begin transaction",
+            None,
+        );
+    }
+
+    // The account as it was, for the audit log
+    let previous = match AccountBuilder::new()
+        .id(account_id)
+        .livemode(auth_info.livemode)
+        .expect_id()
+        .expect_business_name()
+        .expect_email()
+        .expect_balance()
+        .expect_currency()
+        .expect_status()
+        .expect_created_at()
+        .expect_updated_at()
+        .read(Some(&mut conn))
+        .await
+    {
+        Ok(acc) => acc,
+        Err(e) => {
+            tracing::warn!(error = %e, account_id = %account_id, "Account not found");
+            let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+            return create_error_response(
+                StatusCode::NOT_FOUND,
+                "account_not_found",
+                &format!("Account with ID {} not found", account_id),
+                None,
+            );
+        }
+    };
 
     // Update the account details
     let account = match builder
@@ -672,6 +844,7 @@ pub async fn update_account(
                 account_id = %account_id,
                 "Failed to update account"
             );
+            let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
             return create_error_response(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "update_failed",
@@ -681,11 +854,40 @@ pub async fn update_account(
         }
     };
 
+    let event = match commit_account_update(
+        &previous,
+        &account,
+        "account.updated",
+        &auth_info.audit_actor(),
+        None,
+        conn,
+    )
+    .await
+    {
+        Ok(event) => event,
+        Err(response) => return response,
+    };
+
+    // Authentication caches the setting with each of the account's keys
+    if payload.require_signed_writes.is_some() {
+        match get_api_keys_for_account(account.id, conn).await {
+            Ok(api_keys) => invalidate_api_keys(&api_keys.iter().collect::<Vec<_>>()).await,
+            Err(e) => tracing::error!(
+                error = %e,
+                account_id = %account.id,
+                "Failed to load API keys to invalidate after account update"
+            ),
+        }
+    }
+    publish_events(&[event]).await;
+
     // Prepare response
     let response = AccountResponse {
         id: account.id,
         business_name: account.business_name,
         email: account.email,
+        livemode: account.livemode,
+        require_signed_writes: account.require_signed_writes,
         balance: Some(account.balance),
         currency: Some(account.currency),
         status: Some(account.status),
@@ -699,3 +901,272 @@ pub async fn update_account(
 
     (StatusCode::OK, Json(response)).into_response()
 }
+
+/// A change of account status made by an operator
+#[derive(Debug, Clone, Copy)]
+pub enum AccountStatusChange {
+    /// Stop the account's keys and users, and credits to it, until reactivated
+    Suspend,
+    /// Lift a suspension
+    Reactivate,
+    /// Shut an account with no balance left for good
+    Close,
+}
+
+impl AccountStatusChange {
+    fn status(self) -> &'static str {
+        match self {
+            AccountStatusChange::Suspend => "suspended",
+            AccountStatusChange::Reactivate => "active",
+            AccountStatusChange::Close => "closed",
+        }
+    }
+
+    fn action(self) -> &'static str {
+        match self {
+            AccountStatusChange::Suspend => "account.suspended",
+            AccountStatusChange::Reactivate => "account.reactivated",
+            AccountStatusChange::Close => "account.closed",
+        }
+    }
+
+    /// Whether an account in `status` can make this change; closing is final
+    fn applies_to(self, status: &str) -> bool {
+        match self {
+            AccountStatusChange::Suspend => status == "active",
+            AccountStatusChange::Reactivate => status == "suspended",
+            AccountStatusChange::Close => status == "active" || status == "suspended",
+        }
+    }
+}
+
+/// Change the status of any account on an operator's behalf
+///
+/// The account's cached API keys are invalidated so the new status applies to
+/// their next request. Access tokens already issued stay valid until they expire.
+#[instrument(skip(actor), fields(service = "/admin/v1/accounts/:id"))]
+pub async fn set_account_status(
+    account_id: Uuid,
+    change: AccountStatusChange,
+    reason: Option<String>,
+    actor: &AuditActor,
+) -> Response {
+    tracing::info!(
+        account_id = %account_id,
+        change = ?change,
+        admin = ?actor.actor_name,
+        "Changing account status"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            tracing::error!("Database connection pool not initialized");
+            return create_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database_unavailable",
+                "Database connection pool not initialized",
+                None,
+            );
+        }
+    };
+
+    // Connection guard to automatically return connection when scope ends
+    struct ConnectionGuard(Option<sqlx::pool::PoolConnection<sqlx::Postgres>>);
+    impl Drop for ConnectionGuard {
+        fn drop(&mut self) {
+            if let Some(c) = self.0.take() {
+                if let Some(tracker) = POOL_STATE_TRACKER.get() {
+                    tracker.return_connection(c);
+                }
+            }
+        }
+    }
+
+    let mut guard = ConnectionGuard(None);
+    let conn = match tracker.get_connection().await {
+        Ok(c) => {
+            guard.0 = Some(c);
+            guard.0.as_mut().unwrap()
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return create_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "database_error",
+                "Failed to connect to database",
+                None,
+            );
+        }
+    };
+
+    // The status change, its event and its audit entry are committed together
+    if let Err(e) = sqlx::query("BEGIN").execute(&mut **conn).await {
+        tracing::error!(error = %e, "Failed to begin transaction");
+        return create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            "Failed to begin transaction",
+            None,
+        );
+    }
+
+    let previous = match lock_account(account_id, conn).await {
+        Ok(account) => account,
+        Err(e) => {
+            let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+            return e.into_response();
+        }
+    };
+
+    if !change.applies_to(&previous.status) {
+        let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+        return create_error_response(
+            StatusCode::CONFLICT,
+            "invalid_status_transition",
+            &format!(
+                "Account is {} and cannot become {}",
+                previous.status,
+                change.status()
+            ),
+            None,
+        );
+    }
+
+    // Funds are never stranded in a closed account
+    if matches!(change, AccountStatusChange::Close) && previous.balance != 0.0 {
+        let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+        return create_error_response(
+            StatusCode::CONFLICT,
+            "account_has_balance",
+            "Only an account with a zero balance can be closed",
+            None,
+        );
+    }
+
+    let account = match update_account_status(account_id, change.status(), conn).await {
+        Ok(account) => account,
+        Err(e) => {
+            let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+            return create_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "update_failed",
+                &format!("Failed to update account status: {}", e),
+                None,
+            );
+        }
+    };
+
+    let event = match commit_account_update(
+        &previous,
+        &account,
+        change.action(),
+        actor,
+        reason,
+        conn,
+    )
+    .await
+    {
+        Ok(event) => event,
+        Err(response) => return response,
+    };
+
+    // Authentication caches the status with each of the account's keys
+    match get_api_keys_for_account(account.id, conn).await {
+        Ok(api_keys) => invalidate_api_keys(&api_keys.iter().collect::<Vec<_>>()).await,
+        Err(e) => tracing::error!(
+            error = %e,
+            account_id = %account.id,
+            "Failed to load API keys to invalidate after status change"
+        ),
+    }
+    publish_events(&[event]).await;
+
+    tracing::info!(
+        account_id = %account.id,
+        status = %account.status,
+        "Account status changed"
+    );
+
+    (StatusCode::OK, Json(AccountResponse::from(account))).into_response()
+}
+
+/// Record an `account.updated` event and the audit entry of an update, then commit
+/// the open transaction
+/// Rolls back and returns the error response when any step fails
+async fn commit_account_update(
+    previous: &Account,
+    account: &Account,
+    action: &'static str,
+    actor: &AuditActor,
+    reason: Option<String>,
+    conn: &mut PgConnection,
+) -> Result<Event, Response> {
+    let event = match create_event(
+        account.id,
+        "account.updated",
+        account_event_data(account),
+        &mut *conn,
+    )
+    .await
+    {
+        Ok(event) => event,
+        Err(e) => {
+            tracing::error!(
+                error = %e,
+                account_id = %account.id,
+                "Failed to record account event, rolling back"
+            );
+            let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+            return Err(create_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "event_creation_failed",
+                "Failed to record account event, update rolled back",
+                None,
+            ));
+        }
+    };
+
+    let entry = NewAuditEntry::new(actor, Some(account.id), action, "account", account.id)
+        .changed(&account_audit_data(previous), &account_audit_data(account))
+        .reason(reason);
+    if let Err(e) = create_audit_entry(entry, &mut *conn).await {
+        tracing::error!(
+            error = %e,
+            account_id = %account.id,
+            "Failed to write audit entry, rolling back"
+        );
+        let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+        return Err(create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "audit_log_failed",
+            "Failed to write audit entry, update rolled back",
+            None,
+        ));
+    }
+
+    if let Err(e) = sqlx::query("COMMIT").execute(&mut *conn).await {
+        tracing::error!(error = %e, account_id = %account.id, "Failed to commit transaction");
+        let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+        return Err(create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "transaction_commit_failed",
+            "Failed to commit transaction",
+            None,
+        ));
+    }
+
+    Ok(event)
+}
+
+/// Fields of an account tracked by the audit log
+fn account_audit_data(account: &Account) -> serde_json::Value {
+    serde_json::json!({
+        "business_name": account.business_name,
+        "email": account.email,
+        "balance": account.balance,
+        "currency": account.currency,
+        "status": account.status,
+        "require_signed_writes": account.require_signed_writes,
+    })
+}
@@ -15,6 +15,7 @@ use uuid::Uuid;
 pub async fn get_transfer_by_parent_key(
     parent_tx_key: String,
     user_account_id: Uuid,
+    livemode: bool,
 ) -> Result<Vec<TransferResponse>, ServiceError> {
     tracing::info!(parent_tx_key = %parent_tx_key, user_account_id = %user_account_id, "Getting transfer by parent key");
 
@@ -26,9 +27,10 @@ pub async fn get_transfer_by_parent_key(
 
     // Query all transactions with this parent_tx_key
     let transactions: Vec<Transaction> = sqlx::query_as::<_, Transaction>(
-        "SELECT * FROM transactions WHERE parent_tx_key = $1 ORDER BY created_at ASC",
+        "SELECT * FROM transactions WHERE parent_tx_key = $1 AND livemode = $2 ORDER BY created_at ASC",
     )
     .bind(&parent_tx_key)
+    .bind(livemode)
     .fetch_all(&mut *conn)
     .await
     .map_err(|e| {
@@ -72,6 +74,7 @@ pub async fn get_transfer_by_parent_key(
             created_at: txn.created_at.to_rfc3339(),
             idempotency_key: txn.idempotency_key,
             parent_tx_key: txn.parent_tx_key,
+            livemode: txn.livemode,
         })
         .collect();
 
@@ -85,6 +88,7 @@ pub async fn get_transfer_by_parent_key(
 pub async fn get_transfer_by_id(
     transaction_id: Uuid,
     user_account_id: Uuid,
+    livemode: bool,
 ) -> Result<TransferResponse, ServiceError> {
     tracing::info!(transaction_id = %transaction_id, user_account_id = %user_account_id, "Getting transfer by ID");
 
@@ -96,9 +100,10 @@ pub async fn get_transfer_by_id(
 
     // Query transaction by ID
     let transaction: Transaction = sqlx::query_as::<_, Transaction>(
-        "SELECT * FROM transactions WHERE id = $1"
+        "SELECT * FROM transactions WHERE id = $1 AND livemode = $2"
     )
     .bind(transaction_id)
+    .bind(livemode)
     .fetch_one(&mut *conn)
     .await
     .map_err(|e| {
@@ -174,14 +179,79 @@ pub async fn get_transfer_by_id(
         created_at: transaction.created_at.to_rfc3339(),
         idempotency_key: transaction.idempotency_key,
         parent_tx_key: transaction.parent_tx_key,
+        livemode: transaction.livemode,
     })
 }
 
+/// Get a transaction of any account with the other transactions of its group, for operators
+///
+/// # Returns
+/// The transaction and the transfer, debit or credit records sharing its `parent_tx_key`, oldest first
+#[instrument(fields(service = "/admin/v1/transactions/:id"))]
+pub async fn get_transaction_group(
+    transaction_id: Uuid,
+) -> Result<(TransferResponse, Vec<TransferResponse>), ServiceError> {
+    tracing::info!(transaction_id = %transaction_id, "Getting transaction group");
+
+    let tracker = POOL_STATE_TRACKER
+        .get()
+        .ok_or(ServiceError::DatabaseConnectionError)?;
+
+    let mut conn = tracker.get_connection().await?;
+
+    let transactions: Result<Vec<Transaction>, _> = sqlx::query_as::<_, Transaction>(
+        "SELECT * FROM transactions
+         WHERE id = $1
+            OR parent_tx_key = (SELECT parent_tx_key FROM transactions WHERE id = $1)
+         ORDER BY created_at ASC, id ASC",
+    )
+    .bind(transaction_id)
+    .fetch_all(&mut *conn)
+    .await;
+    tracker.return_connection(conn);
+
+    let transactions = transactions.map_err(|e| {
+        tracing::error!(error = %e, transaction_id = %transaction_id, "Failed to fetch transaction group");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    let (mut transaction, mut related) = (None, Vec::new());
+    for txn in transactions {
+        let response = TransferResponse {
+            id: txn.id,
+            transfer_type: format!("{:?}", txn.transaction_type).to_lowercase(),
+            from_account: txn.from_account_id,
+            to_account: txn.to_account_id,
+            amount: txn.amount,
+            currency: txn.currency,
+            status: format!("{:?}", txn.status).to_lowercase(),
+            description: txn.description,
+            created_at: txn.created_at.to_rfc3339(),
+            idempotency_key: txn.idempotency_key,
+            parent_tx_key: txn.parent_tx_key,
+            livemode: txn.livemode,
+        };
+        if response.id == transaction_id {
+            transaction = Some(response);
+        } else {
+            related.push(response);
+        }
+    }
+
+    match transaction {
+        Some(transaction) => Ok((transaction, related)),
+        None => Err(ServiceError::TransactionNotFound(
+            transaction_id.to_string(),
+        )),
+    }
+}
+
 /// List all Transfer-type transactions for the authenticated user
 /// Returns transfers where user is either from_account OR to_account
 #[instrument(fields(service = "/api/v1/transfer/list"))]
 pub async fn list_transfers(
     user_account_id: Uuid,
+    livemode: bool,
     limit: Option<i32>,
     offset: Option<i32>,
 ) -> Result<TransferListResponse, ServiceError> {
@@ -206,12 +276,14 @@ pub async fn list_transfers(
         "SELECT * FROM transactions 
          WHERE transaction_type = 'transfer' 
          AND (from_account_id = $1 OR to_account_id = $1)
+         AND livemode = $4
          ORDER BY created_at DESC 
          LIMIT $2 OFFSET $3",
     )
     .bind(user_account_id)
     .bind(limit as i64)
     .bind(offset as i64)
+    .bind(livemode)
     .fetch_all(&mut *conn)
     .await
     .map_err(|e| {
@@ -223,9 +295,11 @@ pub async fn list_transfers(
     let count_result: (i64,) = sqlx::query_as(
         "SELECT COUNT(*) FROM transactions 
          WHERE transaction_type = 'transfer' 
-         AND (from_account_id = $1 OR to_account_id = $1)",
+         AND (from_account_id = $1 OR to_account_id = $1)
+         AND livemode = $2",
     )
     .bind(user_account_id)
+    .bind(livemode)
     .fetch_one(&mut *conn)
     .await
     .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
@@ -244,6 +318,7 @@ pub async fn list_transfers(
             created_at: txn.created_at.to_rfc3339(),
             idempotency_key: txn.idempotency_key,
             parent_tx_key: txn.parent_tx_key,
+            livemode: txn.livemode,
         })
         .collect();
 
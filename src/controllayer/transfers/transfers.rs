@@ -1,16 +1,20 @@
 use crate::{
     datalayer::{
         CRUD::{
+            events::{Event, create_event},
             transaction::TransactionBuilder,
             types::{TransactionStatus, TransactionType},
             webhook::get_active_webhooks_for_account,
         },
         db_ops::constants::POOL_STATE_TRACKER,
     },
-    errors::errors::create_error_response,
+    errors::errors::{ServiceError, create_error_response},
     handlers::transfer::{TransferRequest, TransferResponse},
     middleware::auth::AuthenticatedApiKey,
-    services::WebhookDispatcher,
+    services::{
+        WebhookDispatcher,
+        event_bus::{publish_events, transaction_event_data},
+    },
 };
 use axum::{
     Json,
@@ -216,6 +220,9 @@ pub async fn execute_transfer(
 
     tracing::info!("Started database transaction for transfer");
 
+    // Events recorded inside the transaction, published to streams once it commits
+    let mut events: Vec<Event> = Vec::new();
+
     // Generate a transaction group ID that will be shared by all related transactions
     // This allows us to group Transfer, Debit, and Credit records together
     let transaction_group_id = format!("txgroup_{}", Uuid::new_v4());
@@ -228,6 +235,7 @@ pub async fn execute_transfer(
     // For Transfer type: Create parent transfer record first
     let parent_tx_id = if matches!(transfer_type, TransactionType::Transfer) {
         let mut builder = TransactionBuilder::new()
+            .livemode(auth_info.livemode)
             .transaction_type(transfer_type.clone())
             .amount(amount)
             .currency(currency.clone())
@@ -266,6 +274,10 @@ pub async fn execute_transfer(
                 tracing::error!(error = %e, "Failed to create parent transfer record");
                 let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
 
+                if let ServiceError::AccountInactive(_) = e {
+                    return e.into_response();
+                }
+
                 if e.to_string().contains("duplicate") || e.to_string().contains("23505") {
                     return create_error_response(
                         StatusCode::CONFLICT,
@@ -299,6 +311,7 @@ pub async fn execute_transfer(
         let parent_key = transaction_group_id.clone();
 
         let mut builder = TransactionBuilder::new()
+            .livemode(auth_info.livemode)
             .from_account_id(from_acc)
             .transaction_type(TransactionType::Debit)
             .amount(amount)
@@ -328,11 +341,29 @@ pub async fn execute_transfer(
                     "Debit record created successfully"
                 );
 
-                // Dispatch webhook for debit transaction
-                if let Ok(webhooks) = get_active_webhooks_for_account(from_acc, conn).await {
-                    let dispatcher = WebhookDispatcher::new();
-                    for webhook in webhooks {
-                        dispatcher.dispatch_debit_webhook(webhook, debit_txn.clone());
+                // Record the event in the same transaction so the log matches committed state
+                match create_event(
+                    from_acc,
+                    "transaction.debited",
+                    transaction_event_data(&debit_txn),
+                    conn,
+                )
+                .await
+                {
+                    Ok(event) => events.push(event),
+                    Err(e) => {
+                        tracing::error!(
+                            error = %e,
+                            account_id = %from_acc,
+                            "Failed to record debit event, rolling back"
+                        );
+                        let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+                        return create_error_response(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            "event_creation_failed",
+                            "Failed to record transfer event, transfer rolled back",
+                            None,
+                        );
                     }
                 }
             }
@@ -365,6 +396,7 @@ pub async fn execute_transfer(
         let parent_key = transaction_group_id.clone();
 
         let mut builder = TransactionBuilder::new()
+            .livemode(auth_info.livemode)
             .to_account_id(to_acc)
             .transaction_type(TransactionType::Credit)
             .amount(amount)
@@ -394,11 +426,29 @@ pub async fn execute_transfer(
                     "Credit record created successfully"
                 );
 
-                // Dispatch webhook for credit transaction
-                if let Ok(webhooks) = get_active_webhooks_for_account(to_acc, conn).await {
-                    let dispatcher = WebhookDispatcher::new();
-                    for webhook in webhooks {
-                        dispatcher.dispatch_credit_webhook(webhook, credit_txn.clone());
+                // Record the event in the same transaction so the log matches committed state
+                match create_event(
+                    to_acc,
+                    "transaction.credited",
+                    transaction_event_data(&credit_txn),
+                    conn,
+                )
+                .await
+                {
+                    Ok(event) => events.push(event),
+                    Err(e) => {
+                        tracing::error!(
+                            error = %e,
+                            account_id = %to_acc,
+                            "Failed to record credit event, rolling back"
+                        );
+                        let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+                        return create_error_response(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            "event_creation_failed",
+                            "Failed to record transfer event, transfer rolled back",
+                            None,
+                        );
                     }
                 }
             }
@@ -409,6 +459,9 @@ pub async fn execute_transfer(
                     "Failed to create credit record, rolling back"
                 );
                 let _ = sqlx::query("ROLLBACK").execute(&mut **conn).await;
+                if let ServiceError::AccountInactive(_) = e {
+                    return e.into_response();
+                }
                 return create_error_response(
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "credit_creation_failed",
@@ -423,6 +476,7 @@ pub async fn execute_transfer(
     match sqlx::query("COMMIT").execute(&mut **conn).await {
         Ok(_) => {
             tracing::info!("Transaction committed successfully - transfer completed atomically");
+            publish_events(&events).await;
         }
         Err(e) => {
             tracing::error!(error = %e, "Failed to commit transaction");
@@ -436,6 +490,16 @@ pub async fn execute_transfer(
         }
     }
 
+    // Webhooks are queued only once the transfer is committed, in the order of its events
+    let dispatcher = WebhookDispatcher::new();
+    for event in &events {
+        if let Ok(webhooks) = get_active_webhooks_for_account(event.account_id, conn).await {
+            for webhook in webhooks {
+                dispatcher.dispatch_event(webhook, event.clone());
+            }
+        }
+    }
+
     // Prepare response - use parent_tx_id if it exists, otherwise we need to get the created transaction ID
     // For simplicity, let's return a success response with the transfer details
     let response_id = parent_tx_id.unwrap_or_else(|| Uuid::new_v4()); // This is a placeholder
@@ -452,6 +516,7 @@ pub async fn execute_transfer(
         created_at: chrono::Utc::now().to_rfc3339(),
         idempotency_key,
         parent_tx_key: transaction_group_id,
+        livemode: auth_info.livemode,
     };
 
     tracing::info!(
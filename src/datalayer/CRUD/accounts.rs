@@ -31,6 +31,8 @@ pub struct AccountBuilder {
     balance: Option<f64>,
     status: Option<String>,
     metadata: Option<serde_json::Value>,
+    livemode: Option<bool>,
+    require_signed_writes: Option<bool>,
     id: Option<Uuid>,
     // Make list that you expect to return
     get_business_name: Option<bool>,
@@ -39,6 +41,8 @@ pub struct AccountBuilder {
     get_balance: Option<bool>,
     get_status: Option<bool>,
     get_metadata: Option<bool>,
+    get_livemode: Option<bool>,
+    get_require_signed_writes: Option<bool>,
     get_id: Option<bool>,
     get_created_at: Option<bool>,
     get_updated_at: Option<bool>,
@@ -53,6 +57,8 @@ impl Default for AccountBuilder {
             balance: None,
             status: None,
             metadata: None,
+            livemode: None,
+            require_signed_writes: None,
             id: None,
             get_business_name: None,
             get_email: None,
@@ -60,6 +66,8 @@ impl Default for AccountBuilder {
             get_balance: None,
             get_status: None,
             get_metadata: None,
+            get_livemode: None,
+            get_require_signed_writes: None,
             get_id: None,
             get_created_at: None,
             get_updated_at: None,
@@ -102,6 +110,18 @@ impl AccountBuilder {
         self
     }
 
+    /// Mode of the account; reads and updates only match accounts of this mode
+    pub fn livemode(mut self, livemode: bool) -> Self {
+        self.livemode = Some(livemode);
+        self
+    }
+
+    /// Whether write scopes need signed requests; only applied by `update`
+    pub fn require_signed_writes(mut self, require_signed_writes: bool) -> Self {
+        self.require_signed_writes = Some(require_signed_writes);
+        self
+    }
+
     pub fn id(mut self, id: Uuid) -> Self {
         self.id = Some(id);
         self
@@ -142,6 +162,16 @@ impl AccountBuilder {
         self
     }
 
+    pub fn expect_livemode(mut self) -> Self {
+        self.get_livemode = Some(true);
+        self
+    }
+
+    pub fn expect_require_signed_writes(mut self) -> Self {
+        self.get_require_signed_writes = Some(true);
+        self
+    }
+
     pub fn expect_created_at(mut self) -> Self {
         self.get_created_at = Some(true);
         self
@@ -178,6 +208,7 @@ impl AccountBuilder {
         let balance = Some(0);
         let status = self.status.clone().unwrap_or_else(|| "active".to_string());
         let metadata = self.metadata;
+        let livemode = self.livemode.unwrap_or(true);
 
         // Validate balance is non-negative
         if balance != Some(0) {
@@ -194,6 +225,8 @@ impl AccountBuilder {
         let get_balance = self.get_balance.unwrap_or(false);
         let get_status = self.get_status.unwrap_or(false);
         let get_metadata = self.get_metadata.unwrap_or(false);
+        let get_livemode = self.get_livemode.unwrap_or(true);
+        let get_require_signed_writes = self.get_require_signed_writes.unwrap_or(true);
         // Default timestamps to true because Account struct needs them?
         // Or should we mandate explicit expect?
         // If we want create() -> Result<Account>, we usually need all fields.
@@ -215,6 +248,7 @@ impl AccountBuilder {
                 .value(Accounts::Balance, balance)
                 .value(Accounts::Status, status.clone())
                 .value(Accounts::Metadata, metadata.clone())
+                .value(Accounts::Livemode, livemode)
                 .value(Accounts::CreatedAt, chrono::Utc::now())
                 .value(Accounts::UpdatedAt, chrono::Utc::now());
 
@@ -239,6 +273,12 @@ impl AccountBuilder {
             if get_metadata {
                 insert = insert.returning(Accounts::Metadata);
             }
+            if get_livemode {
+                insert = insert.returning(Accounts::Livemode);
+            }
+            if get_require_signed_writes {
+                insert = insert.returning(Accounts::RequireSignedWrites);
+            }
             if get_created_at {
                 insert = insert.returning(Accounts::CreatedAt);
             }
@@ -290,7 +330,7 @@ impl AccountBuilder {
             }
         };
 
-        if Self::check_exists(&business_name, &email, db_conn)
+        if Self::check_exists(&business_name, &email, livemode, db_conn)
             .await
             .unwrap_or(false)
         {
@@ -362,6 +402,8 @@ impl AccountBuilder {
         let get_balance = self.get_balance.unwrap_or(false);
         let get_status = self.get_status.unwrap_or(false);
         let get_metadata = self.get_metadata.unwrap_or(false);
+        let get_livemode = self.get_livemode.unwrap_or(true);
+        let get_require_signed_writes = self.get_require_signed_writes.unwrap_or(true);
         let get_created_at = self.get_created_at.unwrap_or(false);
         let get_updated_at = self.get_updated_at.unwrap_or(false);
 
@@ -400,6 +442,9 @@ impl AccountBuilder {
             let mut update = FluentUpdate::table(Accounts::Table)
                 .value(Accounts::UpdatedAt, chrono::Utc::now())
                 .filter(Accounts::Id, id);
+            if let Some(livemode) = self.livemode {
+                update = update.filter(Accounts::Livemode, livemode);
+            }
 
             // Only update fields that are provided
             if let Some(business_name) = self.business_name.clone() {
@@ -414,6 +459,9 @@ impl AccountBuilder {
             if let Some(metadata) = self.metadata.clone() {
                 update = update.value(Accounts::Metadata, metadata);
             }
+            if let Some(require_signed_writes) = self.require_signed_writes {
+                update = update.value(Accounts::RequireSignedWrites, require_signed_writes);
+            }
 
             // Only update balance and currency if balance is provided
             if self.balance.is_some() {
@@ -444,6 +492,12 @@ impl AccountBuilder {
             if get_metadata {
                 update = update.returning(Accounts::Metadata);
             }
+            if get_livemode {
+                update = update.returning(Accounts::Livemode);
+            }
+            if get_require_signed_writes {
+                update = update.returning(Accounts::RequireSignedWrites);
+            }
             if get_created_at {
                 update = update.returning(Accounts::CreatedAt);
             }
@@ -487,6 +541,8 @@ impl AccountBuilder {
         let get_balance = self.get_balance.unwrap_or(false);
         let get_status = self.get_status.unwrap_or(false);
         let get_metadata = self.get_metadata.unwrap_or(false);
+        let get_livemode = self.get_livemode.unwrap_or(true);
+        let get_require_signed_writes = self.get_require_signed_writes.unwrap_or(true);
         let get_created_at = self.get_created_at.unwrap_or(true);
         let get_updated_at = self.get_updated_at.unwrap_or(true);
 
@@ -515,6 +571,12 @@ impl AccountBuilder {
             if get_metadata {
                 select = select.column(Accounts::Metadata);
             }
+            if get_livemode {
+                select = select.column(Accounts::Livemode);
+            }
+            if get_require_signed_writes {
+                select = select.column(Accounts::RequireSignedWrites);
+            }
             if get_created_at {
                 select = select.column(Accounts::CreatedAt);
             }
@@ -541,6 +603,9 @@ impl AccountBuilder {
             if let Some(status) = self.status.as_ref() {
                 select = select.filter(Accounts::Status, status.clone());
             }
+            if let Some(livemode) = self.livemode {
+                select = select.filter(Accounts::Livemode, livemode);
+            }
             // Metadata filter usually not simple equality, skipping for now or assumed exact match stringified?
             // SeaQuery Value::Json can work.
             if let Some(metadata) = self.metadata.as_ref() {
@@ -597,19 +662,24 @@ impl AccountBuilder {
         result
     }
 
-    /// Check if an account exists with the given business name OR email
+    /// Check if an account of the given mode exists with the given business name OR email
     pub async fn check_exists(
         business_name: &str,
         email: &str,
+        livemode: bool,
         conn: &mut sqlx::PgConnection,
     ) -> Result<bool, ServiceError> {
         let (sql, values) = Query::select()
             .column(Accounts::Id)
             .from(Accounts::Table)
             .cond_where(
-                Cond::any()
-                    .add(Expr::col(Accounts::BusinessName).eq(business_name))
-                    .add(Expr::col(Accounts::Email).eq(email)),
+                Cond::all()
+                    .add(Expr::col(Accounts::Livemode).eq(livemode))
+                    .add(
+                        Cond::any()
+                            .add(Expr::col(Accounts::BusinessName).eq(business_name))
+                            .add(Expr::col(Accounts::Email).eq(email)),
+                    ),
             )
             .build(PostgresQueryBuilder);
 
@@ -626,6 +696,89 @@ impl AccountBuilder {
     }
 }
 
+/// Filters of an operator's account search
+pub struct AccountSearch {
+    /// Matches the id exactly, or part of the business name or email in any case
+    pub query: Option<String>,
+    pub status: Option<String>,
+    pub livemode: Option<bool>,
+    pub cursor: Option<Uuid>,
+    pub limit: i64,
+}
+
+/// Search accounts of every business, newest first
+pub async fn search_accounts(
+    search: &AccountSearch,
+    conn: &mut sqlx::PgConnection,
+) -> Result<Vec<Account>, ServiceError> {
+    sqlx::query_as::<_, Account>(
+        r#"
+        SELECT * FROM accounts
+        WHERE (
+            $1::varchar IS NULL
+            OR id::text = lower($1)
+            OR strpos(lower(business_name), lower($1)) > 0
+            OR strpos(lower(email), lower($1)) > 0
+          )
+          AND ($2::varchar IS NULL OR status = $2)
+          AND ($3::boolean IS NULL OR livemode = $3)
+          AND (
+            $4::uuid IS NULL OR (created_at, id) < (
+                SELECT created_at, id FROM accounts WHERE id = $4
+            )
+          )
+        ORDER BY created_at DESC, id DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(&search.query)
+    .bind(&search.status)
+    .bind(search.livemode)
+    .bind(search.cursor)
+    .bind(search.limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to search accounts");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Read an account and lock it until the surrounding transaction ends
+pub async fn lock_account(
+    account_id: Uuid,
+    conn: &mut sqlx::PgConnection,
+) -> Result<Account, ServiceError> {
+    sqlx::query_as::<_, Account>("SELECT * FROM accounts WHERE id = $1 FOR UPDATE")
+        .bind(account_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => ServiceError::AccountNotFound(account_id.to_string()),
+            _ => {
+                tracing::error!(error = %e, account_id = %account_id, "Failed to lock account");
+                ServiceError::DatabaseError(e.to_string())
+            }
+        })
+}
+
+/// Set the status of an account: `active`, `suspended` or `closed`
+pub async fn update_account_status(
+    account_id: Uuid,
+    status: &str,
+    conn: &mut sqlx::PgConnection,
+) -> Result<Account, ServiceError> {
+    sqlx::query_as::<_, Account>("UPDATE accounts SET status = $2 WHERE id = $1 RETURNING *")
+        .bind(account_id)
+        .bind(status)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, account_id = %account_id, "Failed to update account status");
+            ServiceError::DatabaseError(e.to_string())
+        })
+}
+
 /// Helper macro to implement binding for different query types
 macro_rules! impl_bind_values {
     ($func_name:ident, $query_type:ty) => {
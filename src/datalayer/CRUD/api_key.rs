@@ -1,4 +1,4 @@
-use super::types::ApiKey;
+use super::types::{ApiKey, ApiKeyDailyUsage, ApiKeyLastUse};
 use crate::datalayer::CRUD::accounts::AccountBuilder;
 use crate::datalayer::CRUD::sql_generator::sql_generator::{
     FluentInsert, FluentSelect, FluentUpdate,
@@ -7,13 +7,19 @@ use crate::datalayer::CRUD::sql_generator::sql_generator::{
 use crate::datalayer::CRUD::types::ApiKeys;
 use crate::datalayer::db_ops::constants::POOL_STATE_TRACKER;
 use crate::errors::errors::ServiceError;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use sea_query::Value;
 use sqlx::FromRow;
+use sqlx::PgConnection;
 use sqlx::Postgres;
 use sqlx::pool::PoolConnection;
 use uuid::Uuid;
 
+/// Columns of an API key as loaded for authentication, with the account settings it needs
+const AUTH_COLUMNS: &str = "api_keys.*, \
+     accounts.require_signed_writes AS account_requires_signed_writes, \
+     accounts.status AS account_status";
+
 /// Builder for creating and managing API keys
 ///
 /// ## Security Note
@@ -32,6 +38,7 @@ pub struct ApiKeyBuilder {
     rate_limit_per_minute: Option<i32>,
     rate_limit_per_hour: Option<i32>,
     permissions: Option<serde_json::Value>,
+    allowed_ips: Option<serde_json::Value>,
     last_used_at: Option<DateTime<Utc>>,
     expires_at: Option<DateTime<Utc>>,
     revoked_at: Option<DateTime<Utc>>,
@@ -45,6 +52,7 @@ pub struct ApiKeyBuilder {
     get_rate_limit_per_minute: Option<bool>,
     get_rate_limit_per_hour: Option<bool>,
     get_permissions: Option<bool>,
+    get_allowed_ips: Option<bool>,
     get_last_used_at: Option<bool>,
     get_expires_at: Option<bool>,
     get_created_at: Option<bool>,
@@ -63,6 +71,7 @@ impl Default for ApiKeyBuilder {
             rate_limit_per_minute: None,
             rate_limit_per_hour: None,
             permissions: None,
+            allowed_ips: None,
             last_used_at: None,
             expires_at: None,
             revoked_at: None,
@@ -75,6 +84,7 @@ impl Default for ApiKeyBuilder {
             get_rate_limit_per_minute: None,
             get_rate_limit_per_hour: None,
             get_permissions: None,
+            get_allowed_ips: None,
             get_last_used_at: None,
             get_expires_at: None,
             get_created_at: None,
@@ -134,6 +144,11 @@ impl ApiKeyBuilder {
         self
     }
 
+    pub fn allowed_ips(mut self, allowed_ips: serde_json::Value) -> Self {
+        self.allowed_ips = Some(allowed_ips);
+        self
+    }
+
     pub fn last_used_at(mut self, last_used_at: DateTime<Utc>) -> Self {
         self.last_used_at = Some(last_used_at);
         self
@@ -195,6 +210,11 @@ impl ApiKeyBuilder {
         self
     }
 
+    pub fn expect_allowed_ips(mut self) -> Self {
+        self.get_allowed_ips = Some(true);
+        self
+    }
+
     pub fn expect_last_used_at(mut self) -> Self {
         self.get_last_used_at = Some(true);
         self
@@ -245,6 +265,7 @@ impl ApiKeyBuilder {
         let get_name = self.get_name.unwrap_or(false);
         let get_status = self.get_status.unwrap_or(false);
         let get_permissions = self.get_permissions.unwrap_or(false);
+        let get_allowed_ips = self.get_allowed_ips.unwrap_or(false);
         let get_last_used_at = self.get_last_used_at.unwrap_or(false);
         let get_expires_at = self.get_expires_at.unwrap_or(false);
         let get_created_at = self.get_created_at.unwrap_or(true);
@@ -259,6 +280,7 @@ impl ApiKeyBuilder {
                 .value(ApiKeys::Name, self.name.clone())
                 .value(ApiKeys::Status, status.clone())
                 .value(ApiKeys::Permissions, permissions.clone())
+                .value(ApiKeys::AllowedIps, self.allowed_ips.clone())
                 .value(ApiKeys::LastUsedAt, self.last_used_at)
                 .value(ApiKeys::ExpiresAt, self.expires_at)
                 .value(ApiKeys::CreatedAt, Utc::now())
@@ -286,6 +308,9 @@ impl ApiKeyBuilder {
             if get_permissions {
                 insert = insert.returning(ApiKeys::Permissions);
             }
+            if get_allowed_ips {
+                insert = insert.returning(ApiKeys::AllowedIps);
+            }
             if get_last_used_at {
                 insert = insert.returning(ApiKeys::LastUsedAt);
             }
@@ -414,6 +439,7 @@ impl ApiKeyBuilder {
         let get_name = self.get_name.unwrap_or(false);
         let get_status = self.get_status.unwrap_or(false);
         let get_permissions = self.get_permissions.unwrap_or(false);
+        let get_allowed_ips = self.get_allowed_ips.unwrap_or(false);
         let get_last_used_at = self.get_last_used_at.unwrap_or(false);
         let get_expires_at = self.get_expires_at.unwrap_or(false);
         let get_created_at = self.get_created_at.unwrap_or(false);
@@ -424,6 +450,7 @@ impl ApiKeyBuilder {
                 .value(ApiKeys::Name, self.name.clone())
                 .value(ApiKeys::Status, self.status.clone())
                 .value(ApiKeys::Permissions, self.permissions.clone())
+                .value(ApiKeys::AllowedIps, self.allowed_ips.clone())
                 .value(ApiKeys::LastUsedAt, self.last_used_at)
                 .value(ApiKeys::ExpiresAt, self.expires_at)
                 .value(ApiKeys::RevokedAt, self.revoked_at)
@@ -451,6 +478,9 @@ impl ApiKeyBuilder {
             if get_permissions {
                 update = update.returning(ApiKeys::Permissions);
             }
+            if get_allowed_ips {
+                update = update.returning(ApiKeys::AllowedIps);
+            }
             if get_last_used_at {
                 update = update.returning(ApiKeys::LastUsedAt);
             }
@@ -492,6 +522,7 @@ impl ApiKeyBuilder {
         let get_name = self.get_name.unwrap_or(false);
         let get_status = self.get_status.unwrap_or(false);
         let get_permissions = self.get_permissions.unwrap_or(false);
+        let get_allowed_ips = self.get_allowed_ips.unwrap_or(false);
         let get_last_used_at = self.get_last_used_at.unwrap_or(false);
         let get_expires_at = self.get_expires_at.unwrap_or(false);
         let get_created_at = self.get_created_at.unwrap_or(true);
@@ -522,6 +553,9 @@ impl ApiKeyBuilder {
             if get_permissions {
                 select = select.column(ApiKeys::Permissions);
             }
+            if get_allowed_ips {
+                select = select.column(ApiKeys::AllowedIps);
+            }
             if get_last_used_at {
                 select = select.column(ApiKeys::LastUsedAt);
             }
@@ -606,18 +640,419 @@ impl ApiKeyBuilder {
             .id
             .ok_or_else(|| ServiceError::ValidationError("Missing ID for revoke".to_string()))?;
 
+        // Every column is returned: `ApiKey` is decoded from the full row
         ApiKeyBuilder::new()
             .id(id)
             .status("revoked".to_string())
             .revoked_at(Utc::now())
             .expect_id()
+            .expect_account_id()
+            .expect_key_hash()
+            .expect_key_prefix()
+            .expect_name()
             .expect_status()
+            .expect_permissions()
+            .expect_allowed_ips()
+            .expect_last_used_at()
+            .expect_expires_at()
+            .expect_created_at()
             .expect_revoked_at()
             .update(conn)
             .await
     }
 }
 
+/// Get the account's API keys, newest first
+pub async fn get_api_keys_for_account(
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Vec<ApiKey>, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(
+        "SELECT * FROM api_keys WHERE account_id = $1 ORDER BY created_at DESC, id DESC",
+    )
+    .bind(account_id)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to fetch API keys");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Get an API key, verifying it belongs to the account
+pub async fn get_api_key_for_account(
+    api_key_id: Uuid,
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<ApiKey, ServiceError> {
+    sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = $1 AND account_id = $2")
+        .bind(api_key_id)
+        .bind(account_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => ServiceError::ApiKeyNotFound(api_key_id.to_string()),
+            _ => {
+                tracing::error!(error = %e, api_key_id = %api_key_id, "Failed to fetch API key");
+                ServiceError::DatabaseError(e.to_string())
+            }
+        })
+}
+
+/// Lock the account's usable API keys and return their ids
+///
+/// Call inside a transaction before revoking a key, so two concurrent revocations
+/// cannot both see another key left and take away the last one together.
+pub async fn lock_active_api_keys(
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Vec<Uuid>, ServiceError> {
+    sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT id FROM api_keys
+        WHERE account_id = $1
+          AND status = 'active'
+          AND revoked_at IS NULL
+          AND (expires_at IS NULL OR expires_at > NOW())
+        ORDER BY id
+        FOR UPDATE
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to lock API keys");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Let an API key expire at `expires_at`, unless it already expires earlier
+pub async fn expire_api_key(
+    api_key_id: Uuid,
+    expires_at: DateTime<Utc>,
+    conn: &mut PgConnection,
+) -> Result<ApiKey, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(
+        r#"
+        UPDATE api_keys
+        SET expires_at = LEAST(COALESCE(expires_at, $2), $2)
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(api_key_id)
+    .bind(expires_at)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, api_key_id = %api_key_id, "Failed to set API key expiry");
+        match e {
+            sqlx::Error::RowNotFound => ServiceError::ApiKeyNotFound(api_key_id.to_string()),
+            _ => ServiceError::DatabaseError(e.to_string()),
+        }
+    })
+}
+
+/// Replace the IP allowlist of one of the account's keys; `None` clears it
+pub async fn set_api_key_allowed_ips(
+    api_key_id: Uuid,
+    account_id: Uuid,
+    allowed_ips: Option<serde_json::Value>,
+    conn: &mut PgConnection,
+) -> Result<ApiKey, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(
+        r#"
+        UPDATE api_keys
+        SET allowed_ips = $3
+        WHERE id = $1 AND account_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(api_key_id)
+    .bind(account_id)
+    .bind(allowed_ips)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, api_key_id = %api_key_id, "Failed to set API key allowlist");
+        match e {
+            sqlx::Error::RowNotFound => ServiceError::ApiKeyNotFound(api_key_id.to_string()),
+            _ => ServiceError::DatabaseError(e.to_string()),
+        }
+    })
+}
+
+/// Store a new signing secret on one of the account's keys, replacing any previous one
+pub async fn set_api_key_signing_secret(
+    api_key_id: Uuid,
+    account_id: Uuid,
+    encrypted_signing_secret: &str,
+    signing_secret_key_id: &str,
+    conn: &mut PgConnection,
+) -> Result<ApiKey, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(
+        r#"
+        UPDATE api_keys
+        SET encrypted_signing_secret = $3, signing_secret_key_id = $4
+        WHERE id = $1 AND account_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(api_key_id)
+    .bind(account_id)
+    .bind(encrypted_signing_secret)
+    .bind(signing_secret_key_id)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, api_key_id = %api_key_id, "Failed to set API key signing secret");
+        match e {
+            sqlx::Error::RowNotFound => ServiceError::ApiKeyNotFound(api_key_id.to_string()),
+            _ => ServiceError::DatabaseError(e.to_string()),
+        }
+    })
+}
+
+/// Get a batch of API keys whose signing secret is wrapped with another master key than `key_id`
+pub async fn get_api_keys_with_other_signing_key(
+    key_id: &str,
+    after_id: Option<Uuid>,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<ApiKey>, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(
+        r#"
+        SELECT * FROM api_keys
+        WHERE signing_secret_key_id <> $1
+          AND ($2::uuid IS NULL OR id > $2)
+        ORDER BY id
+        LIMIT $3
+        "#,
+    )
+    .bind(key_id)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to fetch API keys for signing secret re-wrap");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Replace a key's encrypted signing secret, if it is still wrapped with `previous_key_id`
+///
+/// # Returns
+/// The updated key, or `None` if its secret changed meanwhile
+pub async fn update_api_key_signing_secret(
+    api_key_id: Uuid,
+    previous_key_id: &str,
+    encrypted_signing_secret: &str,
+    signing_secret_key_id: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<ApiKey>, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(
+        r#"
+        UPDATE api_keys
+        SET encrypted_signing_secret = $3, signing_secret_key_id = $4
+        WHERE id = $1 AND signing_secret_key_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(api_key_id)
+    .bind(previous_key_id)
+    .bind(encrypted_signing_secret)
+    .bind(signing_secret_key_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, api_key_id = %api_key_id, "Failed to update API key signing secret");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Get the API key stored under any of these hashes
+///
+/// A key has exactly one stored hash; several are tried while hashes migrate
+/// between peppers.
+pub async fn get_api_key_by_hashes(
+    key_hashes: &[String],
+    conn: &mut PgConnection,
+) -> Result<ApiKey, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(&format!(
+        "SELECT {} FROM api_keys JOIN accounts ON accounts.id = api_keys.account_id \
+         WHERE api_keys.key_hash = ANY($1)",
+        AUTH_COLUMNS
+    ))
+    .bind(key_hashes)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => ServiceError::ApiKeyNotFound("by hash".to_string()),
+        _ => {
+            tracing::error!(error = %e, "Failed to fetch API key by hash");
+            ServiceError::DatabaseError(e.to_string())
+        }
+    })
+}
+
+/// Get an API key by id for authenticating a signed request
+pub async fn get_api_key_for_auth(
+    api_key_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<ApiKey, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(&format!(
+        "SELECT {} FROM api_keys JOIN accounts ON accounts.id = api_keys.account_id \
+         WHERE api_keys.id = $1",
+        AUTH_COLUMNS
+    ))
+    .bind(api_key_id)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => ServiceError::ApiKeyNotFound(api_key_id.to_string()),
+        _ => {
+            tracing::error!(error = %e, api_key_id = %api_key_id, "Failed to fetch API key");
+            ServiceError::DatabaseError(e.to_string())
+        }
+    })
+}
+
+/// Replace the stored hash of an API key, unless it changed meanwhile
+///
+/// # Returns
+/// The updated key, or `None` if its hash was no longer `old_hash`
+pub async fn rehash_api_key(
+    api_key_id: Uuid,
+    old_hash: &str,
+    new_hash: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<ApiKey>, ServiceError> {
+    sqlx::query_as::<_, ApiKey>(&format!(
+        "UPDATE api_keys SET key_hash = $3 FROM accounts \
+         WHERE api_keys.id = $1 AND api_keys.key_hash = $2 AND accounts.id = api_keys.account_id \
+         RETURNING {}",
+        AUTH_COLUMNS
+    ))
+    .bind(api_key_id)
+    .bind(old_hash)
+    .bind(new_hash)
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, api_key_id = %api_key_id, "Failed to re-hash API key");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Store the latest use of each API key, keeping a later use already stored
+pub async fn record_api_key_last_use(
+    uses: &[ApiKeyLastUse],
+    conn: &mut PgConnection,
+) -> Result<(), ServiceError> {
+    let ids: Vec<Uuid> = uses.iter().map(|u| u.api_key_id).collect();
+    let used_at: Vec<DateTime<Utc>> = uses.iter().map(|u| u.used_at).collect();
+    let ips: Vec<Option<String>> = uses.iter().map(|u| u.ip.clone()).collect();
+    let user_agents: Vec<Option<String>> = uses.iter().map(|u| u.user_agent.clone()).collect();
+
+    sqlx::query(
+        r#"
+        UPDATE api_keys AS k
+        SET last_used_at = u.used_at,
+            last_used_ip = u.ip,
+            last_user_agent = u.user_agent
+        FROM UNNEST($1::uuid[], $2::timestamptz[], $3::text[], $4::text[])
+            AS u(id, used_at, ip, user_agent)
+        WHERE k.id = u.id
+          AND (k.last_used_at IS NULL OR k.last_used_at < u.used_at)
+        "#,
+    )
+    .bind(ids)
+    .bind(used_at)
+    .bind(ips)
+    .bind(user_agents)
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, keys = uses.len(), "Failed to record API key last use");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(())
+}
+
+/// Add request and IP rejection counts to the daily usage of API keys
+///
+/// Counts for keys that no longer exist are dropped.
+pub async fn add_api_key_daily_usage(
+    usage: &[ApiKeyDailyUsage],
+    conn: &mut PgConnection,
+) -> Result<(), ServiceError> {
+    let ids: Vec<Uuid> = usage.iter().map(|u| u.api_key_id).collect();
+    let dates: Vec<NaiveDate> = usage.iter().map(|u| u.usage_date).collect();
+    let counts: Vec<i64> = usage.iter().map(|u| u.request_count).collect();
+    let ip_rejected: Vec<i64> = usage.iter().map(|u| u.ip_rejected_count).collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO api_key_usage_daily (api_key_id, usage_date, request_count, ip_rejected_count)
+        SELECT u.id, u.usage_date, u.request_count, u.ip_rejected_count
+        FROM UNNEST($1::uuid[], $2::date[], $3::bigint[], $4::bigint[])
+            AS u(id, usage_date, request_count, ip_rejected_count)
+        JOIN api_keys k ON k.id = u.id
+        ON CONFLICT (api_key_id, usage_date)
+        DO UPDATE SET
+            request_count = api_key_usage_daily.request_count + EXCLUDED.request_count,
+            ip_rejected_count = api_key_usage_daily.ip_rejected_count + EXCLUDED.ip_rejected_count
+        "#,
+    )
+    .bind(ids)
+    .bind(dates)
+    .bind(counts)
+    .bind(ip_rejected)
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, rows = usage.len(), "Failed to add API key daily usage");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(())
+}
+
+/// Daily usage of an API key from `from` to `to` inclusive, newest first
+///
+/// Days without requests are included with a count of 0.
+pub async fn get_api_key_daily_usage(
+    api_key_id: Uuid,
+    from: NaiveDate,
+    to: NaiveDate,
+    conn: &mut PgConnection,
+) -> Result<Vec<ApiKeyDailyUsage>, ServiceError> {
+    sqlx::query_as::<_, ApiKeyDailyUsage>(
+        r#"
+        SELECT $1::uuid AS api_key_id,
+               d.day::date AS usage_date,
+               COALESCE(u.request_count, 0) AS request_count,
+               COALESCE(u.ip_rejected_count, 0) AS ip_rejected_count
+        FROM generate_series($2::date, $3::date, INTERVAL '1 day') AS d(day)
+        LEFT JOIN api_key_usage_daily u
+            ON u.api_key_id = $1 AND u.usage_date = d.day::date
+        ORDER BY d.day DESC
+        "#,
+    )
+    .bind(api_key_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, api_key_id = %api_key_id, "Failed to fetch API key usage");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
 /// Helper macro to implement binding for different query types
 macro_rules! impl_bind_values {
     ($func_name:ident, $query_type:ty) => {
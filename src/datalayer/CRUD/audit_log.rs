@@ -0,0 +1,257 @@
+use crate::errors::errors::ServiceError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub account_id: Option<Uuid>,
+    pub actor_type: String,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub reason: Option<String>,
+    pub request_id: Option<Uuid>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Who made a change, and the request it came with
+#[derive(Debug, Clone)]
+pub struct AuditActor {
+    /// `api_key`, `user` or `admin`
+    pub actor_type: &'static str,
+    /// The API key or user; `None` for operators
+    pub actor_id: Option<Uuid>,
+    /// The operator's name; `None` for API keys and users
+    pub actor_name: Option<String>,
+    pub request_id: Option<Uuid>,
+    pub ip_address: Option<String>,
+}
+
+/// An audit entry to write
+///
+/// `before` and `after` hold only the fields a change touched; see `changed`.
+#[derive(Debug, Clone)]
+pub struct NewAuditEntry {
+    pub account_id: Option<Uuid>,
+    pub actor: AuditActor,
+    pub action: &'static str,
+    pub target_type: &'static str,
+    pub target_id: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub reason: Option<String>,
+}
+
+impl NewAuditEntry {
+    pub fn new(
+        actor: &AuditActor,
+        account_id: Option<Uuid>,
+        action: &'static str,
+        target_type: &'static str,
+        target_id: impl ToString,
+    ) -> Self {
+        Self {
+            account_id,
+            actor: actor.clone(),
+            action,
+            target_type,
+            target_id: target_id.to_string(),
+            before: None,
+            after: None,
+            reason: None,
+        }
+    }
+
+    /// Record the state of a created resource
+    pub fn created(mut self, after: Value) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    /// Record the state of a deleted resource
+    pub fn deleted(mut self, before: Value) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    /// Record the fields that differ between two states of a resource
+    pub fn changed(mut self, before: &Value, after: &Value) -> Self {
+        let (before, after) = changed_fields(before, after);
+        self.before = Some(before);
+        self.after = Some(after);
+        self
+    }
+
+    /// Record details of an action that is not a change of state, e.g. a count
+    pub fn details(mut self, details: Value) -> Self {
+        self.after = Some(details);
+        self
+    }
+
+    /// Record why the action was taken
+    pub fn reason(mut self, reason: Option<String>) -> Self {
+        self.reason = reason;
+        self
+    }
+}
+
+pub struct AuditFilter {
+    pub action: Option<String>,
+    pub actor_type: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub cursor: Option<Uuid>,
+    pub limit: i64,
+}
+
+/// The top-level fields of two JSON objects whose values differ
+///
+/// # Returns
+/// Those fields with their values before and after; fields missing on one side are `null`
+pub fn changed_fields(before: &Value, after: &Value) -> (Value, Value) {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut changed_before = Map::new();
+    let mut changed_after = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let old = before.get(key).unwrap_or(&Value::Null);
+        let new = after.get(key).unwrap_or(&Value::Null);
+        if old != new && !changed_before.contains_key(key) {
+            changed_before.insert(key.clone(), old.clone());
+            changed_after.insert(key.clone(), new.clone());
+        }
+    }
+
+    (Value::Object(changed_before), Value::Object(changed_after))
+}
+
+/// Append an entry to the audit log
+/// Call on the connection of the surrounding transaction so the entry commits with the change
+pub async fn create_audit_entry(
+    entry: NewAuditEntry,
+    conn: &mut PgConnection,
+) -> Result<AuditEntry, ServiceError> {
+    sqlx::query_as::<_, AuditEntry>(
+        r#"
+        INSERT INTO audit_log (
+            account_id, actor_type, actor_id, actor_name, action, target_type, target_id,
+            before, after, reason, request_id, ip_address
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        RETURNING *
+        "#,
+    )
+    .bind(entry.account_id)
+    .bind(entry.actor.actor_type)
+    .bind(entry.actor.actor_id)
+    .bind(&entry.actor.actor_name)
+    .bind(entry.action)
+    .bind(entry.target_type)
+    .bind(&entry.target_id)
+    .bind(&entry.before)
+    .bind(&entry.after)
+    .bind(&entry.reason)
+    .bind(entry.actor.request_id)
+    .bind(&entry.actor.ip_address)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, action = %entry.action, "Failed to write audit entry");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Get the account's audit entries, newest first, honouring the given filter
+pub async fn get_audit_entries_for_account(
+    account_id: Uuid,
+    filter: &AuditFilter,
+    conn: &mut PgConnection,
+) -> Result<Vec<AuditEntry>, ServiceError> {
+    get_audit_entries(Some(account_id), filter, conn).await
+}
+
+/// Get audit entries newest first, of one account or of all of them
+pub async fn get_audit_entries(
+    account_id: Option<Uuid>,
+    filter: &AuditFilter,
+    conn: &mut PgConnection,
+) -> Result<Vec<AuditEntry>, ServiceError> {
+    sqlx::query_as::<_, AuditEntry>(
+        r#"
+        SELECT * FROM audit_log
+        WHERE ($1::uuid IS NULL OR account_id = $1)
+          AND ($2::varchar IS NULL OR action = $2)
+          AND ($3::varchar IS NULL OR actor_type = $3)
+          AND ($4::uuid IS NULL OR actor_id = $4)
+          AND ($5::varchar IS NULL OR target_type = $5)
+          AND ($6::varchar IS NULL OR target_id = $6)
+          AND ($7::timestamptz IS NULL OR created_at >= $7)
+          AND ($8::timestamptz IS NULL OR created_at < $8)
+          AND (
+            $9::uuid IS NULL OR (created_at, id) < (
+                SELECT created_at, id FROM audit_log WHERE id = $9
+            )
+          )
+        ORDER BY created_at DESC, id DESC
+        LIMIT $10
+        "#,
+    )
+    .bind(account_id)
+    .bind(&filter.action)
+    .bind(&filter.actor_type)
+    .bind(filter.actor_id)
+    .bind(&filter.target_type)
+    .bind(&filter.target_id)
+    .bind(filter.created_after)
+    .bind(filter.created_before)
+    .bind(filter.cursor)
+    .bind(filter.limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = ?account_id, "Failed to fetch audit entries");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_changed_fields_keeps_only_differences() {
+        let (before, after) = changed_fields(
+            &json!({"name": "a", "status": "active", "headers": {"x": "1"}, "old": 1}),
+            &json!({"name": "b", "status": "active", "headers": {"x": "2"}, "new": 2}),
+        );
+        assert_eq!(
+            before,
+            json!({"name": "a", "headers": {"x": "1"}, "old": 1, "new": null})
+        );
+        assert_eq!(
+            after,
+            json!({"name": "b", "headers": {"x": "2"}, "old": null, "new": 2})
+        );
+
+        let unchanged = json!({"status": "active"});
+        assert_eq!(
+            changed_fields(&unchanged, &unchanged),
+            (json!({}), json!({}))
+        );
+    }
+}
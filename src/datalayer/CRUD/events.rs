@@ -0,0 +1,223 @@
+use crate::errors::errors::ServiceError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Event {
+    pub id: Uuid,
+    pub sequence: i64,
+    pub account_id: Uuid,
+    pub event_type: String,
+    pub version: i32,
+    pub data: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Every event type written to the log
+pub const EVENT_TYPES: &[&str] = &[
+    "account.created",
+    "account.updated",
+    "transaction.debited",
+    "transaction.credited",
+    "api_key.created",
+    "api_key.revoked",
+    "webhook.failed",
+];
+
+/// Version of the `data` schema written for new events
+pub const EVENT_VERSION: i32 = 1;
+
+pub struct EventFilter {
+    pub event_type: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub cursor: Option<Uuid>,
+    pub limit: i64,
+}
+
+/// Append an event to the account's event log
+/// Call on the connection of the surrounding transaction so the event commits with the change
+pub async fn create_event(
+    account_id: Uuid,
+    event_type: &str,
+    data: serde_json::Value,
+    conn: &mut PgConnection,
+) -> Result<Event, ServiceError> {
+    let event = sqlx::query_as::<_, Event>(
+        r#"
+        INSERT INTO events (account_id, event_type, version, data)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(account_id)
+    .bind(event_type)
+    .bind(EVENT_VERSION)
+    .bind(data)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, event_type = %event_type, "Failed to create event");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(event)
+}
+
+/// Get the sequence of an event, verifying it belongs to the account
+/// Returns `None` when the event does not exist for this account
+pub async fn get_event_sequence(
+    event_id: Uuid,
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Option<i64>, ServiceError> {
+    sqlx::query_scalar::<_, i64>("SELECT sequence FROM events WHERE id = $1 AND account_id = $2")
+        .bind(event_id)
+        .bind(account_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, event_id = %event_id, "Failed to look up event");
+            ServiceError::DatabaseError(e.to_string())
+        })
+}
+
+/// Get the account's events that come after the given sequence, oldest first
+pub async fn get_events_after_sequence(
+    account_id: Uuid,
+    after_sequence: i64,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Event>, ServiceError> {
+    let events = sqlx::query_as::<_, Event>(
+        r#"
+        SELECT * FROM events
+        WHERE account_id = $1 AND sequence > $2
+        ORDER BY sequence
+        LIMIT $3
+        "#,
+    )
+    .bind(account_id)
+    .bind(after_sequence)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to fetch events");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(events)
+}
+
+/// Get the account's events of the given types created in `[start_at, end_at)`,
+/// oldest first, starting after the given sequence
+pub async fn get_events_in_range(
+    account_id: Uuid,
+    event_types: &[String],
+    start_at: DateTime<Utc>,
+    end_at: DateTime<Utc>,
+    after_sequence: i64,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Event>, ServiceError> {
+    let events = sqlx::query_as::<_, Event>(
+        r#"
+        SELECT * FROM events
+        WHERE account_id = $1
+          AND event_type = ANY($2)
+          AND created_at >= $3
+          AND created_at < $4
+          AND sequence > $5
+        ORDER BY sequence
+        LIMIT $6
+        "#,
+    )
+    .bind(account_id)
+    .bind(event_types)
+    .bind(start_at)
+    .bind(end_at)
+    .bind(after_sequence)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to fetch events in range");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(events)
+}
+
+/// Get the account's events, newest first, honouring the given filter
+/// The cursor must be an event of the same account
+pub async fn get_events_for_account(
+    account_id: Uuid,
+    filter: &EventFilter,
+    conn: &mut PgConnection,
+) -> Result<Vec<Event>, ServiceError> {
+    if let Some(cursor) = filter.cursor {
+        if get_event_sequence(cursor, account_id, &mut *conn)
+            .await?
+            .is_none()
+        {
+            return Err(ServiceError::InvalidInput(format!(
+                "cursor {} is not an event of this account",
+                cursor
+            )));
+        }
+    }
+
+    let events = sqlx::query_as::<_, Event>(
+        r#"
+        SELECT * FROM events
+        WHERE account_id = $1
+          AND ($2::varchar IS NULL OR event_type = $2)
+          AND ($3::timestamptz IS NULL OR created_at >= $3)
+          AND ($4::timestamptz IS NULL OR created_at < $4)
+          AND (
+            $5::uuid IS NULL OR (created_at, id) < (
+                SELECT created_at, id FROM events WHERE id = $5 AND account_id = $1
+            )
+          )
+        ORDER BY created_at DESC, id DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(account_id)
+    .bind(&filter.event_type)
+    .bind(filter.created_after)
+    .bind(filter.created_before)
+    .bind(filter.cursor)
+    .bind(filter.limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to fetch events");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(events)
+}
+
+/// Get a single event, verifying it belongs to the account
+pub async fn get_event_for_account(
+    event_id: Uuid,
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Event, ServiceError> {
+    sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = $1 AND account_id = $2")
+        .bind(event_id)
+        .bind(account_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, event_id = %event_id, "Event not found");
+            match e {
+                sqlx::Error::RowNotFound => ServiceError::EventNotFound(event_id.to_string()),
+                _ => ServiceError::DatabaseError(e.to_string()),
+            }
+        })
+}
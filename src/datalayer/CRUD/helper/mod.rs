@@ -2,3 +2,4 @@ pub mod apikey_generator;
 pub mod conversion;
 pub mod email_regex;
 pub mod transaction;
+pub mod user_credentials;
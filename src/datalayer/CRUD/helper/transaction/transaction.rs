@@ -29,6 +29,7 @@ impl<'a> TransactionHelper<'a> {
         // create a transaction and return it
         let account = AccountBuilder::new()
             .id(self.txn.from_account_id.unwrap())
+            .livemode(self.txn.livemode.unwrap_or(true))
             .expect_id()
             .expect_business_name()
             .expect_email()
@@ -54,6 +55,7 @@ impl<'a> TransactionHelper<'a> {
         // convert balance to storage units
         let test = AccountBuilder::new()
             .id(self.txn.from_account_id.unwrap())
+            .livemode(self.txn.livemode.unwrap_or(true))
             .balance(new_balance)
             .expect_balance()
             .expect_business_name()
@@ -76,6 +78,7 @@ impl<'a> TransactionHelper<'a> {
         // create a transaction and return it
         let account = AccountBuilder::new()
             .id(self.txn.to_account_id.unwrap())
+            .livemode(self.txn.livemode.unwrap_or(true))
             .expect_id()
             .expect_business_name()
             .expect_email()
@@ -92,6 +95,7 @@ impl<'a> TransactionHelper<'a> {
         // convert balance to storage units
         let _ = AccountBuilder::new()
             .id(self.txn.to_account_id.unwrap())
+            .livemode(self.txn.livemode.unwrap_or(true))
             .balance(new_balance)
             .expect_balance()
             .expect_business_name()
@@ -0,0 +1,94 @@
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::SaltString};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// Prefix of user session tokens, which tells them apart from API keys
+pub const SESSION_PREFIX: &str = "dodo_session_";
+
+/// Accepted password lengths, in characters
+pub const PASSWORD_LEN: std::ops::RangeInclusive<usize> = 12..=128;
+
+/// Hash checked against when a login names an unknown user, built on first use
+static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+/// Hash a password with argon2id and a random salt
+///
+/// # Returns
+/// The PHC string to store, which carries the salt and parameters
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt_bytes: [u8; 16] = rand::thread_rng().r#gen();
+    let salt = SaltString::encode_b64(&salt_bytes).map_err(|e| e.to_string())?;
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Check a password against a stored PHC string; malformed hashes never match
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+/// Spend the time of a password check without a user to check against
+///
+/// Logins for unknown emails call this, so response times do not reveal which
+/// emails have users.
+pub fn verify_dummy_password(password: &str) {
+    let hash = DUMMY_HASH.get_or_init(|| {
+        hash_password("dummy password for unknown users").expect("argon2 can hash a fixed password")
+    });
+    let _ = verify_password(password, hash);
+}
+
+/// Generate a session token: dodo_session_{64 hex characters}
+///
+/// # Returns
+/// The plain-text token (shown to the user once) and its hash to store
+pub fn generate_session_token() -> (String, String) {
+    let random_bytes: [u8; 32] = rand::thread_rng().r#gen();
+    let token = format!("{}{}", SESSION_PREFIX, hex::encode(random_bytes));
+    let token_hash = hash_session_token(&token);
+    (token, token_hash)
+}
+
+/// Hash a session token for lookup
+///
+/// Tokens carry 256 random bits, so a plain SHA-256 is enough to keep the stored
+/// hashes useless to whoever reads them.
+pub fn hash_session_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_hash_round_trip() {
+        let hash = hash_password("correct horse battery staple").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse battery staple", &hash));
+        assert!(!verify_password("correct horse battery stapler", &hash));
+        assert!(!verify_password(
+            "correct horse battery staple",
+            "not a hash"
+        ));
+
+        // Salts are random, so equal passwords hash differently
+        assert_ne!(hash, hash_password("correct horse battery staple").unwrap());
+    }
+
+    #[test]
+    fn test_session_tokens_are_prefixed_and_hashed() {
+        let (token, token_hash) = generate_session_token();
+        assert!(token.starts_with(SESSION_PREFIX));
+        assert_eq!(token.len(), SESSION_PREFIX.len() + 64);
+        assert_eq!(token_hash, hash_session_token(&token));
+        assert_ne!(generate_session_token().0, token);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod accounts;
 pub mod api_key;
+pub mod audit_log;
+pub mod events;
 pub mod helper;
 pub mod money;
 pub mod rate_limiter;
@@ -7,6 +9,8 @@ pub mod redis;
 pub mod sql_generator;
 pub mod transaction;
 pub mod types;
+pub mod users;
 pub mod webhook;
+pub mod webhook_backfill;
 
 pub use crate::datalayer::*;
@@ -1,6 +1,4 @@
-use crate::datalayer::CRUD::api_key::ApiKeyBuilder;
 use crate::datalayer::CRUD::redis::redis::RateLimitCounter;
-use crate::datalayer::db_ops::constants::POOL_STATE_TRACKER;
 use crate::datalayer::helper::backoff::ExponentialBackoff;
 use crate::errors::errors::ServiceError;
 use redis::aio::ConnectionManager;
@@ -59,6 +57,8 @@ impl RateLimiter {
 
     /// Check rate limit with backoff for an API key and endpoint
     /// Automatically tracks request count in Redis
+    ///
+    /// The key must already be validated; `auth_middleware` does so for every request.
     pub async fn check_with_backoff(
         &self,
         api_key_id: Uuid,
@@ -66,9 +66,6 @@ impl RateLimiter {
         endpoint: &str,
         redis_conn: ConnectionManager,
     ) -> Result<(), ServiceError> {
-        // Validate API key first
-        self.validate_api_key(api_key_id, api_key_prefix).await?;
-
         // Get current count from Redis
         let mut counter = RateLimitCounter::new(redis_conn.clone());
         let current_count = counter
@@ -97,6 +94,7 @@ impl RateLimiter {
 
             tracing::warn!(
                 api_key_id = %api_key_id,
+                key_prefix = %api_key_prefix,
                 endpoint = %endpoint,
                 request_count = current_count + 1,
                 delay_ms = delay_ms,
@@ -115,58 +113,6 @@ impl RateLimiter {
         Ok(())
     }
 
-    /// Validate that the API key exists and is active
-    async fn validate_api_key(
-        &self,
-        api_key_id: Uuid,
-        api_key_prefix: &str,
-    ) -> Result<(), ServiceError> {
-        let tracker = POOL_STATE_TRACKER
-            .get()
-            .ok_or_else(|| ServiceError::DatabaseConnectionError)?;
-
-        let mut conn = tracker
-            .get_connection()
-            .await
-            .map_err(|e| ServiceError::DatabaseError(format!("Failed to get connection: {}", e)))?;
-
-        let api_key = ApiKeyBuilder::new()
-            .id(api_key_id)
-            .expect_id()
-            .expect_account_id()
-            .expect_key_hash()
-            .expect_key_prefix()
-            .expect_name()
-            .expect_status()
-            .expect_permissions()
-            .expect_last_used_at()
-            .expect_expires_at()
-            .expect_created_at()
-            .expect_revoked_at()
-            .read(Some(&mut conn))
-            .await?;
-
-        tracker.return_connection(conn);
-
-        // Check if API key is active
-        if api_key.status != "active" {
-            return Err(ServiceError::ValidationError(format!(
-                "API key {} is not active (status: {})",
-                api_key_prefix, api_key.status
-            )));
-        }
-
-        // Check if API key is revoked
-        if api_key.revoked_at.is_some() {
-            return Err(ServiceError::ValidationError(format!(
-                "API key {} has been revoked",
-                api_key_prefix
-            )));
-        }
-
-        Ok(())
-    }
-
     /// Get current request count for an API key and endpoint
     pub async fn get_count(
         &self,
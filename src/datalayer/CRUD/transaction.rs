@@ -38,6 +38,7 @@ pub struct TransactionBuilder {
     pub status: Option<TransactionStatus>,
     pub idempotency_key: Option<String>,
     pub parent_tx_key: Option<String>,
+    pub livemode: Option<bool>,
     pub description: Option<String>,
     pub metadata: Option<serde_json::Value>,
     pub error_code: Option<String>,
@@ -54,6 +55,7 @@ pub struct TransactionBuilder {
     pub get_status: Option<bool>,
     pub get_idempotency_key: Option<bool>,
     pub get_parent_tx_key: Option<bool>,
+    pub get_livemode: Option<bool>,
     pub get_description: Option<bool>,
     pub get_metadata: Option<bool>,
     pub get_error_code: Option<bool>,
@@ -113,6 +115,12 @@ impl TransactionBuilder {
         self
     }
 
+    /// Mode of the transaction; its accounts must be of the same mode
+    pub fn livemode(mut self, livemode: bool) -> Self {
+        self.livemode = Some(livemode);
+        self
+    }
+
     pub fn description(mut self, description: String) -> Self {
         self.description = Some(description);
         self
@@ -184,6 +192,11 @@ impl TransactionBuilder {
         self
     }
 
+    pub fn expect_livemode(mut self) -> Self {
+        self.get_livemode = Some(true);
+        self
+    }
+
     pub fn expect_description(mut self) -> Self {
         self.get_description = Some(true);
         self
@@ -366,6 +379,12 @@ impl TransactionBuilder {
             }
         }
 
+        // Test mode has no settlement: every record is completed as soon as it is created
+        let livemode = self.livemode.unwrap_or(true);
+        if !livemode {
+            status = TransactionStatus::Completed;
+        }
+
         // DEBUG
         println!(">>> DEBUG :: Status");
 
@@ -379,6 +398,7 @@ impl TransactionBuilder {
         let get_status = self.get_status.unwrap_or(false);
         let get_idempotency_key = self.get_idempotency_key.unwrap_or(true);
         let get_parent_tx_key = self.get_parent_tx_key.unwrap_or(true);
+        let get_livemode = self.get_livemode.unwrap_or(true);
         let get_description = self.get_description.unwrap_or(false);
         let get_metadata = self.get_metadata.unwrap_or(false);
         let get_error_code = self.get_error_code.unwrap_or(false);
@@ -401,6 +421,7 @@ impl TransactionBuilder {
                 .value(Transactions::Status, status_str)
                 .value(Transactions::IdempotencyKey, idempotency_key)
                 .value(Transactions::ParentTxKey, parent_tx_key)
+                .value(Transactions::Livemode, livemode)
                 .value(Transactions::Description, self.description.clone())
                 .value(Transactions::Metadata, self.metadata.clone())
                 .value(Transactions::ErrorCode, self.error_code.clone())
@@ -434,6 +455,9 @@ impl TransactionBuilder {
             if get_parent_tx_key {
                 insert = insert.returning(Transactions::ParentTxKey);
             }
+            if get_livemode {
+                insert = insert.returning(Transactions::Livemode);
+            }
             if get_description {
                 insert = insert.returning(Transactions::Description);
             }
@@ -496,6 +520,7 @@ impl TransactionBuilder {
         let get_created_at = self.get_created_at.unwrap_or(true);
         let get_completed_at = self.get_completed_at.unwrap_or(true);
         let get_parent_tx_key = self.get_parent_tx_key.unwrap_or(true);
+        let get_livemode = self.get_livemode.unwrap_or(true);
 
         let build_update = || {
             // Convert enum to string for sea-query
@@ -512,6 +537,9 @@ impl TransactionBuilder {
                 .value(Transactions::Description, self.description.clone())
                 .value(Transactions::Metadata, self.metadata.clone())
                 .filter(Transactions::Id, id);
+            if let Some(livemode) = self.livemode {
+                update = update.filter(Transactions::Livemode, livemode);
+            }
 
             if get_id {
                 update = update.returning(Transactions::Id);
@@ -540,6 +568,9 @@ impl TransactionBuilder {
             if get_parent_tx_key {
                 update = update.returning(Transactions::ParentTxKey);
             }
+            if get_livemode {
+                update = update.returning(Transactions::Livemode);
+            }
             if get_description {
                 update = update.returning(Transactions::Description);
             }
@@ -618,6 +649,7 @@ impl TransactionBuilder {
         let get_status = self.get_status.unwrap_or(false);
         let get_idempotency_key = self.get_idempotency_key.unwrap_or(false);
         let get_parent_tx_key = self.get_parent_tx_key.unwrap_or(false);
+        let get_livemode = self.get_livemode.unwrap_or(true);
         let get_description = self.get_description.unwrap_or(false);
         let get_metadata = self.get_metadata.unwrap_or(false);
         let get_error_code = self.get_error_code.unwrap_or(false);
@@ -655,6 +687,9 @@ impl TransactionBuilder {
             if get_parent_tx_key {
                 select = select.column(Transactions::ParentTxKey);
             }
+            if get_livemode {
+                select = select.column(Transactions::Livemode);
+            }
             if get_description {
                 select = select.column(Transactions::Description);
             }
@@ -698,6 +733,9 @@ impl TransactionBuilder {
             if let Some(ref parent_tx_key) = self.parent_tx_key {
                 select = select.filter(Transactions::ParentTxKey, parent_tx_key.clone());
             }
+            if let Some(livemode) = self.livemode {
+                select = select.filter(Transactions::Livemode, livemode);
+            }
 
             select.render()
         };
@@ -834,6 +872,7 @@ impl TransactionBuilder {
         if let Some(from_account_id) = self.from_account_id {
             let from_account = AccountBuilder::new()
                 .id(from_account_id)
+                .livemode(self.livemode.unwrap_or(true))
                 .expect_id()
                 .expect_business_name()
                 .expect_email()
@@ -874,6 +913,7 @@ impl TransactionBuilder {
         if let Some(to_account_id) = self.to_account_id {
             let to_account = AccountBuilder::new()
                 .id(to_account_id)
+                .livemode(self.livemode.unwrap_or(true))
                 .expect_id()
                 .expect_business_name()
                 .expect_email()
@@ -885,8 +925,13 @@ impl TransactionBuilder {
                 .read(Some(conn))
                 .await;
 
-            if to_account.is_err() {
-                return Err(ServiceError::AccountNotFound(to_account_id.to_string()));
+            match to_account {
+                Err(_) => return Err(ServiceError::AccountNotFound(to_account_id.to_string())),
+                // Suspended and closed accounts cannot receive funds
+                Ok(to_account) if to_account.status != "active" => {
+                    return Err(ServiceError::AccountInactive(to_account_id.to_string()));
+                }
+                Ok(_) => {}
             }
         }
 
@@ -910,6 +955,7 @@ impl TransactionBuilder {
                                 .transaction_type(TransactionType::Transfer)
                                 .parent_tx_key(parent_tx_key.clone())
                                 .from_account_id(self.from_account_id.unwrap())
+                                .livemode(self.livemode.unwrap_or(true))
                                 .expect_id()
                                 .expect_amount()
                                 .expect_parent_tx_key()
@@ -948,6 +994,7 @@ impl TransactionBuilder {
                         let debit_exists = TransactionBuilder::new()
                             .transaction_type(TransactionType::Debit)
                             .parent_tx_key(parent_tx_key.clone())
+                            .livemode(self.livemode.unwrap_or(true))
                             .expect_id()
                             .expect_amount()
                             .expect_currency()
@@ -1,5 +1,5 @@
 use crate::datalayer::CRUD::money::from_storage_units;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use sea_query::Iden;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -8,6 +8,7 @@ use uuid::Uuid;
 pub mod db_tables {
     pub const ACCOUNTS: &str = "accounts";
     pub const API_KEYS: &str = "api_keys";
+    pub const API_KEY_USAGE_DAILY: &str = "api_key_usage_daily";
     pub const RATE_LIMIT_COUNTERS: &str = "rate_limit_counters";
     pub const TRANSACTIONS: &str = "transactions";
     pub const WEBHOOK_DELIVERIES: &str = "webhook_deliveries";
@@ -23,6 +24,9 @@ pub enum Accounts {
     #[iden = "business_name"]
     BusinessName,
     Email,
+    Livemode,
+    #[iden = "require_signed_writes"]
+    RequireSignedWrites,
     Balance,
     Currency,
     Status,
@@ -46,6 +50,8 @@ pub enum ApiKeys {
     Name,
     Status,
     Permissions,
+    #[iden = "allowed_ips"]
+    AllowedIps,
     #[iden = "last_used_at"]
     LastUsedAt,
     #[iden = "expires_at"]
@@ -73,6 +79,7 @@ pub enum Transactions {
     IdempotencyKey,
     #[iden = "parent_tx_key"]
     ParentTxKey,
+    Livemode,
     Description,
     Metadata,
     #[iden = "error_code"]
@@ -91,11 +98,18 @@ pub enum Webhooks {
     Id,
     AccountId,
     Url,
-    Secret,
+    EncryptedSecret,
+    SecretKeyId,
+    SigningAlgorithm,
+    Description,
+    Headers,
+    Livemode,
     Events,
     Status,
     MaxRetries,
     RetryBackoffSeconds,
+    MaxConcurrency,
+    DeliverySequence,
     ConsecutiveFailures,
     LastFailureAt,
     CreatedAt,
@@ -129,6 +143,10 @@ pub struct Account {
     pub id: Uuid,
     pub business_name: String,
     pub email: String,
+    /// false for test-mode accounts
+    pub livemode: bool,
+    /// Write scopes can only be used by signed requests
+    pub require_signed_writes: bool,
     pub balance: f64, // API uses dollars, DB stores as i64 storage units
     pub currency: String,
     pub status: String,
@@ -149,6 +167,8 @@ impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for Account {
             id: row.try_get("id")?,
             business_name: row.try_get("business_name")?,
             email: row.try_get("email")?,
+            livemode: row.try_get("livemode")?,
+            require_signed_writes: row.try_get("require_signed_writes")?,
             balance: from_storage_units(balance_storage_units),
             currency: row.try_get("currency")?,
             status: row.try_get("status")?,
@@ -170,12 +190,68 @@ pub struct ApiKey {
     pub name: Option<String>,
     pub status: String,
     pub permissions: Option<serde_json::Value>, // JSONB ["read", "write"]
+    /// JSONB array of CIDRs the key may be used from; null or empty allows any address
+    #[sqlx(default)]
+    #[serde(default)]
+    pub allowed_ips: Option<serde_json::Value>,
+    /// Only loaded by `SELECT *` reads; builder reads leave these empty
+    #[sqlx(default)]
+    #[serde(default)]
+    pub last_used_ip: Option<String>,
+    #[sqlx(default)]
+    #[serde(default)]
+    pub last_user_agent: Option<String>,
+    /// Signing secret, envelope-encrypted like webhook secrets
+    #[sqlx(default)]
+    #[serde(default)]
+    pub encrypted_signing_secret: Option<String>,
+    #[sqlx(default)]
+    #[serde(default)]
+    pub signing_secret_key_id: Option<String>,
+    /// The account's `require_signed_writes`, only loaded by the lookups of authentication
+    #[sqlx(default)]
+    #[serde(default)]
+    pub account_requires_signed_writes: bool,
+    /// The account's `status`, likewise only loaded for authentication
+    #[sqlx(default)]
+    #[serde(default)]
+    pub account_status: Option<String>,
     pub last_used_at: Option<DateTime<Utc>>,
     pub expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub revoked_at: Option<DateTime<Utc>>,
 }
 
+impl ApiKey {
+    /// Whether the key can still authenticate: active, not revoked and not expired
+    pub fn is_usable(&self) -> bool {
+        self.status == "active"
+            && self.revoked_at.is_none()
+            && self
+                .expires_at
+                .is_none_or(|expires_at| expires_at > Utc::now())
+    }
+}
+
+/// Most recent use of an API key, as buffered by authentication
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKeyLastUse {
+    pub api_key_id: Uuid,
+    pub used_at: DateTime<Utc>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// Requests authenticated by an API key on one UTC day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct ApiKeyDailyUsage {
+    pub api_key_id: Uuid,
+    pub usage_date: NaiveDate,
+    pub request_count: i64,
+    /// Requests refused because they came from outside the key's IP allowlist
+    pub ip_rejected_count: i64,
+}
+
 // --- TRANSACTIONS ---
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
@@ -210,6 +286,8 @@ pub struct Transaction {
     pub status: TransactionStatus,
     pub idempotency_key: String,
     pub parent_tx_key: String,
+    /// false for test-mode transactions
+    pub livemode: bool,
     pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
@@ -236,6 +314,7 @@ impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for Transaction {
             status: row.try_get("status")?,
             idempotency_key: row.try_get("idempotency_key")?,
             parent_tx_key: row.try_get("parent_tx_key")?,
+            livemode: row.try_get("livemode")?,
             description: row.try_get("description").ok(),
             metadata: row.try_get("metadata").ok(),
             error_code: row.try_get("error_code").ok(),
@@ -247,30 +326,17 @@ impl<'r> sqlx::FromRow<'r, sqlx::postgres::PgRow> for Transaction {
 }
 
 // --- WEBHOOKS ---
-
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-pub struct Webhook {
-    pub id: Uuid,
-    pub account_id: Uuid,
-    pub url: String,
-    pub secret: String,
-    pub events: serde_json::Value, // JSONB
-    pub status: String,
-    pub max_retries: Option<i32>,
-    pub retry_backoff_seconds: Option<i32>,
-    pub consecutive_failures: Option<i32>,
-    pub last_failure_at: Option<DateTime<Utc>>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-}
+// The webhook model lives with its queries in `CRUD::webhook`
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct WebhookDelivery {
     pub id: Uuid,
     pub webhook_id: Uuid,
     pub transaction_id: Option<Uuid>,
+    pub event_id: Option<Uuid>,
     pub event_type: String,
     pub payload: serde_json::Value,
+    pub sequence: Option<i64>,
     pub status: String,
     pub attempt_count: Option<i32>,
     pub max_attempts: Option<i32>,
@@ -281,6 +347,7 @@ pub struct WebhookDelivery {
     pub created_at: DateTime<Utc>,
     pub delivered_at: Option<DateTime<Utc>>,
     pub failed_at: Option<DateTime<Utc>>,
+    pub dead_lettered_at: Option<DateTime<Utc>>,
 }
 
 // --- RATE LIMITS ---
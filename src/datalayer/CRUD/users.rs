@@ -0,0 +1,384 @@
+use crate::errors::errors::ServiceError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// A person who signs in; never serialised, as it carries the password hash
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct User {
+    pub id: Uuid,
+    pub email: String,
+    pub password_hash: String,
+    pub name: Option<String>,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A member of an account: the membership with the user's details
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Member {
+    pub account_id: Uuid,
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An account a user belongs to, as listed at login
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserMembership {
+    pub account_id: Uuid,
+    pub business_name: String,
+    pub livemode: bool,
+    pub role: String,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UserSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// What a live session may act as on one account
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SessionPrincipal {
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub account_id: Uuid,
+    pub role: String,
+    pub livemode: bool,
+    pub require_signed_writes: bool,
+}
+
+/// Columns of a member, read from a membership `m` joined with its user `u`
+const MEMBER_COLUMNS: &str = "m.account_id, m.user_id, u.email, u.name, m.role, m.created_at";
+
+fn database_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Create a user; fails with `UserAlreadyExists` when the email is taken
+pub async fn create_user(
+    email: &str,
+    password_hash: &str,
+    name: Option<&str>,
+    conn: &mut PgConnection,
+) -> Result<User, ServiceError> {
+    sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (email, password_hash, name)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+    )
+    .bind(email)
+    .bind(password_hash)
+    .bind(name)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+            ServiceError::UserAlreadyExists(email.to_string())
+        }
+        _ => {
+            tracing::error!(error = %e, "Failed to create user");
+            database_error(e)
+        }
+    })
+}
+
+pub async fn get_user_by_email(
+    email: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<User>, ServiceError> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_optional(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to look up user");
+            database_error(e)
+        })
+}
+
+/// Accounts a user belongs to, oldest membership first
+pub async fn get_memberships_for_user(
+    user_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Vec<UserMembership>, ServiceError> {
+    sqlx::query_as::<_, UserMembership>(
+        r#"
+        SELECT m.account_id, a.business_name, a.livemode, m.role
+        FROM account_memberships m
+        JOIN accounts a ON a.id = m.account_id
+        WHERE m.user_id = $1
+        ORDER BY m.created_at, m.account_id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, user_id = %user_id, "Failed to list user memberships");
+        database_error(e)
+    })
+}
+
+pub async fn create_session(
+    user_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+    conn: &mut PgConnection,
+) -> Result<UserSession, ServiceError> {
+    sqlx::query_as::<_, UserSession>(
+        r#"
+        INSERT INTO user_sessions (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        RETURNING id, user_id, created_at, expires_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(expires_at)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, user_id = %user_id, "Failed to create session");
+        database_error(e)
+    })
+}
+
+/// Revoke the session with this token hash
+///
+/// # Returns
+/// The session's id, or `None` when no unrevoked session has the hash
+pub async fn revoke_session(
+    token_hash: &str,
+    conn: &mut PgConnection,
+) -> Result<Option<Uuid>, ServiceError> {
+    sqlx::query_scalar::<_, Uuid>(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = NOW()
+        WHERE token_hash = $1 AND revoked_at IS NULL
+        RETURNING id
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to revoke session");
+        database_error(e)
+    })
+}
+
+/// Active accounts a live session of an active user can act on
+///
+/// With `account_id` only that account is considered. At most two rows are
+/// returned, enough to tell a single membership from several.
+pub async fn get_session_principals(
+    token_hash: &str,
+    account_id: Option<Uuid>,
+    conn: &mut PgConnection,
+) -> Result<Vec<SessionPrincipal>, ServiceError> {
+    sqlx::query_as::<_, SessionPrincipal>(
+        r#"
+        SELECT s.id AS session_id, s.user_id, m.account_id, m.role,
+               a.livemode, a.require_signed_writes
+        FROM user_sessions s
+        JOIN users u ON u.id = s.user_id
+        JOIN account_memberships m ON m.user_id = s.user_id
+        JOIN accounts a ON a.id = m.account_id
+        WHERE s.token_hash = $1
+          AND s.revoked_at IS NULL
+          AND s.expires_at > NOW()
+          AND u.status = 'active'
+          AND a.status = 'active'
+          AND ($2::uuid IS NULL OR m.account_id = $2)
+        ORDER BY m.created_at, m.account_id
+        LIMIT 2
+        "#,
+    )
+    .bind(token_hash)
+    .bind(account_id)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to load session");
+        database_error(e)
+    })
+}
+
+/// Members of an account, oldest first
+pub async fn get_members(
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Vec<Member>, ServiceError> {
+    sqlx::query_as::<_, Member>(&format!(
+        r#"
+        SELECT {}
+        FROM account_memberships m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.account_id = $1
+        ORDER BY m.created_at, m.user_id
+        "#,
+        MEMBER_COLUMNS
+    ))
+    .bind(account_id)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to list members");
+        database_error(e)
+    })
+}
+
+/// One member of an account; fails with `MemberNotFound` for non-members
+pub async fn get_member(
+    account_id: Uuid,
+    user_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Member, ServiceError> {
+    sqlx::query_as::<_, Member>(&format!(
+        r#"
+        SELECT {}
+        FROM account_memberships m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.account_id = $1 AND m.user_id = $2
+        "#,
+        MEMBER_COLUMNS
+    ))
+    .bind(account_id)
+    .bind(user_id)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => ServiceError::MemberNotFound(user_id.to_string()),
+        _ => {
+            tracing::error!(error = %e, account_id = %account_id, "Failed to get member");
+            database_error(e)
+        }
+    })
+}
+
+/// Add a user to an account; fails with `MemberAlreadyExists` when they belong to it
+pub async fn add_member(
+    account_id: Uuid,
+    user_id: Uuid,
+    role: &str,
+    conn: &mut PgConnection,
+) -> Result<Member, ServiceError> {
+    sqlx::query_as::<_, Member>(&format!(
+        r#"
+        WITH m AS (
+            INSERT INTO account_memberships (account_id, user_id, role)
+            VALUES ($1, $2, $3)
+            RETURNING *
+        )
+        SELECT {}
+        FROM m
+        JOIN users u ON u.id = m.user_id
+        "#,
+        MEMBER_COLUMNS
+    ))
+    .bind(account_id)
+    .bind(user_id)
+    .bind(role)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+            ServiceError::MemberAlreadyExists(user_id.to_string())
+        }
+        _ => {
+            tracing::error!(error = %e, account_id = %account_id, "Failed to add member");
+            database_error(e)
+        }
+    })
+}
+
+pub async fn update_member_role(
+    account_id: Uuid,
+    user_id: Uuid,
+    role: &str,
+    conn: &mut PgConnection,
+) -> Result<Member, ServiceError> {
+    sqlx::query_as::<_, Member>(&format!(
+        r#"
+        WITH m AS (
+            UPDATE account_memberships
+            SET role = $3
+            WHERE account_id = $1 AND user_id = $2
+            RETURNING *
+        )
+        SELECT {}
+        FROM m
+        JOIN users u ON u.id = m.user_id
+        "#,
+        MEMBER_COLUMNS
+    ))
+    .bind(account_id)
+    .bind(user_id)
+    .bind(role)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => ServiceError::MemberNotFound(user_id.to_string()),
+        _ => {
+            tracing::error!(error = %e, account_id = %account_id, "Failed to update member");
+            database_error(e)
+        }
+    })
+}
+
+pub async fn remove_member(
+    account_id: Uuid,
+    user_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<(), ServiceError> {
+    let result =
+        sqlx::query("DELETE FROM account_memberships WHERE account_id = $1 AND user_id = $2")
+            .bind(account_id)
+            .bind(user_id)
+            .execute(conn)
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, account_id = %account_id, "Failed to remove member");
+                database_error(e)
+            })?;
+
+    if result.rows_affected() == 0 {
+        return Err(ServiceError::MemberNotFound(user_id.to_string()));
+    }
+    Ok(())
+}
+
+/// Lock the owner memberships of an account and return the owners
+///
+/// Run inside a transaction before removing or demoting an owner, so two
+/// concurrent changes cannot leave the account without one.
+pub async fn lock_owners(
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Vec<Uuid>, ServiceError> {
+    sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT user_id FROM account_memberships
+        WHERE account_id = $1 AND role = 'owner'
+        FOR UPDATE
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to lock owners");
+        database_error(e)
+    })
+}
@@ -1,3 +1,4 @@
+use super::types::WebhookDelivery;
 use crate::errors::errors::ServiceError;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -9,34 +10,94 @@ pub struct Webhook {
     pub id: Uuid,
     pub account_id: Uuid,
     pub url: String,
-    pub secret: String,
-    pub events: serde_json::Value, // JSONB array
+    /// Envelope-encrypted HMAC secret; decrypted only to sign deliveries
+    #[serde(skip_serializing)]
+    pub encrypted_secret: String,
+    #[serde(skip_serializing)]
+    pub secret_key_id: String,
+    pub signing_algorithm: String,
+    /// Mode of the owning account; test-mode webhooks receive only test-mode events
+    pub livemode: bool,
+    pub description: Option<String>,
+    pub headers: serde_json::Value, // JSONB object of custom static headers
+    pub events: serde_json::Value,  // JSONB array
     pub status: String,
     pub max_retries: Option<i32>,
     pub retry_backoff_seconds: Option<i32>,
+    pub max_concurrency: i32,
+    pub delivery_sequence: i64,
     pub consecutive_failures: Option<i32>,
     pub last_failure_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Event types a webhook can subscribe to ("*" matches every event)
+pub const WEBHOOK_EVENT_TYPES: &[&str] = &["*", "transaction.debited", "transaction.credited"];
+
+/// Upper bound for a webhook's `max_concurrency`
+pub const MAX_DELIVERY_CONCURRENCY: i32 = 20;
+
+/// How long a pending delivery picked up for an attempt is hidden from the retry sweeper
+///
+/// `next_retry_at` is pushed this far ahead while an attempt is queued or in flight, so a
+/// delivery whose process died mid-attempt is picked up again once the lease runs out.
+pub const DELIVERY_LEASE_SECONDS: i32 = 300;
+
+impl Webhook {
+    /// Whether this webhook is subscribed to the given event type
+    pub fn subscribes_to(&self, event_type: &str) -> bool {
+        self.events
+            .as_array()
+            .map(|events| {
+                events
+                    .iter()
+                    .filter_map(|e| e.as_str())
+                    .any(|e| e == "*" || e == event_type)
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Partial update for a webhook's configuration
+/// Fields left as `None` keep their current value
+#[derive(Debug, Clone, Default)]
+pub struct WebhookUpdate {
+    pub url: Option<String>,
+    pub events: Option<serde_json::Value>,
+    pub max_retries: Option<i32>,
+    pub retry_backoff_seconds: Option<i32>,
+    pub max_concurrency: Option<i32>,
+    pub signing_algorithm: Option<String>,
+    /// An empty string clears the description
+    pub description: Option<String>,
+    pub headers: Option<serde_json::Value>,
+}
+
 /// Create a new webhook for an account
+/// `status` is `active`, or `pending_verification` when the endpoint must pass the handshake first
+/// The secret must already be encrypted, with the id of the master key that wrapped it
+/// The webhook takes the mode of its account
 pub async fn create_webhook(
     account_id: Uuid,
     url: String,
-    secret: String,
+    encrypted_secret: &str,
+    secret_key_id: &str,
+    status: &str,
     conn: &mut PgConnection,
 ) -> Result<Webhook, ServiceError> {
     let webhook = sqlx::query_as::<_, Webhook>(
         r#"
-        INSERT INTO webhooks (account_id, url, secret, status)
-        VALUES ($1, $2, $3, 'active')
+        INSERT INTO webhooks (account_id, url, encrypted_secret, secret_key_id, status, livemode)
+        SELECT $1, $2, $3, $4, $5, livemode FROM accounts WHERE id = $1
         RETURNING *
         "#,
     )
     .bind(account_id)
     .bind(&url)
-    .bind(&secret)
+    .bind(encrypted_secret)
+    .bind(secret_key_id)
+    .bind(status)
     .fetch_one(conn)
     .await
     .map_err(|e| {
@@ -67,15 +128,19 @@ pub async fn get_active_webhooks_for_account(
     .await
 }
 
-/// Get all webhooks for an account (including inactive)
+/// Get a page of webhooks for an account (including inactive), newest first
 pub async fn get_webhooks_for_account(
     account_id: Uuid,
+    limit: i64,
+    offset: i64,
     conn: &mut PgConnection,
 ) -> Result<Vec<Webhook>, ServiceError> {
     let webhooks = sqlx::query_as::<_, Webhook>(
-        "SELECT * FROM webhooks WHERE account_id = $1 ORDER BY created_at DESC",
+        "SELECT * FROM webhooks WHERE account_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
     )
     .bind(account_id)
+    .bind(limit)
+    .bind(offset)
     .fetch_all(conn)
     .await
     .map_err(|e| {
@@ -86,6 +151,23 @@ pub async fn get_webhooks_for_account(
     Ok(webhooks)
 }
 
+/// Count all webhooks for an account (including inactive)
+pub async fn count_webhooks_for_account(
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<i64, ServiceError> {
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM webhooks WHERE account_id = $1")
+        .bind(account_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, account_id = %account_id, "Failed to count webhooks");
+            ServiceError::DatabaseError(e.to_string())
+        })?;
+
+    Ok(count.0)
+}
+
 /// Get a specific webhook by ID
 pub async fn get_webhook_by_id(
     webhook_id: Uuid,
@@ -111,6 +193,24 @@ pub async fn get_webhook_by_id(
     Ok(webhook)
 }
 
+/// Get a webhook of any account, for operators
+pub async fn get_webhook(
+    webhook_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Webhook, ServiceError> {
+    sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE id = $1")
+        .bind(webhook_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, webhook_id = %webhook_id, "Webhook not found");
+            match e {
+                sqlx::Error::RowNotFound => ServiceError::WebhookNotFound(webhook_id.to_string()),
+                _ => ServiceError::DatabaseError(e.to_string()),
+            }
+        })
+}
+
 /// Delete a webhook
 pub async fn delete_webhook(
     webhook_id: Uuid,
@@ -140,6 +240,66 @@ pub async fn delete_webhook(
     Ok(())
 }
 
+/// Update a webhook's configuration
+/// Scoped by account_id like `delete_webhook`, so a foreign webhook reads as not found
+/// A new `url` must pass the handshake again, so the webhook moves to
+/// `pending_verification` unless it is disabled
+pub async fn update_webhook(
+    webhook_id: Uuid,
+    account_id: Uuid,
+    changes: WebhookUpdate,
+    conn: &mut PgConnection,
+) -> Result<Webhook, ServiceError> {
+    let webhook = sqlx::query_as::<_, Webhook>(
+        r#"
+        UPDATE webhooks
+        SET url = COALESCE($3, url),
+            events = COALESCE($4, events),
+            max_retries = COALESCE($5, max_retries),
+            retry_backoff_seconds = COALESCE($6, retry_backoff_seconds),
+            description = CASE WHEN $7::text IS NULL THEN description ELSE NULLIF($7, '') END,
+            headers = COALESCE($8, headers),
+            max_concurrency = COALESCE($9, max_concurrency),
+            signing_algorithm = COALESCE($10, signing_algorithm),
+            status = CASE
+                WHEN $3 IS NOT NULL AND $3 <> url AND status <> 'disabled'
+                    THEN 'pending_verification'
+                ELSE status
+            END,
+            updated_at = NOW()
+        WHERE id = $1 AND account_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(webhook_id)
+    .bind(account_id)
+    .bind(&changes.url)
+    .bind(&changes.events)
+    .bind(changes.max_retries)
+    .bind(changes.retry_backoff_seconds)
+    .bind(&changes.description)
+    .bind(&changes.headers)
+    .bind(changes.max_concurrency)
+    .bind(&changes.signing_algorithm)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, webhook_id = %webhook_id, "Failed to update webhook");
+        match e {
+            sqlx::Error::RowNotFound => ServiceError::WebhookNotFound(webhook_id.to_string()),
+            _ => ServiceError::DatabaseError(e.to_string()),
+        }
+    })?;
+
+    tracing::info!(
+        webhook_id = %webhook_id,
+        account_id = %account_id,
+        "Webhook updated successfully"
+    );
+
+    Ok(webhook)
+}
+
 /// Update webhook status (activate/deactivate)
 pub async fn update_webhook_status(
     webhook_id: Uuid,
@@ -176,3 +336,419 @@ pub async fn update_webhook_status(
 
     Ok(webhook)
 }
+
+/// Get webhooks whose secret is wrapped with a key other than `key_id`, in id order
+/// Pages by `after_id`, so rows that cannot be re-wrapped are not picked up again
+pub async fn get_webhooks_with_other_secret_key(
+    key_id: &str,
+    after_id: Option<Uuid>,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Webhook>, ServiceError> {
+    sqlx::query_as::<_, Webhook>(
+        r#"
+        SELECT * FROM webhooks
+        WHERE secret_key_id <> $1
+          AND ($2::uuid IS NULL OR id > $2)
+        ORDER BY id
+        LIMIT $3
+        "#,
+    )
+    .bind(key_id)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to fetch webhooks for secret re-wrap");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Replace a webhook's encrypted secret, if it is still wrapped with `previous_key_id`
+pub async fn update_webhook_secret(
+    webhook_id: Uuid,
+    previous_key_id: &str,
+    encrypted_secret: &str,
+    secret_key_id: &str,
+    conn: &mut PgConnection,
+) -> Result<(), ServiceError> {
+    sqlx::query(
+        r#"
+        UPDATE webhooks
+        SET encrypted_secret = $3, secret_key_id = $4
+        WHERE id = $1 AND secret_key_id = $2
+        "#,
+    )
+    .bind(webhook_id)
+    .bind(previous_key_id)
+    .bind(encrypted_secret)
+    .bind(secret_key_id)
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, webhook_id = %webhook_id, "Failed to update webhook secret");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(())
+}
+
+/// Filters for listing the delivery history of a webhook
+///
+/// Results are ordered newest first. `cursor` is the id of the last delivery
+/// from the previous page; only older deliveries are returned after it.
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryFilter {
+    pub status: Option<String>,
+    pub event_type: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub cursor: Option<Uuid>,
+    pub limit: i64,
+}
+
+/// Get deliveries for a webhook, newest first, honouring the given filter
+/// The cursor must be a delivery of the same webhook
+pub async fn get_deliveries_for_webhook(
+    webhook_id: Uuid,
+    filter: &DeliveryFilter,
+    conn: &mut PgConnection,
+) -> Result<Vec<WebhookDelivery>, ServiceError> {
+    if let Some(cursor) = filter.cursor {
+        let (found,) = sqlx::query_as::<_, (bool,)>(
+            "SELECT EXISTS (SELECT 1 FROM webhook_deliveries WHERE id = $1 AND webhook_id = $2)",
+        )
+        .bind(cursor)
+        .bind(webhook_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, webhook_id = %webhook_id, "Failed to resolve delivery cursor");
+            ServiceError::DatabaseError(e.to_string())
+        })?;
+
+        if !found {
+            return Err(ServiceError::InvalidInput(format!(
+                "cursor {} is not a delivery of this webhook",
+                cursor
+            )));
+        }
+    }
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT * FROM webhook_deliveries
+        WHERE webhook_id = $1
+          AND ($2::varchar IS NULL OR status = $2)
+          AND ($3::varchar IS NULL OR event_type = $3)
+          AND ($4::timestamptz IS NULL OR created_at >= $4)
+          AND ($5::timestamptz IS NULL OR created_at < $5)
+          AND (
+            $6::uuid IS NULL OR (created_at, id) < (
+                SELECT created_at, id FROM webhook_deliveries WHERE id = $6 AND webhook_id = $1
+            )
+          )
+        ORDER BY created_at DESC, id DESC
+        LIMIT $7
+        "#,
+    )
+    .bind(webhook_id)
+    .bind(&filter.status)
+    .bind(&filter.event_type)
+    .bind(filter.created_after)
+    .bind(filter.created_before)
+    .bind(filter.cursor)
+    .bind(filter.limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, webhook_id = %webhook_id, "Failed to fetch webhook deliveries");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(deliveries)
+}
+
+/// Get a single delivery, verifying it belongs to a webhook owned by the account
+pub async fn get_delivery_for_account(
+    delivery_id: Uuid,
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<WebhookDelivery, ServiceError> {
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT d.* FROM webhook_deliveries d
+        JOIN webhooks w ON w.id = d.webhook_id
+        WHERE d.id = $1 AND w.account_id = $2
+        "#,
+    )
+    .bind(delivery_id)
+    .bind(account_id)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, delivery_id = %delivery_id, "Webhook delivery not found");
+        match e {
+            sqlx::Error::RowNotFound => {
+                ServiceError::WebhookDeliveryNotFound(delivery_id.to_string())
+            }
+            _ => ServiceError::DatabaseError(e.to_string()),
+        }
+    })?;
+
+    Ok(delivery)
+}
+
+/// Get a delivery of any account, for operators
+pub async fn get_delivery(
+    delivery_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<WebhookDelivery, ServiceError> {
+    sqlx::query_as::<_, WebhookDelivery>("SELECT * FROM webhook_deliveries WHERE id = $1")
+        .bind(delivery_id)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, delivery_id = %delivery_id, "Webhook delivery not found");
+            match e {
+                sqlx::Error::RowNotFound => {
+                    ServiceError::WebhookDeliveryNotFound(delivery_id.to_string())
+                }
+                _ => ServiceError::DatabaseError(e.to_string()),
+            }
+        })
+}
+
+/// Selects dead-lettered deliveries of an account for listing, redrive or discard
+pub struct DeadLetterFilter {
+    pub webhook_id: Option<Uuid>,
+    pub dead_lettered_after: Option<DateTime<Utc>>,
+    pub dead_lettered_before: Option<DateTime<Utc>>,
+}
+
+/// Dead-letter queue size of a single webhook
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DeadLetterCount {
+    pub webhook_id: Uuid,
+    pub count: i64,
+    pub oldest_dead_lettered_at: Option<DateTime<Utc>>,
+}
+
+/// Get dead-lettered deliveries of an account, most recently dead-lettered first
+/// The cursor must be a dead-lettered delivery of the same account
+pub async fn get_dead_letters_for_account(
+    account_id: Uuid,
+    filter: &DeadLetterFilter,
+    cursor: Option<Uuid>,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<WebhookDelivery>, ServiceError> {
+    if let Some(cursor) = cursor {
+        let (found,) = sqlx::query_as::<_, (bool,)>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM webhook_deliveries d
+                JOIN webhooks w ON w.id = d.webhook_id
+                WHERE d.id = $1 AND w.account_id = $2 AND d.dead_lettered_at IS NOT NULL
+            )
+            "#,
+        )
+        .bind(cursor)
+        .bind(account_id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, account_id = %account_id, "Failed to resolve dead-letter cursor");
+            ServiceError::DatabaseError(e.to_string())
+        })?;
+        if !found {
+            return Err(ServiceError::InvalidInput(format!(
+                "cursor {} is not a dead-lettered delivery of this account",
+                cursor
+            )));
+        }
+    }
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT id, webhook_id, transaction_id, event_id, event_type, payload, sequence, status,
+               attempt_count, max_attempts, next_retry_at, http_status_code,
+               response_body, error_message, created_at, delivered_at, failed_at,
+               dead_lettered_at
+        FROM webhook_dead_letters
+        WHERE account_id = $1
+          AND ($2::uuid IS NULL OR webhook_id = $2)
+          AND ($3::timestamptz IS NULL OR dead_lettered_at >= $3)
+          AND ($4::timestamptz IS NULL OR dead_lettered_at < $4)
+          AND (
+            $5::uuid IS NULL OR (dead_lettered_at, id) < (
+                SELECT d.dead_lettered_at, d.id FROM webhook_deliveries d
+                JOIN webhooks w ON w.id = d.webhook_id
+                WHERE d.id = $5 AND w.account_id = $1
+            )
+          )
+        ORDER BY dead_lettered_at DESC, id DESC
+        LIMIT $6
+        "#,
+    )
+    .bind(account_id)
+    .bind(filter.webhook_id)
+    .bind(filter.dead_lettered_after)
+    .bind(filter.dead_lettered_before)
+    .bind(cursor)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to fetch dead-lettered deliveries");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(deliveries)
+}
+
+/// Count dead-lettered deliveries of an account, per webhook
+pub async fn count_dead_letters_for_account(
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<Vec<DeadLetterCount>, ServiceError> {
+    let counts = sqlx::query_as::<_, DeadLetterCount>(
+        r#"
+        SELECT webhook_id, COUNT(*) AS count, MIN(dead_lettered_at) AS oldest_dead_lettered_at
+        FROM webhook_dead_letters
+        WHERE account_id = $1
+        GROUP BY webhook_id
+        ORDER BY count DESC
+        "#,
+    )
+    .bind(account_id)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to count dead-lettered deliveries");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(counts)
+}
+
+/// Move up to `limit` dead-lettered deliveries back to `pending` for redelivery
+///
+/// Only deliveries of active webhooks are picked. Attempt counters are reset so the
+/// dispatcher gets the full retry budget again. Returns the requeued deliveries.
+pub async fn requeue_dead_letters(
+    account_id: Uuid,
+    filter: &DeadLetterFilter,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<WebhookDelivery>, ServiceError> {
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = 'pending',
+            attempt_count = 0,
+            next_retry_at = NOW() + $6 * INTERVAL '1 second',
+            failed_at = NULL,
+            dead_lettered_at = NULL
+        WHERE id IN (
+            SELECT d.id FROM webhook_deliveries d
+            JOIN webhooks w ON w.id = d.webhook_id
+            WHERE d.status = 'dead_lettered'
+              AND w.account_id = $1
+              AND w.status = 'active'
+              AND ($2::uuid IS NULL OR d.webhook_id = $2)
+              AND ($3::timestamptz IS NULL OR d.dead_lettered_at >= $3)
+              AND ($4::timestamptz IS NULL OR d.dead_lettered_at < $4)
+            ORDER BY d.dead_lettered_at
+            LIMIT $5
+            FOR UPDATE OF d SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(account_id)
+    .bind(filter.webhook_id)
+    .bind(filter.dead_lettered_after)
+    .bind(filter.dead_lettered_before)
+    .bind(limit)
+    .bind(DELIVERY_LEASE_SECONDS)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to requeue dead-lettered deliveries");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(deliveries)
+}
+
+/// Claim up to `limit` pending deliveries whose retry is due, oldest first
+///
+/// Only deliveries of active webhooks are picked. Each claimed delivery is leased for
+/// `DELIVERY_LEASE_SECONDS`, so other instances skip it while it is being attempted.
+pub async fn claim_due_deliveries(
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<WebhookDelivery>, ServiceError> {
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        UPDATE webhook_deliveries
+        SET next_retry_at = NOW() + $2 * INTERVAL '1 second'
+        WHERE id IN (
+            SELECT d.id FROM webhook_deliveries d
+            JOIN webhooks w ON w.id = d.webhook_id
+            WHERE d.status = 'pending'
+              AND d.next_retry_at <= NOW()
+              AND w.status = 'active'
+            ORDER BY d.next_retry_at
+            LIMIT $1
+            FOR UPDATE OF d SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(limit)
+    .bind(DELIVERY_LEASE_SECONDS)
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to claim due webhook deliveries");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(deliveries)
+}
+
+/// Mark dead-lettered deliveries as `discarded`, returning how many were discarded
+pub async fn discard_dead_letters(
+    account_id: Uuid,
+    filter: &DeadLetterFilter,
+    conn: &mut PgConnection,
+) -> Result<u64, ServiceError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE webhook_deliveries d
+        SET status = 'discarded'
+        FROM webhooks w
+        WHERE w.id = d.webhook_id
+          AND d.status = 'dead_lettered'
+          AND w.account_id = $1
+          AND ($2::uuid IS NULL OR d.webhook_id = $2)
+          AND ($3::timestamptz IS NULL OR d.dead_lettered_at >= $3)
+          AND ($4::timestamptz IS NULL OR d.dead_lettered_at < $4)
+        "#,
+    )
+    .bind(account_id)
+    .bind(filter.webhook_id)
+    .bind(filter.dead_lettered_after)
+    .bind(filter.dead_lettered_before)
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, account_id = %account_id, "Failed to discard dead-lettered deliveries");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(result.rows_affected())
+}
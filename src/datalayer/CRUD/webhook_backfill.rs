@@ -0,0 +1,178 @@
+use crate::errors::errors::ServiceError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WebhookBackfill {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub account_id: Uuid,
+    pub event_types: serde_json::Value, // JSONB array
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    pub status: String,
+    pub total_events: i32,
+    pub queued_events: i32,
+    pub last_sequence: i64,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl WebhookBackfill {
+    /// Event types to replay, as stored in the `event_types` array
+    pub fn event_types(&self) -> Vec<String> {
+        serde_json::from_value(self.event_types.clone()).unwrap_or_default()
+    }
+}
+
+/// Create a running backfill, counting the events it will replay
+/// Fails with `WebhookBackfillInProgress` when the webhook already has one running
+pub async fn create_backfill(
+    webhook_id: Uuid,
+    account_id: Uuid,
+    event_types: &[String],
+    start_at: DateTime<Utc>,
+    end_at: DateTime<Utc>,
+    conn: &mut PgConnection,
+) -> Result<WebhookBackfill, ServiceError> {
+    let backfill = sqlx::query_as::<_, WebhookBackfill>(
+        r#"
+        INSERT INTO webhook_backfills (webhook_id, account_id, event_types, start_at, end_at, total_events)
+        VALUES ($1, $2, $3, $4, $5, (
+            SELECT COUNT(*)::int FROM events
+            WHERE account_id = $2
+              AND event_type = ANY($6)
+              AND created_at >= $4
+              AND created_at < $5
+        ))
+        RETURNING *
+        "#,
+    )
+    .bind(webhook_id)
+    .bind(account_id)
+    .bind(serde_json::json!(event_types))
+    .bind(start_at)
+    .bind(end_at)
+    .bind(event_types)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, webhook_id = %webhook_id, "Failed to create webhook backfill");
+        match e {
+            sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+                ServiceError::WebhookBackfillInProgress(webhook_id.to_string())
+            }
+            _ => ServiceError::DatabaseError(e.to_string()),
+        }
+    })?;
+
+    tracing::info!(
+        backfill_id = %backfill.id,
+        webhook_id = %webhook_id,
+        total_events = backfill.total_events,
+        "Webhook backfill created"
+    );
+
+    Ok(backfill)
+}
+
+/// Get a backfill of a webhook, verifying it belongs to the account
+pub async fn get_backfill_for_account(
+    backfill_id: Uuid,
+    webhook_id: Uuid,
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<WebhookBackfill, ServiceError> {
+    sqlx::query_as::<_, WebhookBackfill>(
+        "SELECT * FROM webhook_backfills WHERE id = $1 AND webhook_id = $2 AND account_id = $3",
+    )
+    .bind(backfill_id)
+    .bind(webhook_id)
+    .bind(account_id)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, backfill_id = %backfill_id, "Webhook backfill not found");
+        match e {
+            sqlx::Error::RowNotFound => {
+                ServiceError::WebhookBackfillNotFound(backfill_id.to_string())
+            }
+            _ => ServiceError::DatabaseError(e.to_string()),
+        }
+    })
+}
+
+/// Get every backfill still marked as running, e.g. to resume them after a restart
+pub async fn get_running_backfills(
+    conn: &mut PgConnection,
+) -> Result<Vec<WebhookBackfill>, ServiceError> {
+    sqlx::query_as::<_, WebhookBackfill>(
+        "SELECT * FROM webhook_backfills WHERE status = 'running' ORDER BY created_at",
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to fetch running webhook backfills");
+        ServiceError::DatabaseError(e.to_string())
+    })
+}
+
+/// Record that events up to `last_sequence` have been queued for delivery
+pub async fn record_backfill_progress(
+    backfill_id: Uuid,
+    queued_events: i32,
+    last_sequence: i64,
+    conn: &mut PgConnection,
+) -> Result<(), ServiceError> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_backfills
+        SET queued_events = $2, last_sequence = $3, updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(backfill_id)
+    .bind(queued_events)
+    .bind(last_sequence)
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, backfill_id = %backfill_id, "Failed to record webhook backfill progress");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(())
+}
+
+/// Mark a backfill as `completed` or `failed`
+pub async fn finish_backfill(
+    backfill_id: Uuid,
+    status: &str,
+    error_message: Option<&str>,
+    conn: &mut PgConnection,
+) -> Result<(), ServiceError> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_backfills
+        SET status = $2, error_message = $3, updated_at = NOW(), completed_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(backfill_id)
+    .bind(status)
+    .bind(error_message)
+    .execute(conn)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, backfill_id = %backfill_id, "Failed to finish webhook backfill");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    tracing::info!(backfill_id = %backfill_id, status = %status, "Webhook backfill finished");
+
+    Ok(())
+}
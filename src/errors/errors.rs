@@ -15,6 +15,16 @@ pub enum ServiceError {
     ApiKeyExpired,
     InsufficientPermissions(String),
     Unauthorized(String),
+    ApiKeyNotFound(String),
+    ApiKeyNotActive(String),
+    LastActiveApiKey(String),
+
+    // User & Membership Errors
+    InvalidCredentials,
+    UserAlreadyExists(String),
+    MemberNotFound(String),
+    MemberAlreadyExists(String),
+    LastOwner(String),
 
     // Account Errors
     AccountNotFound(String),
@@ -43,12 +53,18 @@ pub enum ServiceError {
 
     // Webhook Errors
     WebhookNotFound(String),
+    WebhookDeliveryNotFound(String),
     WebhookDeliveryFailed {
         webhook_id: String,
         reason: String,
     },
     InvalidWebhookUrl(String),
     WebhookAlreadyExists(String),
+    WebhookBackfillNotFound(String),
+    WebhookBackfillInProgress(String),
+
+    // Event Errors
+    EventNotFound(String),
 
     // Rate Limiting
     RateLimitExceeded {
@@ -101,10 +117,29 @@ impl fmt::Display for ServiceError {
             ServiceError::InsufficientPermissions(reason) => {
                 write!(f, "Insufficient permissions for this operation: {}", reason)
             }
+            ServiceError::ApiKeyNotFound(id) => write!(f, "API key not found: {}", id),
+            ServiceError::ApiKeyNotActive(id) => write!(f, "API key is not active: {}", id),
+            ServiceError::LastActiveApiKey(id) => {
+                write!(f, "API key {} is the last active key of its account", id)
+            }
             ServiceError::Unauthorized(reason) => {
                 write!(f, "Unauthorized: {}", reason)
             }
 
+            ServiceError::InvalidCredentials => write!(f, "Email or password is incorrect"),
+            ServiceError::UserAlreadyExists(email) => {
+                write!(f, "A user already exists with email: {}", email)
+            }
+            ServiceError::MemberNotFound(user_id) => {
+                write!(f, "User {} is not a member of the account", user_id)
+            }
+            ServiceError::MemberAlreadyExists(user_id) => {
+                write!(f, "User {} is already a member of the account", user_id)
+            }
+            ServiceError::LastOwner(user_id) => {
+                write!(f, "User {} is the last owner of the account", user_id)
+            }
+
             ServiceError::InvalidCurrency => write!(f, "Invalid currency"),
 
             ServiceError::AccountNotFound(id) => write!(f, "Account not found: {}", id),
@@ -138,13 +173,27 @@ impl fmt::Display for ServiceError {
             }
 
             ServiceError::WebhookNotFound(id) => write!(f, "Webhook not found: {}", id),
+            ServiceError::WebhookDeliveryNotFound(id) => {
+                write!(f, "Webhook delivery not found: {}", id)
+            }
             ServiceError::WebhookDeliveryFailed { webhook_id, reason } => {
                 write!(f, "Webhook delivery failed for {}: {}", webhook_id, reason)
             }
             ServiceError::InvalidWebhookUrl(url) => write!(f, "Invalid webhook URL: {}", url),
+            ServiceError::EventNotFound(id) => write!(f, "Event not found: {}", id),
             ServiceError::WebhookAlreadyExists(url) => {
                 write!(f, "Webhook already exists for URL: {}", url)
             }
+            ServiceError::WebhookBackfillNotFound(id) => {
+                write!(f, "Webhook backfill not found: {}", id)
+            }
+            ServiceError::WebhookBackfillInProgress(webhook_id) => {
+                write!(
+                    f,
+                    "A backfill is already running for webhook {}",
+                    webhook_id
+                )
+            }
 
             ServiceError::RateLimitExceeded {
                 limit,
@@ -191,7 +240,8 @@ impl ServiceError {
             // 401 Unauthorized
             ServiceError::InvalidApiKey
             | ServiceError::MissingApiKey
-            | ServiceError::ApiKeyExpired => StatusCode::UNAUTHORIZED,
+            | ServiceError::ApiKeyExpired
+            | ServiceError::InvalidCredentials => StatusCode::UNAUTHORIZED,
 
             // 403 Forbidden
             ServiceError::InsufficientPermissions(_) | ServiceError::Unauthorized(_) => {
@@ -200,8 +250,13 @@ impl ServiceError {
 
             // 404 Not Found
             ServiceError::AccountNotFound(_)
+            | ServiceError::ApiKeyNotFound(_)
             | ServiceError::TransactionNotFound(_)
-            | ServiceError::WebhookNotFound(_) => StatusCode::NOT_FOUND,
+            | ServiceError::WebhookNotFound(_)
+            | ServiceError::WebhookDeliveryNotFound(_)
+            | ServiceError::WebhookBackfillNotFound(_)
+            | ServiceError::MemberNotFound(_)
+            | ServiceError::EventNotFound(_) => StatusCode::NOT_FOUND,
 
             ServiceError::InvalidCurrency => StatusCode::BAD_REQUEST,
 
@@ -209,6 +264,12 @@ impl ServiceError {
             ServiceError::AccountAlreadyExists(_)
             | ServiceError::DuplicateTransaction(_)
             | ServiceError::WebhookAlreadyExists(_)
+            | ServiceError::WebhookBackfillInProgress(_)
+            | ServiceError::ApiKeyNotActive(_)
+            | ServiceError::LastActiveApiKey(_)
+            | ServiceError::UserAlreadyExists(_)
+            | ServiceError::MemberAlreadyExists(_)
+            | ServiceError::LastOwner(_)
             | ServiceError::TransactionConflict
             | ServiceError::IdempotencyKeyMismatch { .. } => StatusCode::CONFLICT,
 
@@ -252,6 +313,15 @@ impl ServiceError {
             ServiceError::ApiKeyExpired => "API_KEY_EXPIRED",
             ServiceError::InsufficientPermissions(_) => "INSUFFICIENT_PERMISSIONS",
             ServiceError::Unauthorized(_) => "UNAUTHORIZED",
+            ServiceError::ApiKeyNotFound(_) => "API_KEY_NOT_FOUND",
+            ServiceError::ApiKeyNotActive(_) => "API_KEY_NOT_ACTIVE",
+            ServiceError::LastActiveApiKey(_) => "LAST_ACTIVE_API_KEY",
+
+            ServiceError::InvalidCredentials => "INVALID_CREDENTIALS",
+            ServiceError::UserAlreadyExists(_) => "USER_ALREADY_EXISTS",
+            ServiceError::MemberNotFound(_) => "MEMBER_NOT_FOUND",
+            ServiceError::MemberAlreadyExists(_) => "MEMBER_ALREADY_EXISTS",
+            ServiceError::LastOwner(_) => "LAST_OWNER",
 
             ServiceError::AccountNotFound(_) => "ACCOUNT_NOT_FOUND",
             ServiceError::AccountAlreadyExists(_) => "ACCOUNT_ALREADY_EXISTS",
@@ -271,9 +341,14 @@ impl ServiceError {
             ServiceError::IdempotencyKeyMismatch { .. } => "IDEMPOTENCY_KEY_MISMATCH",
 
             ServiceError::WebhookNotFound(_) => "WEBHOOK_NOT_FOUND",
+            ServiceError::WebhookDeliveryNotFound(_) => "WEBHOOK_DELIVERY_NOT_FOUND",
             ServiceError::WebhookDeliveryFailed { .. } => "WEBHOOK_DELIVERY_FAILED",
             ServiceError::InvalidWebhookUrl(_) => "INVALID_WEBHOOK_URL",
             ServiceError::WebhookAlreadyExists(_) => "WEBHOOK_ALREADY_EXISTS",
+            ServiceError::WebhookBackfillNotFound(_) => "WEBHOOK_BACKFILL_NOT_FOUND",
+            ServiceError::WebhookBackfillInProgress(_) => "WEBHOOK_BACKFILL_IN_PROGRESS",
+
+            ServiceError::EventNotFound(_) => "EVENT_NOT_FOUND",
 
             ServiceError::RateLimitExceeded { .. } => "RATE_LIMIT_EXCEEDED",
 
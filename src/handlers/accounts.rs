@@ -1,4 +1,7 @@
-use crate::{controllayer::accounts::accounts_handler, datalayer::CRUD::helper::conversion};
+use crate::{
+    controllayer::accounts::accounts_handler,
+    datalayer::CRUD::{helper::conversion, types::Account},
+};
 use axum::{
     Json,
     extract::{Path, State},
@@ -15,6 +18,8 @@ pub struct CreateAccountRequest {
     pub business_name: String,
     pub email: String,
     pub currency: String,
+    /// `false` creates a test-mode account with a `dodo_test_` key; defaults to live
+    pub livemode: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -34,6 +39,8 @@ pub struct UpdateAccountRequest {
     pub business_name: Option<String>,
     pub email: Option<String>,
     pub status: Option<String>,
+    /// Only accept signed requests for write scopes
+    pub require_signed_writes: Option<bool>,
 }
 
 // ===== RESPONSE DTOs =====
@@ -43,12 +50,31 @@ pub struct AccountResponse {
     pub id: Uuid,
     pub business_name: String,
     pub email: String,
+    pub livemode: bool,
+    #[serde(default)]
+    pub require_signed_writes: bool,
     pub balance: Option<f64>,
     pub currency: Option<String>,
     pub status: Option<String>,
     pub created_at: Option<String>,
 }
 
+impl From<Account> for AccountResponse {
+    fn from(a: Account) -> Self {
+        Self {
+            id: a.id,
+            business_name: a.business_name,
+            email: a.email,
+            livemode: a.livemode,
+            require_signed_writes: a.require_signed_writes,
+            balance: Some(a.balance),
+            currency: Some(a.currency),
+            status: Some(a.status),
+            created_at: Some(a.created_at.to_rfc3339()),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct CreateAccountResponse {
     pub account: AccountResponse,
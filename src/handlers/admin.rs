@@ -0,0 +1,548 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    controllayer::{
+        accounts::accounts_handler::{AccountStatusChange, set_account_status},
+        transfers::queries::get_transaction_group,
+    },
+    datalayer::{
+        CRUD::{
+            accounts::{AccountBuilder, AccountSearch, search_accounts},
+            api_key::{get_api_key_for_auth, get_api_keys_for_account},
+            audit_log::{
+                AuditEntry, AuditFilter, NewAuditEntry, create_audit_entry, get_audit_entries,
+            },
+            redis::redis::RateLimitCounter,
+            webhook::{get_delivery, get_webhook},
+        },
+        db_ops::constants::POOL_STATE_TRACKER,
+    },
+    errors::errors::{ServiceError, create_error_response},
+    handlers::{
+        accounts::AccountResponse,
+        api_keys::{ApiKeyResponse, begin_transaction, finish_transaction},
+        transfer::TransferResponse,
+    },
+    middleware::admin_auth::AdminPrincipal,
+    services::{
+        WebhookDispatcher,
+        circuit_breaker::{BreakerSnapshot, circuit_breakers},
+    },
+    state::AppState,
+};
+
+// ===== REQUEST DTOs =====
+
+#[derive(Debug, Deserialize)]
+pub struct SearchAccountsQuery {
+    /// An account id, or part of a business name or email
+    pub query: Option<String>,
+    pub status: Option<String>,
+    pub livemode: Option<bool>,
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountStatusRequest {
+    /// Why the operator changes the status, kept in the audit log
+    pub reason: String,
+}
+
+/// Names the Redis rate-limit counter to clear: an API key's or a client address's
+#[derive(Debug, Deserialize)]
+pub struct ResetRateLimitRequest {
+    pub api_key_id: Option<Uuid>,
+    pub ip: Option<IpAddr>,
+    /// Request path the counter is kept for, e.g. `/api/v1/transfer`
+    pub endpoint: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminAuditLogQuery {
+    pub account_id: Option<Uuid>,
+    pub action: Option<String>,
+    pub actor_type: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+// ===== RESPONSE DTOs =====
+
+#[derive(Debug, Serialize)]
+pub struct CircuitBreakersResponse {
+    pub breakers: Vec<BreakerSnapshot>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountSearchResponse {
+    pub accounts: Vec<AccountResponse>,
+    pub has_more: bool,
+    pub next_cursor: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminAccountResponse {
+    #[serde(flatten)]
+    pub account: AccountResponse,
+    pub api_keys: Vec<ApiKeyResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminTransactionResponse {
+    pub transaction: TransferResponse,
+    /// The other transfer, debit and credit records of the same transfer
+    pub related: Vec<TransferResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminAuditLogResponse {
+    pub entries: Vec<AuditEntry>,
+    pub has_more: bool,
+    pub next_cursor: Option<Uuid>,
+}
+
+// ===== HANDLERS =====
+
+/// GET /admin/v1/webhooks/circuit-breakers
+/// Circuit breaker state of every webhook destination this instance has contacted
+#[instrument(fields(service = "/admin/v1/webhooks/circuit-breakers"))]
+pub async fn list_circuit_breakers() -> impl IntoResponse {
+    let breakers = circuit_breakers().snapshot();
+    tracing::info!(hosts = breakers.len(), "Listing webhook circuit breakers");
+
+    (StatusCode::OK, Json(CircuitBreakersResponse { breakers }))
+}
+
+/// GET /admin/v1/accounts
+/// Search accounts of every business, newest first
+#[instrument(fields(service = "/admin/v1/accounts"))]
+pub async fn search_all_accounts(Query(params): Query<SearchAccountsQuery>) -> Response {
+    tracing::info!(query = ?params.query, status = ?params.status, "Searching accounts");
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => return ServiceError::DatabaseConnectionError.into_response(),
+    };
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return ServiceError::DatabaseConnectionError.into_response();
+        }
+    };
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    // Fetch one extra row to know whether another page exists
+    let search = AccountSearch {
+        query: params.query.filter(|query| !query.trim().is_empty()),
+        status: params.status,
+        livemode: params.livemode,
+        cursor: params.cursor,
+        limit: limit + 1,
+    };
+
+    let result = search_accounts(&search, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(mut accounts) => {
+            let has_more = accounts.len() as i64 > limit;
+            accounts.truncate(limit as usize);
+            let next_cursor = if has_more {
+                accounts.last().map(|a| a.id)
+            } else {
+                None
+            };
+
+            (
+                StatusCode::OK,
+                Json(AccountSearchResponse {
+                    accounts: accounts.into_iter().map(AccountResponse::from).collect(),
+                    has_more,
+                    next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /admin/v1/accounts/:id
+/// Any account, live or test, with its API keys
+#[instrument(fields(service = "/admin/v1/accounts/:id"))]
+pub async fn get_any_account(Path(account_id): Path<Uuid>) -> Response {
+    tracing::info!(account_id = %account_id, "Getting account for admin");
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => return ServiceError::DatabaseConnectionError.into_response(),
+    };
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return ServiceError::DatabaseConnectionError.into_response();
+        }
+    };
+
+    let account = AccountBuilder::new()
+        .id(account_id)
+        .expect_id()
+        .expect_business_name()
+        .expect_email()
+        .expect_balance()
+        .expect_currency()
+        .expect_status()
+        .expect_created_at()
+        .expect_updated_at()
+        .read(Some(&mut conn))
+        .await;
+    let account = match account {
+        Ok(account) => account,
+        Err(_) => {
+            tracker.return_connection(conn);
+            return ServiceError::AccountNotFound(account_id.to_string()).into_response();
+        }
+    };
+
+    let api_keys = get_api_keys_for_account(account_id, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match api_keys {
+        Ok(api_keys) => (
+            StatusCode::OK,
+            Json(AdminAccountResponse {
+                account: account.into(),
+                api_keys: api_keys.into_iter().map(ApiKeyResponse::from).collect(),
+            }),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /admin/v1/accounts/:id/suspend
+/// Block an active account's API keys, users and incoming credits
+#[instrument(fields(service = "/admin/v1/accounts/:id/suspend"))]
+pub async fn suspend_account(
+    Extension(admin): Extension<AdminPrincipal>,
+    Path(account_id): Path<Uuid>,
+    Json(payload): Json<AccountStatusRequest>,
+) -> Response {
+    change_account_status(admin, account_id, AccountStatusChange::Suspend, payload).await
+}
+
+/// POST /admin/v1/accounts/:id/reactivate
+/// Lift the suspension of an account
+#[instrument(fields(service = "/admin/v1/accounts/:id/reactivate"))]
+pub async fn reactivate_account(
+    Extension(admin): Extension<AdminPrincipal>,
+    Path(account_id): Path<Uuid>,
+    Json(payload): Json<AccountStatusRequest>,
+) -> Response {
+    change_account_status(admin, account_id, AccountStatusChange::Reactivate, payload).await
+}
+
+/// POST /admin/v1/accounts/:id/close
+/// Close an account with a zero balance for good
+#[instrument(fields(service = "/admin/v1/accounts/:id/close"))]
+pub async fn close_account(
+    Extension(admin): Extension<AdminPrincipal>,
+    Path(account_id): Path<Uuid>,
+    Json(payload): Json<AccountStatusRequest>,
+) -> Response {
+    change_account_status(admin, account_id, AccountStatusChange::Close, payload).await
+}
+
+/// GET /admin/v1/transactions/:id
+/// Any transaction, with the other records of its transfer
+#[instrument(fields(service = "/admin/v1/transactions/:id"))]
+pub async fn get_any_transaction(Path(transaction_id): Path<Uuid>) -> Response {
+    match get_transaction_group(transaction_id).await {
+        Ok((transaction, related)) => (
+            StatusCode::OK,
+            Json(AdminTransactionResponse {
+                transaction,
+                related,
+            }),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /admin/v1/webhooks/deliveries/:id/retry
+/// Send a logged delivery of any account again, whatever its status
+#[instrument(fields(service = "/admin/v1/webhooks/deliveries/:id/retry"))]
+pub async fn retry_webhook_delivery(
+    Extension(admin): Extension<AdminPrincipal>,
+    Path(delivery_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(admin = %admin.name, delivery_id = %delivery_id, "Retrying webhook delivery");
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => return ServiceError::DatabaseConnectionError.into_response(),
+    };
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return ServiceError::DatabaseConnectionError.into_response();
+        }
+    };
+
+    let result = async {
+        let delivery = get_delivery(delivery_id, &mut conn).await?;
+        let webhook = get_webhook(delivery.webhook_id, &mut conn).await?;
+        let entry = NewAuditEntry::new(
+            &admin.audit_actor(),
+            Some(webhook.account_id),
+            "webhook.delivery_retried",
+            "webhook_delivery",
+            delivery.id,
+        )
+        .details(serde_json::json!({
+            "webhook_id": webhook.id,
+            "event_type": delivery.event_type,
+            "status": delivery.status,
+        }));
+        create_audit_entry(entry, &mut conn).await?;
+        Ok::<_, ServiceError>((webhook, delivery))
+    }
+    .await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok((webhook, delivery)) => {
+            let webhook_id = webhook.id;
+            WebhookDispatcher::new().redeliver(webhook, delivery);
+
+            (
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({
+                    "message": "Redelivery scheduled",
+                    "delivery_id": delivery_id,
+                    "webhook_id": webhook_id
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /admin/v1/rate-limits/reset
+/// Clear the rate-limit counter of an API key or client address on one endpoint
+#[instrument(skip(state), fields(service = "/admin/v1/rate-limits/reset"))]
+pub async fn reset_rate_limit(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AdminPrincipal>,
+    Json(payload): Json<ResetRateLimitRequest>,
+) -> Response {
+    tracing::info!(
+        admin = %admin.name,
+        api_key_id = ?payload.api_key_id,
+        ip = ?payload.ip,
+        endpoint = %payload.endpoint,
+        "Resetting rate limit"
+    );
+
+    if !payload.endpoint.starts_with('/') {
+        return create_error_response(
+            StatusCode::BAD_REQUEST,
+            "invalid_endpoint",
+            "endpoint must be a request path starting with '/'",
+            None,
+        );
+    }
+    let target = match (payload.api_key_id, payload.ip) {
+        (Some(api_key_id), None) => RateLimitTarget::ApiKey(api_key_id),
+        (None, Some(ip)) => RateLimitTarget::Ip(ip),
+        _ => {
+            return create_error_response(
+                StatusCode::BAD_REQUEST,
+                "invalid_rate_limit_target",
+                "Give exactly one of api_key_id and ip",
+                None,
+            );
+        }
+    };
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => return ServiceError::DatabaseConnectionError.into_response(),
+    };
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return ServiceError::DatabaseConnectionError.into_response();
+        }
+    };
+
+    // The entry is only kept when Redis dropped the counter
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let mut counter = RateLimitCounter::new((*state.redis).clone());
+                let details = serde_json::json!({ "endpoint": payload.endpoint });
+                let reset = match target {
+                    RateLimitTarget::ApiKey(api_key_id) => {
+                        let api_key = get_api_key_for_auth(api_key_id, &mut conn).await?;
+                        let entry = NewAuditEntry::new(
+                            &admin.audit_actor(),
+                            Some(api_key.account_id),
+                            "rate_limit.reset",
+                            "api_key",
+                            api_key.id,
+                        )
+                        .details(details);
+                        create_audit_entry(entry, &mut conn).await?;
+                        counter.reset_count(api_key.id, &payload.endpoint).await
+                    }
+                    RateLimitTarget::Ip(ip) => {
+                        let entry = NewAuditEntry::new(
+                            &admin.audit_actor(),
+                            None,
+                            "rate_limit.reset",
+                            "ip",
+                            ip,
+                        )
+                        .details(details);
+                        create_audit_entry(entry, &mut conn).await?;
+                        counter
+                            .reset_count_by_key(&format!("ip:{}:{}", ip, payload.endpoint))
+                            .await
+                    }
+                };
+                reset.map_err(|e| ServiceError::DatabaseError(format!("Redis error: {}", e)))
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(()) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "message": "Rate limit reset",
+                "api_key_id": payload.api_key_id,
+                "ip": payload.ip,
+                "endpoint": payload.endpoint
+            })),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /admin/v1/audit-log
+/// Audit entries of every account, including operator actions, newest first
+#[instrument(fields(service = "/admin/v1/audit-log"))]
+pub async fn list_all_audit_log(Query(params): Query<AdminAuditLogQuery>) -> Response {
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => return ServiceError::DatabaseConnectionError.into_response(),
+    };
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return ServiceError::DatabaseConnectionError.into_response();
+        }
+    };
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    // Fetch one extra row to know whether another page exists
+    let filter = AuditFilter {
+        action: params.action,
+        actor_type: params.actor_type,
+        actor_id: params.actor_id,
+        target_type: params.target_type,
+        target_id: params.target_id,
+        created_after: params.created_after,
+        created_before: params.created_before,
+        cursor: params.cursor,
+        limit: limit + 1,
+    };
+
+    let result = get_audit_entries(params.account_id, &filter, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(mut entries) => {
+            let has_more = entries.len() as i64 > limit;
+            entries.truncate(limit as usize);
+            let next_cursor = if has_more {
+                entries.last().map(|e| e.id)
+            } else {
+                None
+            };
+
+            (
+                StatusCode::OK,
+                Json(AdminAuditLogResponse {
+                    entries,
+                    has_more,
+                    next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+// ===== HELPERS =====
+
+/// Whose rate-limit counter a reset clears
+#[derive(Debug, Clone, Copy)]
+enum RateLimitTarget {
+    ApiKey(Uuid),
+    Ip(IpAddr),
+}
+
+async fn change_account_status(
+    admin: AdminPrincipal,
+    account_id: Uuid,
+    change: AccountStatusChange,
+    payload: AccountStatusRequest,
+) -> Response {
+    let reason = payload.reason.trim();
+    if reason.is_empty() {
+        return create_error_response(
+            StatusCode::BAD_REQUEST,
+            "reason_required",
+            "A reason is required to change an account's status",
+            None,
+        );
+    }
+
+    set_account_status(
+        account_id,
+        change,
+        Some(reason.to_string()),
+        &admin.audit_actor(),
+    )
+    .await
+}
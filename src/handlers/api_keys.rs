@@ -0,0 +1,1119 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Postgres, pool::PoolConnection};
+use tracing::instrument;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::{
+    datalayer::{
+        CRUD::{
+            api_key::{
+                ApiKeyBuilder, expire_api_key, get_api_key_daily_usage, get_api_key_for_account,
+                get_api_keys_for_account, lock_active_api_keys, set_api_key_allowed_ips,
+                set_api_key_signing_secret,
+            },
+            audit_log::{NewAuditEntry, create_audit_entry},
+            events::{Event, create_event},
+            helper::apikey_generator::{generate_api_key, is_live_prefix},
+            types::ApiKey,
+        },
+        db_ops::constants::POOL_STATE_TRACKER,
+    },
+    errors::errors::ServiceError,
+    middleware::{
+        auth::AuthenticatedApiKey,
+        ip_allowlist::{MAX_ALLOWED_IPS, allowed_ips_from_json, normalize_allowed_ips},
+        scopes::{covers, is_known_permission, permissions_from_json},
+    },
+    services::{
+        api_key_cache::invalidate_api_keys,
+        event_bus::{api_key_event_data, publish_events},
+        webhook_secrets::{generate_signing_secret, secret_keys},
+    },
+};
+
+/// Longest grace period during which a rotated key keeps working
+const MAX_ROTATION_GRACE_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Maximum length of an API key name
+const MAX_NAME_LEN: usize = 100;
+
+/// Days of usage returned when none are requested, and the most that can be
+const DEFAULT_USAGE_DAYS: i64 = 30;
+const MAX_USAGE_DAYS: i64 = 90;
+
+// ===== REQUEST DTOs =====
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: Option<String>,
+    /// Scopes for the new key; defaults to the caller's own, which it cannot exceed
+    pub permissions: Option<Vec<String>>,
+    /// The key stops working at this time; omitted keys never expire
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Addresses or CIDRs the key may be used from; omitted or empty allows any
+    pub allowed_ips: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAllowedIpsRequest {
+    /// Replaces the key's allowlist; an empty list allows any address again
+    pub allowed_ips: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateApiKeyRequest {
+    /// How long the old key keeps working; 0 or omitted revokes it immediately
+    pub grace_period_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiKeyUsageQuery {
+    /// Number of days up to and including today (UTC)
+    pub days: Option<i64>,
+}
+
+// ===== RESPONSE DTOs =====
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub key_prefix: String,
+    /// `active`, `expired` or `revoked`
+    pub status: String,
+    pub permissions: Option<serde_json::Value>,
+    /// Empty when the key can be used from any address
+    pub allowed_ips: Vec<String>,
+    /// Whether the key has a secret to sign requests with
+    pub signing_enabled: bool,
+    pub last_used_at: Option<String>,
+    pub last_used_ip: Option<String>,
+    pub last_user_agent: Option<String>,
+    pub expires_at: Option<String>,
+    pub created_at: String,
+    pub revoked_at: Option<String>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(k: ApiKey) -> Self {
+        let status = if k.status == "active" && !k.is_usable() {
+            "expired".to_string()
+        } else {
+            k.status
+        };
+
+        Self {
+            id: k.id,
+            name: k.name,
+            key_prefix: k.key_prefix,
+            status,
+            allowed_ips: allowed_ips_from_json(k.allowed_ips.as_ref()),
+            signing_enabled: k.encrypted_signing_secret.is_some(),
+            permissions: k.permissions,
+            last_used_at: k.last_used_at.map(|t| t.to_rfc3339()),
+            last_used_ip: k.last_used_ip,
+            last_user_agent: k.last_user_agent,
+            expires_at: k.expires_at.map(|t| t.to_rfc3339()),
+            created_at: k.created_at.to_rfc3339(),
+            revoked_at: k.revoked_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeysListResponse {
+    pub api_keys: Vec<ApiKeyResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyUsageDay {
+    pub date: String,
+    pub request_count: i64,
+    /// Requests refused because they came from outside the key's allowlist
+    pub ip_rejected_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyUsageResponse {
+    pub api_key_id: Uuid,
+    /// Newest first, one entry per day including days without requests
+    pub days: Vec<ApiKeyUsageDay>,
+    pub total_requests: i64,
+    pub total_ip_rejected: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    pub api_key: ApiKeyResponse,
+    pub key: String, // Plain-text API key (only shown once)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateApiKeyResponse {
+    pub api_key: ApiKeyResponse,
+    pub key: String, // Plain-text API key (only shown once)
+    pub previous_api_key: ApiKeyResponse,
+    /// Signing secret of the new key, issued when the old key had one (only shown once)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SigningSecretResponse {
+    pub api_key: ApiKeyResponse,
+    pub signing_secret: String, // Plain-text signing secret (only shown once)
+}
+
+// ===== HANDLERS =====
+
+/// GET /api/v1/api-keys
+/// List the account's API keys, newest first
+#[instrument(fields(service = "/api/v1/api-keys"))]
+pub async fn list_api_keys(Extension(auth_info): Extension<AuthenticatedApiKey>) -> Response {
+    tracing::info!(account_id = %auth_info.account_id, "Listing API keys");
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = get_api_keys_for_account(auth_info.account_id, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(keys) => (
+            StatusCode::OK,
+            Json(ApiKeysListResponse {
+                api_keys: keys.into_iter().map(Into::into).collect(),
+            }),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/v1/api-keys/:id/usage
+/// Daily request counts of an API key, to find keys that are no longer used
+#[instrument(fields(service = "/api/v1/api-keys/:id/usage"))]
+pub async fn get_api_key_usage(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(api_key_id): Path<Uuid>,
+    Query(params): Query<ApiKeyUsageQuery>,
+) -> Response {
+    let days = params.days.unwrap_or(DEFAULT_USAGE_DAYS);
+
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        api_key_id = %api_key_id,
+        days = days,
+        "Fetching API key usage"
+    );
+
+    if !(1..=MAX_USAGE_DAYS).contains(&days) {
+        return ServiceError::ValidationError(format!(
+            "days must be between 1 and {}",
+            MAX_USAGE_DAYS
+        ))
+        .into_response();
+    }
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let to = chrono::Utc::now().date_naive();
+    let from = to - chrono::Duration::days(days - 1);
+    let result = async {
+        // Also checks the key belongs to the account
+        get_api_key_for_account(api_key_id, auth_info.account_id, &mut conn).await?;
+        get_api_key_daily_usage(api_key_id, from, to, &mut conn).await
+    }
+    .await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(usage) => {
+            let response = ApiKeyUsageResponse {
+                api_key_id,
+                total_requests: usage.iter().map(|d| d.request_count).sum(),
+                total_ip_rejected: usage.iter().map(|d| d.ip_rejected_count).sum(),
+                days: usage
+                    .into_iter()
+                    .map(|d| ApiKeyUsageDay {
+                        date: d.usage_date.to_string(),
+                        request_count: d.request_count,
+                        ip_rejected_count: d.ip_rejected_count,
+                    })
+                    .collect(),
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/v1/api-keys
+/// Create an additional API key for the account
+#[instrument(fields(service = "/api/v1/api-keys"))]
+pub async fn create_api_key(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        name = ?payload.name,
+        "Creating API key"
+    );
+
+    if payload
+        .name
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_NAME_LEN)
+    {
+        return ServiceError::ValidationError(format!(
+            "name must be at most {} characters",
+            MAX_NAME_LEN
+        ))
+        .into_response();
+    }
+    if payload
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= chrono::Utc::now())
+    {
+        return ServiceError::ValidationError("expires_at must be in the future".to_string())
+            .into_response();
+    }
+    let permissions = payload
+        .permissions
+        .unwrap_or_else(|| auth_info.permissions.clone());
+    if let Err(e) = validate_permissions(&auth_info, &permissions) {
+        return e.into_response();
+    }
+    let allowed_ips = match validate_allowed_ips(payload.allowed_ips.as_deref().unwrap_or_default())
+    {
+        Ok(allowed_ips) => allowed_ips,
+        Err(e) => return e.into_response(),
+    };
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let (key, api_key) = issue_api_key(
+                    auth_info.account_id,
+                    payload.name,
+                    Some(serde_json::json!(permissions)),
+                    allowed_ips,
+                    payload.expires_at,
+                    auth_info.livemode,
+                    &mut conn,
+                )
+                .await?;
+                let event = create_event(
+                    auth_info.account_id,
+                    "api_key.created",
+                    api_key_event_data(&api_key),
+                    &mut conn,
+                )
+                .await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "api_key.created",
+                    "api_key",
+                    api_key.id,
+                )
+                .created(api_key_audit_data(&api_key));
+                create_audit_entry(entry, &mut conn).await?;
+                Ok((key, api_key, event))
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok((key, api_key, event)) => {
+            publish_events(&[event]).await;
+
+            tracing::info!(
+                account_id = %auth_info.account_id,
+                api_key_id = %api_key.id,
+                "API key created"
+            );
+
+            let response = CreateApiKeyResponse {
+                api_key: api_key.into(),
+                key,
+            };
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/v1/api-keys/:id/revoke
+/// Revoke an API key; the account's last usable key cannot be revoked
+#[instrument(fields(service = "/api/v1/api-keys/:id/revoke"))]
+pub async fn revoke_api_key(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(api_key_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        api_key_id = %api_key_id,
+        "Revoking API key"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let api_key =
+                    get_api_key_for_account(api_key_id, auth_info.account_id, &mut conn).await?;
+                if !api_key.is_usable() {
+                    return Err(ServiceError::ApiKeyNotActive(api_key_id.to_string()));
+                }
+                // A restricted key cannot take away access broader than its own
+                if !covers(
+                    &auth_info.permissions,
+                    &permissions_from_json(api_key.permissions.as_ref()),
+                ) {
+                    return Err(ServiceError::InsufficientPermissions(
+                        "cannot revoke a key with scopes the caller does not hold".to_string(),
+                    ));
+                }
+
+                // Locking the account's usable keys serialises concurrent revocations
+                let active = lock_active_api_keys(auth_info.account_id, &mut conn).await?;
+                if !active.iter().any(|id| *id != api_key_id) {
+                    return Err(ServiceError::LastActiveApiKey(api_key_id.to_string()));
+                }
+
+                let (revoked, event) = revoke(api_key_id, auth_info.account_id, &mut conn).await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "api_key.revoked",
+                    "api_key",
+                    api_key_id,
+                )
+                .changed(&api_key_audit_data(&api_key), &api_key_audit_data(&revoked));
+                create_audit_entry(entry, &mut conn).await?;
+                Ok((revoked, event))
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok((revoked, event)) => {
+            // Cached records would keep the key working until they expire
+            invalidate_api_keys(&[&revoked]).await;
+            publish_events(&[event]).await;
+
+            tracing::info!(
+                account_id = %auth_info.account_id,
+                api_key_id = %api_key_id,
+                "API key revoked"
+            );
+
+            (StatusCode::OK, Json(ApiKeyResponse::from(revoked))).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/v1/api-keys/:id/rotate
+/// Replace an API key with a new one, optionally keeping the old one working for a while
+#[instrument(fields(service = "/api/v1/api-keys/:id/rotate"))]
+pub async fn rotate_api_key(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(api_key_id): Path<Uuid>,
+    payload: Option<Json<RotateApiKeyRequest>>,
+) -> Response {
+    let Json(payload) = payload.unwrap_or_default();
+    let grace_period_seconds = payload.grace_period_seconds.unwrap_or(0);
+
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        api_key_id = %api_key_id,
+        grace_period_seconds = grace_period_seconds,
+        "Rotating API key"
+    );
+
+    if !(0..=MAX_ROTATION_GRACE_SECONDS).contains(&grace_period_seconds) {
+        return ServiceError::ValidationError(format!(
+            "grace_period_seconds must be between 0 and {}",
+            MAX_ROTATION_GRACE_SECONDS
+        ))
+        .into_response();
+    }
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let previous =
+                    get_api_key_for_account(api_key_id, auth_info.account_id, &mut conn).await?;
+                if !previous.is_usable() {
+                    return Err(ServiceError::ApiKeyNotActive(api_key_id.to_string()));
+                }
+                // Rotating hands out a key with the old key's scopes
+                if !covers(
+                    &auth_info.permissions,
+                    &permissions_from_json(previous.permissions.as_ref()),
+                ) {
+                    return Err(ServiceError::InsufficientPermissions(
+                        "cannot rotate a key with scopes the caller does not hold".to_string(),
+                    ));
+                }
+
+                // The replacement keeps the old key's name, permissions, allowlist and mode
+                let (key, api_key) = issue_api_key(
+                    auth_info.account_id,
+                    previous.name.clone(),
+                    previous.permissions.clone(),
+                    previous.allowed_ips.clone(),
+                    None,
+                    is_live_prefix(&previous.key_prefix),
+                    &mut conn,
+                )
+                .await?;
+                let (api_key, signing_secret) = if previous.encrypted_signing_secret.is_some() {
+                    let (signing_secret, api_key) =
+                        issue_signing_secret(api_key.id, auth_info.account_id, &mut conn).await?;
+                    (api_key, Some(signing_secret))
+                } else {
+                    (api_key, None)
+                };
+                let mut events = vec![
+                    create_event(
+                        auth_info.account_id,
+                        "api_key.created",
+                        api_key_event_data(&api_key),
+                        &mut conn,
+                    )
+                    .await?,
+                ];
+
+                let replaced = if grace_period_seconds > 0 {
+                    let expires_at =
+                        chrono::Utc::now() + chrono::Duration::seconds(grace_period_seconds);
+                    expire_api_key(api_key_id, expires_at, &mut conn).await?
+                } else {
+                    let (revoked, event) =
+                        revoke(api_key_id, auth_info.account_id, &mut conn).await?;
+                    events.push(event);
+                    revoked
+                };
+
+                let actor = auth_info.audit_actor();
+                let created = NewAuditEntry::new(
+                    &actor,
+                    Some(auth_info.account_id),
+                    "api_key.created",
+                    "api_key",
+                    api_key.id,
+                )
+                .created(api_key_audit_data(&api_key));
+                create_audit_entry(created, &mut conn).await?;
+                let mut replaced_data = api_key_audit_data(&replaced);
+                replaced_data["replaced_by"] = serde_json::json!(api_key.id);
+                let rotated = NewAuditEntry::new(
+                    &actor,
+                    Some(auth_info.account_id),
+                    "api_key.rotated",
+                    "api_key",
+                    api_key_id,
+                )
+                .changed(&api_key_audit_data(&previous), &replaced_data);
+                create_audit_entry(rotated, &mut conn).await?;
+                let previous = replaced;
+
+                Ok((key, api_key, previous, signing_secret, events))
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok((key, api_key, previous, signing_secret, events)) => {
+            invalidate_api_keys(&[&previous]).await;
+            publish_events(&events).await;
+
+            tracing::info!(
+                account_id = %auth_info.account_id,
+                previous_api_key_id = %api_key_id,
+                api_key_id = %api_key.id,
+                "API key rotated"
+            );
+
+            let response = RotateApiKeyResponse {
+                api_key: api_key.into(),
+                key,
+                previous_api_key: previous.into(),
+                signing_secret: signing_secret.map(|secret| secret.to_string()),
+            };
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// PUT /api/v1/api-keys/:id/allowed-ips
+/// Replace the addresses an API key may be used from
+#[instrument(fields(service = "/api/v1/api-keys/:id/allowed-ips"))]
+pub async fn set_allowed_ips(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(api_key_id): Path<Uuid>,
+    Json(payload): Json<SetAllowedIpsRequest>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        api_key_id = %api_key_id,
+        entries = payload.allowed_ips.len(),
+        "Setting API key allowlist"
+    );
+
+    let allowed_ips = match validate_allowed_ips(&payload.allowed_ips) {
+        Ok(allowed_ips) => allowed_ips,
+        Err(e) => return e.into_response(),
+    };
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let api_key =
+                    get_api_key_for_account(api_key_id, auth_info.account_id, &mut conn).await?;
+                if !api_key.is_usable() {
+                    return Err(ServiceError::ApiKeyNotActive(api_key_id.to_string()));
+                }
+                let updated = set_api_key_allowed_ips(
+                    api_key_id,
+                    auth_info.account_id,
+                    allowed_ips,
+                    &mut conn,
+                )
+                .await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "api_key.updated",
+                    "api_key",
+                    api_key_id,
+                )
+                .changed(&api_key_audit_data(&api_key), &api_key_audit_data(&updated));
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(updated)
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(api_key) => {
+            // Cached records would keep enforcing the old allowlist until they expire
+            invalidate_api_keys(&[&api_key]).await;
+
+            tracing::info!(
+                account_id = %auth_info.account_id,
+                api_key_id = %api_key_id,
+                "API key allowlist updated"
+            );
+
+            (StatusCode::OK, Json(ApiKeyResponse::from(api_key))).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/v1/api-keys/:id/signing-secret
+/// Issue a secret for signing requests with an API key, replacing any previous one
+#[instrument(fields(service = "/api/v1/api-keys/:id/signing-secret"))]
+pub async fn create_signing_secret(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(api_key_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        api_key_id = %api_key_id,
+        "Issuing API key signing secret"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let api_key =
+                    get_api_key_for_account(api_key_id, auth_info.account_id, &mut conn).await?;
+                if !api_key.is_usable() {
+                    return Err(ServiceError::ApiKeyNotActive(api_key_id.to_string()));
+                }
+                // The secret lets its holder act with the key's scopes
+                if !covers(
+                    &auth_info.permissions,
+                    &permissions_from_json(api_key.permissions.as_ref()),
+                ) {
+                    return Err(ServiceError::InsufficientPermissions(
+                        "cannot issue a secret for a key with scopes the caller does not hold"
+                            .to_string(),
+                    ));
+                }
+                let issued =
+                    issue_signing_secret(api_key_id, auth_info.account_id, &mut conn).await?;
+                // Never the secret itself
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "api_key.signing_secret_issued",
+                    "api_key",
+                    api_key_id,
+                );
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(issued)
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok((signing_secret, api_key)) => {
+            // Cached records would keep accepting signatures made with the old secret
+            invalidate_api_keys(&[&api_key]).await;
+
+            tracing::info!(
+                account_id = %auth_info.account_id,
+                api_key_id = %api_key_id,
+                "API key signing secret issued"
+            );
+
+            let response = SigningSecretResponse {
+                api_key: api_key.into(),
+                signing_secret: signing_secret.to_string(),
+            };
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+// ===== HELPERS =====
+
+/// Check requested permissions are known and no broader than the caller's
+fn validate_permissions(
+    auth_info: &AuthenticatedApiKey,
+    permissions: &[String],
+) -> Result<(), ServiceError> {
+    if permissions.is_empty() {
+        return Err(ServiceError::ValidationError(
+            "permissions must not be empty".to_string(),
+        ));
+    }
+    if let Some(unknown) = permissions.iter().find(|p| !is_known_permission(p)) {
+        return Err(ServiceError::ValidationError(format!(
+            "unknown permission '{}'",
+            unknown
+        )));
+    }
+    if !covers(&auth_info.permissions, permissions) {
+        return Err(ServiceError::InsufficientPermissions(
+            "a key cannot be granted scopes the caller does not hold".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check an allowlist given through the API and convert it for storage
+///
+/// # Returns
+/// The normalised allowlist, or `None` when it is empty and any address is allowed
+fn validate_allowed_ips(entries: &[String]) -> Result<Option<serde_json::Value>, ServiceError> {
+    if entries.len() > MAX_ALLOWED_IPS {
+        return Err(ServiceError::ValidationError(format!(
+            "allowed_ips must have at most {} entries",
+            MAX_ALLOWED_IPS
+        )));
+    }
+    let allowed_ips = normalize_allowed_ips(entries)
+        .map_err(|e| ServiceError::ValidationError(format!("allowed_ips: {}", e)))?;
+
+    Ok((!allowed_ips.is_empty()).then(|| serde_json::json!(allowed_ips)))
+}
+
+pub(crate) async fn begin_transaction(conn: &mut PgConnection) -> Result<(), ServiceError> {
+    sqlx::query("BEGIN").execute(conn).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to begin transaction");
+        ServiceError::DatabaseError(e.to_string())
+    })?;
+
+    Ok(())
+}
+
+/// Commit when the transaction's work succeeded, otherwise roll it back
+pub(crate) async fn finish_transaction<T>(
+    result: Result<T, ServiceError>,
+    conn: &mut PgConnection,
+) -> Result<T, ServiceError> {
+    match result {
+        Ok(value) => match sqlx::query("COMMIT").execute(&mut *conn).await {
+            Ok(_) => Ok(value),
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to commit transaction");
+                let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+                Err(ServiceError::DatabaseError(e.to_string()))
+            }
+        },
+        Err(e) => {
+            let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+            Err(e)
+        }
+    }
+}
+
+/// Generate and store a new API key, returning the plain-text key and its record
+async fn issue_api_key(
+    account_id: Uuid,
+    name: Option<String>,
+    permissions: Option<serde_json::Value>,
+    allowed_ips: Option<serde_json::Value>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    live: bool,
+    conn: &mut PoolConnection<Postgres>,
+) -> Result<(String, ApiKey), ServiceError> {
+    let (key, key_hash, key_prefix) = generate_api_key(live);
+
+    let mut builder = ApiKeyBuilder::new()
+        .account_id(account_id)
+        .key_hash(key_hash)
+        .key_prefix(key_prefix)
+        .status("active".to_string())
+        .permissions(permissions.unwrap_or_else(|| serde_json::json!(["read", "write"])));
+    if let Some(name) = name {
+        builder = builder.name(name);
+    }
+    if let Some(allowed_ips) = allowed_ips {
+        builder = builder.allowed_ips(allowed_ips);
+    }
+    if let Some(expires_at) = expires_at {
+        builder = builder.expires_at(expires_at);
+    }
+
+    let api_key = builder
+        .expect_id()
+        .expect_account_id()
+        .expect_key_prefix()
+        .expect_key_hash()
+        .expect_name()
+        .expect_permissions()
+        .expect_allowed_ips()
+        .expect_status()
+        .expect_last_used_at()
+        .expect_expires_at()
+        .expect_created_at()
+        .expect_revoked_at()
+        .create(Some(conn))
+        .await?;
+
+    Ok((key, api_key))
+}
+
+/// Generate a signing secret for a key and store it encrypted
+///
+/// # Returns
+/// The plain-text secret and the updated key
+async fn issue_signing_secret(
+    api_key_id: Uuid,
+    account_id: Uuid,
+    conn: &mut PgConnection,
+) -> Result<(Zeroizing<String>, ApiKey), ServiceError> {
+    let signing_secret = generate_signing_secret();
+    let encrypted = secret_keys().encrypt(&signing_secret).map_err(|reason| {
+        tracing::error!(reason = %reason, "Failed to encrypt signing secret");
+        ServiceError::ConfigurationError(reason)
+    })?;
+    let api_key = set_api_key_signing_secret(
+        api_key_id,
+        account_id,
+        &encrypted.ciphertext,
+        &encrypted.key_id,
+        conn,
+    )
+    .await?;
+
+    Ok((signing_secret, api_key))
+}
+
+/// Fields of an API key tracked by the audit log; never the key, its hash or secrets
+fn api_key_audit_data(api_key: &ApiKey) -> serde_json::Value {
+    serde_json::json!({
+        "name": api_key.name,
+        "key_prefix": api_key.key_prefix,
+        "permissions": api_key.permissions,
+        "allowed_ips": api_key.allowed_ips,
+        "status": api_key.status,
+        "expires_at": api_key.expires_at,
+        "revoked_at": api_key.revoked_at,
+    })
+}
+
+/// Revoke a key and record the `api_key.revoked` event
+async fn revoke(
+    api_key_id: Uuid,
+    account_id: Uuid,
+    conn: &mut PoolConnection<Postgres>,
+) -> Result<(ApiKey, Event), ServiceError> {
+    let revoked = ApiKeyBuilder::new()
+        .id(api_key_id)
+        .revoke(Some(&mut *conn))
+        .await?;
+    let event = create_event(
+        account_id,
+        "api_key.revoked",
+        api_key_event_data(&revoked),
+        conn,
+    )
+    .await?;
+
+    Ok((revoked, event))
+}
@@ -0,0 +1,166 @@
+use axum::{
+    Extension, Json,
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    datalayer::{
+        CRUD::audit_log::{AuditEntry, AuditFilter, get_audit_entries_for_account},
+        db_ops::constants::POOL_STATE_TRACKER,
+    },
+    middleware::auth::AuthenticatedApiKey,
+};
+
+// ===== REQUEST DTOs =====
+
+#[derive(Debug, Deserialize)]
+pub struct ListAuditLogQuery {
+    pub action: Option<String>,
+    pub actor_type: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+// ===== RESPONSE DTOs =====
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntryResponse {
+    pub id: Uuid,
+    pub actor_type: String,
+    pub actor_id: Option<Uuid>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub reason: Option<String>,
+    pub request_id: Option<Uuid>,
+    pub ip_address: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditLogListResponse {
+    pub entries: Vec<AuditEntryResponse>,
+    pub has_more: bool,
+    pub next_cursor: Option<Uuid>,
+}
+
+impl From<AuditEntry> for AuditEntryResponse {
+    fn from(e: AuditEntry) -> Self {
+        Self {
+            id: e.id,
+            actor_type: e.actor_type,
+            actor_id: e.actor_id,
+            action: e.action,
+            target_type: e.target_type,
+            target_id: e.target_id,
+            before: e.before,
+            after: e.after,
+            reason: e.reason,
+            request_id: e.request_id,
+            ip_address: e.ip_address,
+            created_at: e.created_at.to_rfc3339(),
+        }
+    }
+}
+
+// ===== HANDLERS =====
+
+/// GET /api/v1/audit-log
+/// List the changes made to the authenticated account, newest first
+#[instrument(fields(service = "/api/v1/audit-log"))]
+pub async fn list_audit_log(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Query(params): Query<ListAuditLogQuery>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        action = ?params.action,
+        cursor = ?params.cursor,
+        "Listing audit log"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    // Fetch one extra row to know whether another page exists
+    let filter = AuditFilter {
+        action: params.action,
+        actor_type: params.actor_type,
+        actor_id: params.actor_id,
+        target_type: params.target_type,
+        target_id: params.target_id,
+        created_after: params.created_after,
+        created_before: params.created_before,
+        cursor: params.cursor,
+        limit: limit + 1,
+    };
+
+    let result = get_audit_entries_for_account(auth_info.account_id, &filter, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(mut entries) => {
+            let has_more = entries.len() as i64 > limit;
+            entries.truncate(limit as usize);
+            let next_cursor = if has_more {
+                entries.last().map(|e| e.id)
+            } else {
+                None
+            };
+
+            (
+                StatusCode::OK,
+                Json(AuditLogListResponse {
+                    entries: entries.into_iter().map(AuditEntryResponse::from).collect(),
+                    has_more,
+                    next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,425 @@
+use axum::{
+    Extension, Json,
+    extract::{
+        Path, Query,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, StatusCode},
+    response::{
+        IntoResponse, Response,
+        sse::{Event as SseEvent, KeepAlive, Sse},
+    },
+};
+use futures_util::{Stream, StreamExt, future};
+use serde::{Deserialize, Serialize};
+use tokio_stream::wrappers::BroadcastStream;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    datalayer::{
+        CRUD::events::{
+            EVENT_TYPES, Event, EventFilter, get_event_for_account, get_event_sequence,
+            get_events_after_sequence, get_events_for_account,
+        },
+        db_ops::constants::POOL_STATE_TRACKER,
+    },
+    errors::errors::ServiceError,
+    middleware::auth::AuthenticatedApiKey,
+    services::event_bus::EVENT_BUS,
+};
+
+/// Maximum number of missed events replayed when a stream resumes
+const MAX_REPLAY_EVENTS: i64 = 1000;
+
+// ===== REQUEST DTOs =====
+
+#[derive(Debug, Deserialize)]
+pub struct StreamEventsQuery {
+    /// Resume after this event; alternative to the `Last-Event-ID` header
+    pub last_event_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListEventsQuery {
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+// ===== RESPONSE DTOs =====
+
+#[derive(Debug, Serialize)]
+pub struct EventResponse {
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub version: i32,
+    pub data: serde_json::Value,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventsListResponse {
+    pub events: Vec<EventResponse>,
+    pub has_more: bool,
+    pub next_cursor: Option<Uuid>,
+}
+
+impl From<Event> for EventResponse {
+    fn from(e: Event) -> Self {
+        Self {
+            id: e.id,
+            event_type: e.event_type,
+            version: e.version,
+            data: e.data,
+            created_at: e.created_at.to_rfc3339(),
+        }
+    }
+}
+
+// ===== HANDLERS =====
+
+/// GET /api/v1/events
+/// List the authenticated account's events, newest first
+#[instrument(fields(service = "/api/v1/events"))]
+pub async fn list_events(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Query(params): Query<ListEventsQuery>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        event_type = ?params.event_type,
+        cursor = ?params.cursor,
+        "Listing events"
+    );
+
+    if let Some(event_type) = &params.event_type {
+        if !EVENT_TYPES.contains(&event_type.as_str()) {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "INVALID_EVENT_TYPE",
+                        "message": format!(
+                            "Unknown event type '{}'. Valid types: {}",
+                            event_type,
+                            EVENT_TYPES.join(", ")
+                        )
+                    }
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    // Fetch one extra row to know whether another page exists
+    let filter = EventFilter {
+        event_type: params.event_type,
+        created_after: params.created_after,
+        created_before: params.created_before,
+        cursor: params.cursor,
+        limit: limit + 1,
+    };
+
+    let result = get_events_for_account(auth_info.account_id, &filter, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(mut events) => {
+            let has_more = events.len() as i64 > limit;
+            events.truncate(limit as usize);
+            let next_cursor = if has_more {
+                events.last().map(|e| e.id)
+            } else {
+                None
+            };
+
+            (
+                StatusCode::OK,
+                Json(EventsListResponse {
+                    events: events.into_iter().map(EventResponse::from).collect(),
+                    has_more,
+                    next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/v1/events/:id
+/// Get a single event of the authenticated account
+#[instrument(fields(service = "/api/v1/events/:id"))]
+pub async fn get_event(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(event_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        event_id = %event_id,
+        "Getting event"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = get_event_for_account(event_id, auth_info.account_id, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(event) => (StatusCode::OK, Json(EventResponse::from(event))).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/v1/events/stream
+/// Stream the authenticated account's events as Server-Sent Events
+#[instrument(skip(headers), fields(service = "/api/v1/events/stream"))]
+pub async fn stream_events(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    headers: HeaderMap,
+    Query(params): Query<StreamEventsQuery>,
+) -> Response {
+    tracing::info!(account_id = %auth_info.account_id, "Opening event stream");
+
+    let stream = match resume_point(&headers, params.last_event_id) {
+        Ok(last_event_id) => account_event_stream(auth_info.account_id, last_event_id).await,
+        Err(e) => Err(e),
+    };
+
+    match stream {
+        Ok(stream) => {
+            let sse_events = stream.map(|event| {
+                SseEvent::default()
+                    .id(event.id.to_string())
+                    .event(event.event_type.clone())
+                    .json_data(EventResponse::from(event))
+            });
+            Sse::new(sse_events)
+                .keep_alive(KeepAlive::default())
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/v1/events/ws
+/// WebSocket equivalent of the SSE stream; each event is sent as a JSON text message
+#[instrument(skip(headers, ws), fields(service = "/api/v1/events/ws"))]
+pub async fn stream_events_ws(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    headers: HeaderMap,
+    Query(params): Query<StreamEventsQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    tracing::info!(account_id = %auth_info.account_id, "Opening event WebSocket");
+
+    // Resolve the stream before upgrading so resume errors are reported as HTTP errors
+    let stream = match resume_point(&headers, params.last_event_id) {
+        Ok(last_event_id) => account_event_stream(auth_info.account_id, last_event_id).await,
+        Err(e) => Err(e),
+    };
+
+    match stream {
+        Ok(stream) => ws.on_upgrade(move |socket| forward_to_socket(socket, stream)),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Read the resume point from the `Last-Event-ID` header or the `last_event_id` query parameter
+fn resume_point(
+    headers: &HeaderMap,
+    from_query: Option<Uuid>,
+) -> Result<Option<Uuid>, ServiceError> {
+    match headers.get("Last-Event-ID") {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .and_then(|v| Uuid::parse_str(v.trim()).ok())
+            .map(Some)
+            .ok_or_else(|| {
+                ServiceError::ValidationError("Last-Event-ID must be an event id".to_string())
+            }),
+        None => Ok(from_query),
+    }
+}
+
+/// Build the account's event stream: missed events from the log, then live events
+///
+/// The live subscription is opened before the log is read so nothing committed in
+/// between is lost; live events already replayed are skipped by sequence. The stream
+/// ends when this subscriber lags behind the bus, and the client resumes from its
+/// last event id.
+async fn account_event_stream(
+    account_id: Uuid,
+    last_event_id: Option<Uuid>,
+) -> Result<impl Stream<Item = Event> + Send + 'static, ServiceError> {
+    let bus = EVENT_BUS
+        .get()
+        .ok_or_else(|| ServiceError::InternalServerError("Event stream unavailable".to_string()))?;
+    let receiver = bus.subscribe();
+
+    let (missed, replayed_up_to) = match last_event_id {
+        Some(event_id) => replay_after(account_id, event_id).await?,
+        None => (Vec::new(), i64::MIN),
+    };
+
+    let live = BroadcastStream::new(receiver)
+        .take_while(move |received| {
+            if received.is_err() {
+                tracing::warn!(account_id = %account_id, "Event stream lagged, closing");
+            }
+            future::ready(received.is_ok())
+        })
+        .filter_map(move |received| {
+            future::ready(
+                received
+                    .ok()
+                    .filter(|e| e.account_id == account_id && e.sequence > replayed_up_to),
+            )
+        });
+
+    Ok(futures_util::stream::iter(missed).chain(live))
+}
+
+/// Load the events logged after `event_id`, with the sequence the replay ends at
+async fn replay_after(account_id: Uuid, event_id: Uuid) -> Result<(Vec<Event>, i64), ServiceError> {
+    let tracker = POOL_STATE_TRACKER.get().ok_or_else(|| {
+        ServiceError::DatabaseError("Database connection unavailable".to_string())
+    })?;
+    let mut conn = tracker
+        .get_connection()
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    let sequence = match get_event_sequence(event_id, account_id, &mut conn).await {
+        Ok(Some(sequence)) => sequence,
+        Ok(None) => {
+            tracker.return_connection(conn);
+            return Err(ServiceError::EventNotFound(event_id.to_string()));
+        }
+        Err(e) => {
+            tracker.return_connection(conn);
+            return Err(e);
+        }
+    };
+
+    let result =
+        get_events_after_sequence(account_id, sequence, MAX_REPLAY_EVENTS + 1, &mut conn).await;
+    tracker.return_connection(conn);
+    let events = result?;
+
+    if events.len() as i64 > MAX_REPLAY_EVENTS {
+        return Err(ServiceError::ValidationError(format!(
+            "More than {} events were missed since {}; cannot resume the stream",
+            MAX_REPLAY_EVENTS, event_id
+        )));
+    }
+
+    let replayed_up_to = events.last().map(|e| e.sequence).unwrap_or(sequence);
+    Ok((events, replayed_up_to))
+}
+
+/// Push stream events to a WebSocket until either side closes
+async fn forward_to_socket(mut socket: WebSocket, stream: impl Stream<Item = Event> + Send) {
+    let mut stream = std::pin::pin!(stream);
+
+    loop {
+        tokio::select! {
+            next = stream.next() => {
+                let Some(event) = next else {
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                };
+                let text = match serde_json::to_string(&EventResponse::from(event)) {
+                    Ok(t) => t,
+                    Err(e) => {
+                        tracing::error!(error = %e, "Failed to serialize stream event");
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => {
+                // Pings are answered by axum; anything else from the client is ignored
+                match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+
+    tracing::debug!("Event WebSocket closed");
+}
@@ -1,4 +1,10 @@
 pub mod accounts;
+pub mod admin;
+pub mod api_keys;
+pub mod audit_log;
+pub mod events;
 pub mod health;
+pub mod oauth;
 pub mod transfer;
+pub mod users;
 pub mod webhooks;
@@ -0,0 +1,213 @@
+use axum::{
+    Extension, Form, Json,
+    extract::ConnectInfo,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    errors::errors::{ServiceError, create_error_response},
+    middleware::{auth::authenticate_api_key, ip_allowlist::client_ip_from, scopes::Scope},
+    services::access_tokens::{
+        ACCESS_TOKEN_TTL_SECONDS, AUDIENCE, AccessTokenClaims, ISSUER, access_token_keys,
+        issue_access_token,
+    },
+};
+
+/// The only grant this endpoint supports
+const CLIENT_CREDENTIALS: &str = "client_credentials";
+
+// ===== REQUEST DTOs =====
+
+/// Form body of a token request (RFC 6749, section 4.4)
+#[derive(Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    /// The API key id; optional, checked against the key when given
+    pub client_id: Option<String>,
+    /// The API key, unless sent with HTTP Basic authentication
+    pub client_secret: Option<String>,
+    /// Space-separated scopes to narrow the token to; defaults to all the key holds
+    pub scope: Option<String>,
+}
+
+// ===== RESPONSE DTOs =====
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+// ===== HANDLERS =====
+
+/// POST /api/v1/oauth/token
+/// Exchange an API key for a short-lived access token (OAuth 2.0 client credentials)
+#[instrument(skip_all, fields(service = "/api/v1/oauth/token"))]
+pub async fn issue_token(
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    request_id: Option<Extension<Uuid>>,
+    headers: HeaderMap,
+    Form(payload): Form<TokenRequest>,
+) -> Response {
+    let request_id = request_id.map(|Extension(id)| id.to_string());
+
+    if access_token_keys().active().is_none() {
+        return ServiceError::ConfigurationError("access tokens are not configured".to_string())
+            .into_response();
+    }
+
+    if payload.grant_type != CLIENT_CREDENTIALS {
+        return create_error_response(
+            StatusCode::BAD_REQUEST,
+            "unsupported_grant_type",
+            "grant_type must be client_credentials",
+            request_id,
+        );
+    }
+
+    // Credentials go in HTTP Basic authentication or in the body (RFC 6749, section 2.3.1)
+    let (client_id, client_secret) = match basic_credentials(&headers) {
+        Some((client_id, client_secret)) => (Some(client_id), Some(client_secret)),
+        None => (payload.client_id, payload.client_secret),
+    };
+    let Some(api_key) = client_secret.filter(|secret| !secret.is_empty()) else {
+        return create_error_response(
+            StatusCode::UNAUTHORIZED,
+            "invalid_client",
+            "An API key is required as client_secret",
+            request_id,
+        );
+    };
+
+    let client_ip = connect_info.map(|ConnectInfo(peer)| client_ip_from(peer, &headers));
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok());
+    let auth_info = match authenticate_api_key(&api_key, client_ip, user_agent, &request_id).await {
+        Ok(auth_info) => auth_info,
+        Err(response) => return response,
+    };
+
+    let client_id_matches = client_id
+        .filter(|id| !id.is_empty())
+        .is_none_or(|id| id == auth_info.api_key_id.to_string());
+    if !client_id_matches {
+        return create_error_response(
+            StatusCode::UNAUTHORIZED,
+            "invalid_client",
+            "client_id does not match the API key",
+            request_id,
+        );
+    }
+
+    // Legacy `read`/`write` permissions are spelled out, so verifiers only see scopes
+    let held: Vec<Scope> = Scope::ALL
+        .into_iter()
+        .filter(|scope| auth_info.has_scope(*scope))
+        .collect();
+    let scopes = match payload.scope.as_deref() {
+        Some(requested) if !requested.trim().is_empty() => {
+            let mut scopes = Vec::new();
+            for name in requested.split_whitespace() {
+                match Scope::parse(name).filter(|scope| held.contains(scope)) {
+                    Some(scope) if !scopes.contains(&scope) => scopes.push(scope),
+                    Some(_) => {}
+                    None => {
+                        return create_error_response(
+                            StatusCode::BAD_REQUEST,
+                            "invalid_scope",
+                            &format!("API key does not hold the '{}' scope", name),
+                            request_id,
+                        );
+                    }
+                }
+            }
+            scopes
+        }
+        _ => held,
+    };
+    let scope = scopes
+        .iter()
+        .map(|scope| scope.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // The allowlist travels in the token; the key itself is not looked at again
+    let now = chrono::Utc::now().timestamp();
+    let claims = AccessTokenClaims {
+        iss: ISSUER.to_string(),
+        aud: AUDIENCE.to_string(),
+        sub: auth_info.api_key_id,
+        account_id: auth_info.account_id,
+        scope: scope.clone(),
+        livemode: auth_info.livemode,
+        allowed_ips: auth_info.allowed_ips.clone(),
+        require_signed_writes: auth_info.require_signed_writes,
+        iat: now,
+        exp: now + ACCESS_TOKEN_TTL_SECONDS,
+        jti: Uuid::new_v4(),
+    };
+
+    match issue_access_token(access_token_keys(), &claims) {
+        Ok(access_token) => {
+            tracing::info!(
+                api_key_id = %auth_info.api_key_id,
+                account_id = %auth_info.account_id,
+                token_id = %claims.jti,
+                "Access token issued"
+            );
+
+            let response = TokenResponse {
+                access_token,
+                token_type: "Bearer",
+                expires_in: ACCESS_TOKEN_TTL_SECONDS,
+                scope,
+            };
+            (
+                StatusCode::OK,
+                [(header::CACHE_CONTROL, "no-store")],
+                Json(response),
+            )
+                .into_response()
+        }
+        Err(reason) => {
+            tracing::error!(reason = %reason, "Failed to issue access token");
+            ServiceError::ConfigurationError("access tokens are not configured".to_string())
+                .into_response()
+        }
+    }
+}
+
+/// GET /.well-known/jwks.json
+/// Public keys for verifying access tokens, as a JSON Web Key Set
+#[instrument(fields(service = "/.well-known/jwks.json"))]
+pub async fn access_token_jwks() -> Response {
+    (
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(access_token_keys().jwks()),
+    )
+        .into_response()
+}
+
+// ===== HELPERS =====
+
+/// `client_id` and `client_secret` from an `Authorization: Basic` header
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (client_id, client_secret) = decoded.split_once(':')?;
+    Some((client_id.to_string(), client_secret.to_string()))
+}
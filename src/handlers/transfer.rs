@@ -67,6 +67,7 @@ pub struct TransferResponse {
     pub created_at: String,
     pub idempotency_key: String,
     pub parent_tx_key: String,
+    pub livemode: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -104,6 +105,7 @@ pub async fn get_transfer_byparentkey(
     match crate::controllayer::transfers::queries::get_transfer_by_parent_key(
         parent_key,
         auth_info.account_id,
+        auth_info.livemode,
     )
     .await
     {
@@ -125,6 +127,7 @@ pub async fn get_transfer_byid(
     match crate::controllayer::transfers::queries::get_transfer_by_id(
         transfer_id,
         auth_info.account_id,
+        auth_info.livemode,
     )
     .await
     {
@@ -182,6 +185,7 @@ pub async fn list_transfers(
 
     match crate::controllayer::transfers::queries::list_transfers(
         auth_info.account_id,
+        auth_info.livemode,
         params.limit,
         params.offset,
     )
@@ -0,0 +1,760 @@
+use axum::{
+    Extension, Json,
+    extract::Path,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    datalayer::{
+        CRUD::{
+            audit_log::{NewAuditEntry, create_audit_entry},
+            helper::{
+                email_regex::is_valid_email,
+                user_credentials::{
+                    PASSWORD_LEN, SESSION_PREFIX, generate_session_token, hash_password,
+                    hash_session_token, verify_dummy_password, verify_password,
+                },
+            },
+            users::{
+                Member, User, UserMembership, add_member, create_session, create_user, get_member,
+                get_members, get_memberships_for_user, get_user_by_email, lock_owners,
+                remove_member, revoke_session, update_member_role,
+            },
+        },
+        db_ops::constants::POOL_STATE_TRACKER,
+    },
+    errors::errors::{ServiceError, create_error_response},
+    handlers::api_keys::{begin_transaction, finish_transaction},
+    middleware::{auth::AuthenticatedApiKey, roles::Role},
+};
+
+/// How long a session token works after login
+const SESSION_TTL_HOURS: i64 = 12;
+
+/// Maximum length of a user's name
+const MAX_NAME_LEN: usize = 255;
+
+// ===== REQUEST DTOs =====
+
+#[derive(Deserialize)]
+pub struct RegisterUserRequest {
+    pub email: String,
+    /// 12 to 128 characters
+    pub password: String,
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddMemberRequest {
+    /// Email of a registered user
+    pub email: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemberRequest {
+    pub role: Role,
+}
+
+// ===== RESPONSE DTOs =====
+
+#[derive(Debug, Serialize)]
+pub struct UserResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub status: String,
+    pub created_at: String,
+}
+
+impl From<User> for UserResponse {
+    fn from(u: User) -> Self {
+        Self {
+            id: u.id,
+            email: u.email,
+            name: u.name,
+            status: u.status,
+            created_at: u.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub token: String, // Plain-text session token (only shown once)
+    pub expires_at: String,
+    pub user: UserResponse,
+    /// Accounts the session can act on, chosen per request with `X-Account-Id`
+    pub memberships: Vec<UserMembership>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberResponse {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: String,
+    pub created_at: String,
+}
+
+impl From<Member> for MemberResponse {
+    fn from(m: Member) -> Self {
+        Self {
+            user_id: m.user_id,
+            email: m.email,
+            name: m.name,
+            role: m.role,
+            created_at: m.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MembersListResponse {
+    pub members: Vec<MemberResponse>,
+}
+
+// ===== HANDLERS =====
+
+/// POST /api/v1/users
+/// Register a user; they reach no account until one of its members adds them
+#[instrument(skip(payload), fields(service = "/api/v1/users"))]
+pub async fn register_user(Json(payload): Json<RegisterUserRequest>) -> Response {
+    let email = payload.email.trim().to_lowercase();
+    tracing::info!(email = %email, "Registering user");
+
+    if !is_valid_email(&email) {
+        return ServiceError::ValidationError("email is not a valid address".to_string())
+            .into_response();
+    }
+    if !PASSWORD_LEN.contains(&payload.password.chars().count()) {
+        return ServiceError::ValidationError(format!(
+            "password must be {} to {} characters",
+            PASSWORD_LEN.start(),
+            PASSWORD_LEN.end()
+        ))
+        .into_response();
+    }
+    if payload
+        .name
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_NAME_LEN)
+    {
+        return ServiceError::ValidationError(format!(
+            "name must be at most {} characters",
+            MAX_NAME_LEN
+        ))
+        .into_response();
+    }
+
+    // Argon2 is deliberately slow; keep it off the async workers
+    let password = payload.password;
+    let password_hash = match tokio::task::spawn_blocking(move || hash_password(&password)).await {
+        Ok(Ok(hash)) => hash,
+        Ok(Err(reason)) => {
+            tracing::error!(reason = %reason, "Failed to hash password");
+            return ServiceError::InternalServerError("failed to hash password".to_string())
+                .into_response();
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Password hashing task failed");
+            return ServiceError::InternalServerError("failed to hash password".to_string())
+                .into_response();
+        }
+    };
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = create_user(&email, &password_hash, payload.name.as_deref(), &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(user) => {
+            tracing::info!(user_id = %user.id, "User registered");
+            (StatusCode::CREATED, Json(UserResponse::from(user))).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/v1/auth/login
+/// Exchange a user's email and password for a session token
+#[instrument(skip(payload), fields(service = "/api/v1/auth/login"))]
+pub async fn login(Json(payload): Json<LoginRequest>) -> Response {
+    let email = payload.email.trim().to_lowercase();
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let user = match get_user_by_email(&email, &mut conn).await {
+        Ok(user) => user,
+        Err(e) => {
+            tracker.return_connection(conn);
+            return e.into_response();
+        }
+    };
+
+    // Unknown emails cost as much as wrong passwords, so neither can be told apart
+    let password = payload.password;
+    let stored_hash = user.as_ref().map(|u| u.password_hash.clone());
+    let verified = tokio::task::spawn_blocking(move || match stored_hash {
+        Some(hash) => verify_password(&password, &hash),
+        None => {
+            verify_dummy_password(&password);
+            false
+        }
+    })
+    .await
+    .unwrap_or(false);
+
+    let user = match user {
+        Some(user) if verified && user.status == "active" => user,
+        _ => {
+            tracker.return_connection(conn);
+            tracing::warn!(email = %email, "Failed login");
+            return ServiceError::InvalidCredentials.into_response();
+        }
+    };
+
+    let result = async {
+        let (token, token_hash) = generate_session_token();
+        let expires_at = chrono::Utc::now() + chrono::Duration::hours(SESSION_TTL_HOURS);
+        let session = create_session(user.id, &token_hash, expires_at, &mut conn).await?;
+        let memberships = get_memberships_for_user(user.id, &mut conn).await?;
+        Ok::<_, ServiceError>((token, session, memberships))
+    }
+    .await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok((token, session, memberships)) => {
+            tracing::info!(user_id = %user.id, session_id = %session.id, "User logged in");
+
+            let response = LoginResponse {
+                token,
+                expires_at: session.expires_at.to_rfc3339(),
+                user: user.into(),
+                memberships,
+            };
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/v1/auth/logout
+/// Revoke the session token sent in the Authorization header
+#[instrument(skip(headers), fields(service = "/api/v1/auth/logout"))]
+pub async fn logout(headers: HeaderMap) -> Response {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .filter(|token| token.starts_with(SESSION_PREFIX));
+    let Some(token) = token else {
+        return create_error_response(
+            StatusCode::UNAUTHORIZED,
+            "invalid_session",
+            "Authorization header must carry a session token",
+            None,
+        );
+    };
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = revoke_session(&hash_session_token(token), &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(Some(session_id)) => {
+            tracing::info!(session_id = %session_id, "User logged out");
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(None) => create_error_response(
+            StatusCode::UNAUTHORIZED,
+            "invalid_session",
+            "Session is invalid or already revoked",
+            None,
+        ),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/v1/members
+/// List the users who belong to the account and their roles
+#[instrument(fields(service = "/api/v1/members"))]
+pub async fn list_members(Extension(auth_info): Extension<AuthenticatedApiKey>) -> Response {
+    tracing::info!(account_id = %auth_info.account_id, "Listing members");
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = get_members(auth_info.account_id, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(members) => (
+            StatusCode::OK,
+            Json(MembersListResponse {
+                members: members.into_iter().map(Into::into).collect(),
+            }),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/v1/members
+/// Give a registered user a role on the account
+#[instrument(fields(service = "/api/v1/members"))]
+pub async fn create_member(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Json(payload): Json<AddMemberRequest>,
+) -> Response {
+    let email = payload.email.trim().to_lowercase();
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        role = payload.role.as_str(),
+        "Adding member"
+    );
+
+    if let Err(e) = check_owner_change(&auth_info, &[payload.role]) {
+        return e.into_response();
+    }
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let user = get_user_by_email(&email, &mut conn).await?.ok_or_else(|| {
+                    ServiceError::ValidationError(format!("no user with email {}", email))
+                })?;
+                let member = add_member(
+                    auth_info.account_id,
+                    user.id,
+                    payload.role.as_str(),
+                    &mut conn,
+                )
+                .await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "member.added",
+                    "member",
+                    member.user_id,
+                )
+                .created(member_audit_data(&member));
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(member)
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(member) => {
+            tracing::info!(
+                account_id = %auth_info.account_id,
+                user_id = %member.user_id,
+                role = %member.role,
+                "Member added"
+            );
+            (StatusCode::CREATED, Json(MemberResponse::from(member))).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// PATCH /api/v1/members/:user_id
+/// Change a member's role; the account's last owner cannot be demoted
+#[instrument(fields(service = "/api/v1/members/:user_id"))]
+pub async fn update_member(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<UpdateMemberRequest>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        user_id = %user_id,
+        role = payload.role.as_str(),
+        "Updating member"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let member = get_member(auth_info.account_id, user_id, &mut conn).await?;
+                let current = Role::parse(&member.role).unwrap_or(Role::Viewer);
+                check_owner_change(&auth_info, &[current, payload.role])?;
+
+                if current == Role::Owner && payload.role != Role::Owner {
+                    check_not_last_owner(auth_info.account_id, user_id, &mut conn).await?;
+                }
+                let updated = update_member_role(
+                    auth_info.account_id,
+                    user_id,
+                    payload.role.as_str(),
+                    &mut conn,
+                )
+                .await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "member.updated",
+                    "member",
+                    user_id,
+                )
+                .changed(&member_audit_data(&member), &member_audit_data(&updated));
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(updated)
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(member) => {
+            tracing::info!(
+                account_id = %auth_info.account_id,
+                user_id = %user_id,
+                role = %member.role,
+                "Member updated"
+            );
+            (StatusCode::OK, Json(MemberResponse::from(member))).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// DELETE /api/v1/members/:user_id
+/// Remove a user from the account; the last owner cannot be removed
+#[instrument(fields(service = "/api/v1/members/:user_id"))]
+pub async fn delete_member(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(user_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        user_id = %user_id,
+        "Removing member"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let member = get_member(auth_info.account_id, user_id, &mut conn).await?;
+                let current = Role::parse(&member.role).unwrap_or(Role::Viewer);
+                check_owner_change(&auth_info, &[current])?;
+
+                if current == Role::Owner {
+                    check_not_last_owner(auth_info.account_id, user_id, &mut conn).await?;
+                }
+                remove_member(auth_info.account_id, user_id, &mut conn).await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "member.removed",
+                    "member",
+                    user_id,
+                )
+                .deleted(member_audit_data(&member));
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(())
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(()) => {
+            tracing::info!(
+                account_id = %auth_info.account_id,
+                user_id = %user_id,
+                "Member removed"
+            );
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+// ===== HELPERS =====
+
+/// Fields of a membership tracked by the audit log
+fn member_audit_data(member: &Member) -> serde_json::Value {
+    serde_json::json!({
+        "user_id": member.user_id,
+        "email": member.email,
+        "role": member.role,
+    })
+}
+
+/// Only owners may grant, change or remove the owner role
+///
+/// API keys with `members:manage` act for the business itself and may; this is
+/// how an account's first owner is added.
+fn check_owner_change(auth_info: &AuthenticatedApiKey, roles: &[Role]) -> Result<(), ServiceError> {
+    let caller_is_owner = auth_info.role.is_none_or(|role| role == Role::Owner);
+    if roles.contains(&Role::Owner) && !caller_is_owner {
+        return Err(ServiceError::InsufficientPermissions(
+            "only owners can grant, change or remove the owner role".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fail with `LastOwner` when `user_id` is the account's only owner
+///
+/// Locks the owners until the transaction ends.
+async fn check_not_last_owner(
+    account_id: Uuid,
+    user_id: Uuid,
+    conn: &mut sqlx::PgConnection,
+) -> Result<(), ServiceError> {
+    let owners = lock_owners(account_id, conn).await?;
+    if !owners.iter().any(|owner| *owner != user_id) {
+        return Err(ServiceError::LastOwner(user_id.to_string()));
+    }
+
+    Ok(())
+}
@@ -1,31 +1,63 @@
 use axum::{
     Extension, Json,
     extract::{Path, Query},
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tracing::instrument;
 use uuid::Uuid;
 
 use crate::{
     datalayer::{
-        CRUD::webhook::{
-            create_webhook as create_webhook_db, delete_webhook as delete_webhook_db,
-            get_webhooks_for_account,
+        CRUD::{
+            audit_log::{NewAuditEntry, create_audit_entry},
+            types::WebhookDelivery,
+            webhook::{
+                DeadLetterCount, DeadLetterFilter, DeliveryFilter, MAX_DELIVERY_CONCURRENCY,
+                WEBHOOK_EVENT_TYPES, Webhook, WebhookUpdate, count_dead_letters_for_account,
+                count_webhooks_for_account, create_webhook as create_webhook_db,
+                delete_webhook as delete_webhook_db,
+                discard_dead_letters as discard_dead_letters_db, get_dead_letters_for_account,
+                get_deliveries_for_webhook, get_delivery_for_account, get_webhook_by_id,
+                get_webhooks_for_account, requeue_dead_letters,
+                update_webhook as update_webhook_db, update_webhook_status,
+            },
+            webhook_backfill::{WebhookBackfill, create_backfill, get_backfill_for_account},
         },
         db_ops::constants::POOL_STATE_TRACKER,
     },
+    errors::errors::ServiceError,
+    handlers::api_keys::{begin_transaction, finish_transaction},
     middleware::auth::AuthenticatedApiKey,
+    services::{
+        WebhookDispatcher,
+        egress_guard::validate_webhook_url,
+        webhook_backfill::start_backfill,
+        webhook_secrets::{generate_webhook_secret, secret_keys},
+        webhook_signing::{SIGNING_ALGORITHMS, signing_keys},
+    },
 };
 
+/// Maximum number of characters of the receiver's response body returned in delivery listings
+const RESPONSE_BODY_EXCERPT_LEN: usize = 500;
+
+/// Maximum number of custom static headers a webhook may carry
+const MAX_CUSTOM_HEADERS: usize = 10;
+
+/// Maximum number of dead-lettered deliveries requeued by a single redrive call
+const REDRIVE_BATCH_LIMIT: i64 = 500;
+
 // ===== REQUEST DTOs =====
 
 #[derive(Debug, Deserialize)]
 pub struct CreateWebhookRequest {
     pub account_id: Uuid,
     pub url: String,
-    pub secret: String,
+    /// Require the endpoint to echo a challenge before the webhook becomes active
+    #[serde(default)]
+    pub verify: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,6 +65,32 @@ pub struct DeleteWebhookRequest {
     pub webhook_id: Uuid,
 }
 
+/// Partial update - omitted fields are left unchanged
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub max_retries: Option<i32>,
+    pub retry_backoff_seconds: Option<i32>,
+    /// Attempts in flight at once; 1 makes first attempts strictly in order
+    pub max_concurrency: Option<i32>,
+    /// `hmac-sha256` or `ed25519`
+    pub signing_algorithm: Option<String>,
+    /// An empty string clears the description
+    pub description: Option<String>,
+    /// Replaces the whole set of custom headers; `{}` removes them all
+    pub headers: Option<HashMap<String, String>>,
+}
+
+/// Replay historical events of a time range to a webhook
+#[derive(Debug, Deserialize)]
+pub struct CreateBackfillRequest {
+    pub start_at: chrono::DateTime<chrono::Utc>,
+    pub end_at: chrono::DateTime<chrono::Utc>,
+    /// Defaults to every event type the webhook subscribes to
+    pub event_types: Option<Vec<String>>,
+}
+
 // ===== RESPONSE DTOs =====
 
 #[derive(Debug, Serialize)]
@@ -40,14 +98,138 @@ pub struct WebhookResponse {
     pub id: Uuid,
     pub account_id: Uuid,
     pub url: String,
+    pub description: Option<String>,
+    pub events: serde_json::Value,
+    pub headers: serde_json::Value,
+    pub max_retries: Option<i32>,
+    pub retry_backoff_seconds: Option<i32>,
+    pub max_concurrency: i32,
+    pub signing_algorithm: String,
+    pub livemode: bool,
     pub status: String,
     pub created_at: String,
+    pub updated_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_error: Option<String>,
+    /// Signing secret, returned only when the webhook is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(w: Webhook) -> Self {
+        Self {
+            id: w.id,
+            account_id: w.account_id,
+            url: w.url,
+            description: w.description,
+            events: w.events,
+            headers: w.headers,
+            max_retries: w.max_retries,
+            retry_backoff_seconds: w.retry_backoff_seconds,
+            max_concurrency: w.max_concurrency,
+            signing_algorithm: w.signing_algorithm,
+            livemode: w.livemode,
+            status: w.status,
+            created_at: w.created_at.to_rfc3339(),
+            updated_at: w.updated_at.to_rfc3339(),
+            verification_error: None,
+            secret: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
 pub struct WebhooksListResponse {
     pub webhooks: Vec<WebhookResponse>,
-    pub total: usize,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveryResponse {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub event_id: Option<Uuid>,
+    pub event_type: String,
+    pub sequence: Option<i64>,
+    pub status: String,
+    pub attempt_count: i32,
+    pub http_status_code: Option<i32>,
+    pub response_body_excerpt: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+    pub failed_at: Option<String>,
+    pub dead_lettered_at: Option<String>,
+}
+
+impl From<WebhookDelivery> for WebhookDeliveryResponse {
+    fn from(d: WebhookDelivery) -> Self {
+        Self {
+            id: d.id,
+            webhook_id: d.webhook_id,
+            transaction_id: d.transaction_id,
+            event_id: d.event_id,
+            event_type: d.event_type,
+            sequence: d.sequence,
+            status: d.status,
+            attempt_count: d.attempt_count.unwrap_or(0),
+            http_status_code: d.http_status_code,
+            response_body_excerpt: d
+                .response_body
+                .map(|body| body.chars().take(RESPONSE_BODY_EXCERPT_LEN).collect()),
+            error_message: d.error_message,
+            created_at: d.created_at.to_rfc3339(),
+            delivered_at: d.delivered_at.map(|t| t.to_rfc3339()),
+            failed_at: d.failed_at.map(|t| t.to_rfc3339()),
+            dead_lettered_at: d.dead_lettered_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookDeliveriesListResponse {
+    pub deliveries: Vec<WebhookDeliveryResponse>,
+    pub has_more: bool,
+    pub next_cursor: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookBackfillResponse {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_types: serde_json::Value,
+    pub start_at: String,
+    pub end_at: String,
+    pub status: String,
+    pub total_events: i32,
+    pub queued_events: i32,
+    pub error_message: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub completed_at: Option<String>,
+}
+
+impl From<WebhookBackfill> for WebhookBackfillResponse {
+    fn from(b: WebhookBackfill) -> Self {
+        Self {
+            id: b.id,
+            webhook_id: b.webhook_id,
+            event_types: b.event_types,
+            start_at: b.start_at.to_rfc3339(),
+            end_at: b.end_at.to_rfc3339(),
+            status: b.status,
+            total_events: b.total_events,
+            queued_events: b.queued_events,
+            error_message: b.error_message,
+            created_at: b.created_at.to_rfc3339(),
+            updated_at: b.updated_at.to_rfc3339(),
+            completed_at: b.completed_at.map(|t| t.to_rfc3339()),
+        }
+    }
 }
 
 // ===== HANDLERS =====
@@ -80,14 +262,14 @@ pub async fn create_webhook(
             .into_response();
     }
 
-    // Validate URL format
-    if !payload.url.starts_with("http://") && !payload.url.starts_with("https://") {
+    // Validate URL format and reject internal destinations
+    if let Err(reason) = validate_webhook_url(&payload.url) {
         return (
             StatusCode::BAD_REQUEST,
             Json(serde_json::json!({
                 "error": {
                     "code": "INVALID_URL",
-                    "message": "Webhook URL must start with http:// or https://"
+                    "message": reason
                 }
             })),
         )
@@ -128,26 +310,85 @@ pub async fn create_webhook(
         }
     };
 
-    // Create webhook
-    match create_webhook_db(auth_info.account_id, payload.url, payload.secret, &mut conn).await {
-        Ok(webhook) => {
-            tracker.return_connection(conn);
+    let status = if payload.verify {
+        "pending_verification"
+    } else {
+        "active"
+    };
 
-            let response = WebhookResponse {
-                id: webhook.id,
-                account_id: webhook.account_id,
-                url: webhook.url,
-                status: webhook.status,
-                created_at: webhook.created_at.to_rfc3339(),
-            };
+    // The secret is generated here, shown once in the response and stored encrypted
+    let secret = generate_webhook_secret();
+    let encrypted = match secret_keys().encrypt(&secret) {
+        Ok(encrypted) => encrypted,
+        Err(reason) => {
+            tracker.return_connection(conn);
+            tracing::error!(reason = %reason, "Failed to encrypt webhook secret");
+            return ServiceError::ConfigurationError(reason).into_response();
+        }
+    };
 
-            (StatusCode::CREATED, Json(response)).into_response()
+    // Create webhook; it and its audit entry are committed together
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let webhook = create_webhook_db(
+                    auth_info.account_id,
+                    payload.url,
+                    &encrypted.ciphertext,
+                    &encrypted.key_id,
+                    status,
+                    &mut conn,
+                )
+                .await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "webhook.created",
+                    "webhook",
+                    webhook.id,
+                )
+                .created(webhook_audit_data(&webhook));
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(webhook)
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
         }
-        Err(e) => {
-            tracker.return_connection(conn);
-            e.into_response()
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+    let webhook = match result {
+        Ok(webhook) => webhook,
+        Err(e) => return e.into_response(),
+    };
+
+    // Verification handshake: the webhook stays pending until the endpoint echoes the challenge
+    let (webhook, verification_error) = if payload.verify {
+        match WebhookDispatcher::new().verify_endpoint(&webhook).await {
+            Ok(()) => match activate_webhook(webhook.id, auth_info.account_id).await {
+                Ok(activated) => (activated, None),
+                Err(e) => return e.into_response(),
+            },
+            Err(reason) => {
+                tracing::warn!(
+                    webhook_id = %webhook.id,
+                    reason = %reason,
+                    "Webhook endpoint verification failed"
+                );
+                (webhook, Some(reason))
+            }
         }
-    }
+    } else {
+        (webhook, None)
+    };
+
+    let response = WebhookResponse {
+        verification_error,
+        secret: Some(secret.to_string()),
+        ..WebhookResponse::from(webhook)
+    };
+
+    (StatusCode::CREATED, Json(response)).into_response()
 }
 
 /// POST /api/v1/webhooks/unset
@@ -196,8 +437,31 @@ pub async fn delete_webhook(
         }
     };
 
-    match delete_webhook_db(payload.webhook_id, auth_info.account_id, &mut conn).await {
-        Ok(_) => {
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let webhook =
+                    get_webhook_by_id(payload.webhook_id, auth_info.account_id, &mut conn).await?;
+                delete_webhook_db(payload.webhook_id, auth_info.account_id, &mut conn).await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "webhook.deleted",
+                    "webhook",
+                    payload.webhook_id,
+                )
+                .deleted(webhook_audit_data(&webhook));
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(())
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => {
             tracker.return_connection(conn);
             (
                 StatusCode::OK,
@@ -217,10 +481,12 @@ pub async fn delete_webhook(
 #[derive(Debug, Deserialize)]
 pub struct GetWebhooksQuery {
     pub account_id: Uuid,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
 }
 
-/// GET /api/v1/webhooks/info?account_id=xxx
-/// Get all webhooks for the authenticated account
+/// GET /api/v1/webhooks/info?account_id=xxx&limit=50&offset=0
+/// Get the webhooks of the authenticated account, newest first
 #[instrument(fields(service = "/api/v1/webhooks/info"))]
 pub async fn get_webhooks(
     Extension(auth_info): Extension<AuthenticatedApiKey>,
@@ -279,28 +545,31 @@ pub async fn get_webhooks(
         }
     };
 
-    match get_webhooks_for_account(auth_info.account_id, &mut conn).await {
-        Ok(webhooks) => {
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let total = match count_webhooks_for_account(auth_info.account_id, &mut conn).await {
+        Ok(total) => total,
+        Err(e) => {
             tracker.return_connection(conn);
+            return e.into_response();
+        }
+    };
 
-            let response_webhooks: Vec<WebhookResponse> = webhooks
-                .into_iter()
-                .map(|w| WebhookResponse {
-                    id: w.id,
-                    account_id: w.account_id,
-                    url: w.url,
-                    status: w.status,
-                    created_at: w.created_at.to_rfc3339(),
-                })
-                .collect();
+    match get_webhooks_for_account(auth_info.account_id, limit, offset, &mut conn).await {
+        Ok(webhooks) => {
+            tracker.return_connection(conn);
 
-            let total = response_webhooks.len();
+            let response_webhooks: Vec<WebhookResponse> =
+                webhooks.into_iter().map(WebhookResponse::from).collect();
 
             (
                 StatusCode::OK,
                 Json(WebhooksListResponse {
                     webhooks: response_webhooks,
                     total,
+                    limit,
+                    offset,
                 }),
             )
                 .into_response()
@@ -311,3 +580,1339 @@ pub async fn get_webhooks(
         }
     }
 }
+
+/// GET /api/v1/webhooks/:id
+/// Get a single webhook owned by the authenticated account
+#[instrument(fields(service = "/api/v1/webhooks/:id"))]
+pub async fn get_webhook(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(webhook_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = %webhook_id,
+        "Fetching webhook"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = get_webhook_by_id(webhook_id, auth_info.account_id, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(webhook) => (StatusCode::OK, Json(WebhookResponse::from(webhook))).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// PATCH /api/v1/webhooks/:id
+/// Update the configuration of a webhook owned by the authenticated account
+#[instrument(fields(service = "/api/v1/webhooks/:id"))]
+pub async fn update_webhook(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(webhook_id): Path<Uuid>,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = %webhook_id,
+        "Updating webhook"
+    );
+
+    if let Err(e) = validate_webhook_update(&payload) {
+        return e.into_response();
+    }
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let changes = WebhookUpdate {
+        url: payload.url,
+        events: payload.events.map(|events| serde_json::json!(events)),
+        max_retries: payload.max_retries,
+        retry_backoff_seconds: payload.retry_backoff_seconds,
+        max_concurrency: payload.max_concurrency,
+        signing_algorithm: payload.signing_algorithm,
+        description: payload.description,
+        headers: payload.headers.map(|headers| serde_json::json!(headers)),
+    };
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let previous =
+                    get_webhook_by_id(webhook_id, auth_info.account_id, &mut conn).await?;
+                let webhook =
+                    update_webhook_db(webhook_id, auth_info.account_id, changes, &mut conn).await?;
+                let entry = NewAuditEntry::new(
+                    &auth_info.audit_actor(),
+                    Some(auth_info.account_id),
+                    "webhook.updated",
+                    "webhook",
+                    webhook_id,
+                )
+                .changed(
+                    &webhook_audit_data(&previous),
+                    &webhook_audit_data(&webhook),
+                );
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(webhook)
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(webhook) => (StatusCode::OK, Json(WebhookResponse::from(webhook))).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Fields of a webhook tracked by the audit log
+///
+/// Only header names are kept: custom headers often carry credentials, and the
+/// audit log cannot be edited to remove them later.
+fn webhook_audit_data(webhook: &Webhook) -> serde_json::Value {
+    let header_names: Vec<&String> = webhook
+        .headers
+        .as_object()
+        .map(|headers| headers.keys().collect())
+        .unwrap_or_default();
+    serde_json::json!({
+        "url": webhook.url,
+        "description": webhook.description,
+        "events": webhook.events,
+        "header_names": header_names,
+        "max_retries": webhook.max_retries,
+        "retry_backoff_seconds": webhook.retry_backoff_seconds,
+        "max_concurrency": webhook.max_concurrency,
+        "signing_algorithm": webhook.signing_algorithm,
+        "status": webhook.status,
+    })
+}
+
+/// Mark a webhook that passed the verification handshake as `active`
+///
+/// Takes its own connection: the handshake goes out to the customer's endpoint, so
+/// callers return theirs before starting it.
+async fn activate_webhook(webhook_id: Uuid, account_id: Uuid) -> Result<Webhook, ServiceError> {
+    let tracker = POOL_STATE_TRACKER.get().ok_or_else(|| {
+        ServiceError::DatabaseError("Database connection unavailable".to_string())
+    })?;
+    let mut conn = tracker.get_connection().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to get database connection");
+        ServiceError::DatabaseError("Failed to connect to database".to_string())
+    })?;
+
+    let result = update_webhook_status(webhook_id, account_id, "active", &mut conn).await;
+    tracker.return_connection(conn);
+    result
+}
+
+/// Audit entry for a bulk action on dead-lettered deliveries
+fn dead_letters_audit_entry(
+    auth_info: &AuthenticatedApiKey,
+    action: &'static str,
+    selection: &DeadLetterSelectionRequest,
+    count: u64,
+) -> NewAuditEntry {
+    // The selection is the target: a webhook, or every webhook of the account
+    let (target_type, target_id) = match selection.webhook_id {
+        Some(webhook_id) => ("webhook", webhook_id),
+        None => ("account", auth_info.account_id),
+    };
+    NewAuditEntry::new(
+        &auth_info.audit_actor(),
+        Some(auth_info.account_id),
+        action,
+        target_type,
+        target_id,
+    )
+    .details(serde_json::json!({
+        "webhook_id": selection.webhook_id,
+        "dead_lettered_after": selection.dead_lettered_after,
+        "dead_lettered_before": selection.dead_lettered_before,
+        "count": count,
+    }))
+}
+
+/// Validate a webhook update before it reaches the database
+fn validate_webhook_update(payload: &UpdateWebhookRequest) -> Result<(), ServiceError> {
+    if let Some(url) = payload.url.as_deref() {
+        validate_webhook_url(url).map_err(ServiceError::InvalidWebhookUrl)?;
+    }
+
+    if let Some(events) = payload.events.as_ref() {
+        if events.is_empty() {
+            return Err(ServiceError::ValidationError(
+                "events must contain at least one event type".to_string(),
+            ));
+        }
+        if let Some(unknown) = events
+            .iter()
+            .find(|e| !WEBHOOK_EVENT_TYPES.contains(&e.as_str()))
+        {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown event type '{}'. Supported: {}",
+                unknown,
+                WEBHOOK_EVENT_TYPES.join(", ")
+            )));
+        }
+    }
+
+    if let Some(max_retries) = payload.max_retries {
+        if !(0..=10).contains(&max_retries) {
+            return Err(ServiceError::ValidationError(
+                "max_retries must be between 0 and 10".to_string(),
+            ));
+        }
+    }
+
+    if let Some(backoff) = payload.retry_backoff_seconds {
+        if !(1..=86_400).contains(&backoff) {
+            return Err(ServiceError::ValidationError(
+                "retry_backoff_seconds must be between 1 and 86400".to_string(),
+            ));
+        }
+    }
+
+    if let Some(algorithm) = payload.signing_algorithm.as_deref() {
+        if !SIGNING_ALGORITHMS.contains(&algorithm) {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown signing_algorithm '{}'. Supported: {}",
+                algorithm,
+                SIGNING_ALGORITHMS.join(", ")
+            )));
+        }
+        if algorithm == "ed25519" && signing_keys().active().is_none() {
+            return Err(ServiceError::ValidationError(
+                "ed25519 signing is not available: no platform signing key is configured"
+                    .to_string(),
+            ));
+        }
+    }
+
+    if let Some(max_concurrency) = payload.max_concurrency {
+        if !(1..=MAX_DELIVERY_CONCURRENCY).contains(&max_concurrency) {
+            return Err(ServiceError::ValidationError(format!(
+                "max_concurrency must be between 1 and {}",
+                MAX_DELIVERY_CONCURRENCY
+            )));
+        }
+    }
+
+    if let Some(headers) = payload.headers.as_ref() {
+        if headers.len() > MAX_CUSTOM_HEADERS {
+            return Err(ServiceError::ValidationError(format!(
+                "At most {} custom headers are allowed",
+                MAX_CUSTOM_HEADERS
+            )));
+        }
+        for (name, value) in headers {
+            let lower = name.to_ascii_lowercase();
+            if matches!(
+                lower.as_str(),
+                "content-type" | "content-length" | "host" | "traceparent" | "tracestate"
+            ) || lower.starts_with("x-webhook-")
+            {
+                return Err(ServiceError::ValidationError(format!(
+                    "Header '{}' is reserved and cannot be overridden",
+                    name
+                )));
+            }
+            if axum::http::HeaderName::from_bytes(name.as_bytes()).is_err()
+                || axum::http::HeaderValue::from_str(value).is_err()
+            {
+                return Err(ServiceError::ValidationError(format!(
+                    "Header '{}' has an invalid name or value",
+                    name
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDeliveriesQuery {
+    pub status: Option<String>,
+    pub event_type: Option<String>,
+    pub created_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// GET /api/v1/webhooks/:id/deliveries
+/// List the delivery history of a webhook, newest first, with cursor pagination
+#[instrument(fields(service = "/api/v1/webhooks/:id/deliveries"))]
+pub async fn list_webhook_deliveries(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(webhook_id): Path<Uuid>,
+    Query(params): Query<ListDeliveriesQuery>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = %webhook_id,
+        status = ?params.status,
+        event_type = ?params.event_type,
+        cursor = ?params.cursor,
+        "Listing webhook deliveries"
+    );
+
+    if let Some(status) = params.status.as_deref() {
+        if !matches!(
+            status,
+            "pending" | "delivered" | "failed" | "dead_lettered" | "discarded"
+        ) {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "INVALID_STATUS",
+                        "message": "status must be one of: pending, delivered, failed, dead_lettered, discarded"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    // Ownership check: the webhook must belong to the authenticated account
+    if let Err(e) = get_webhook_by_id(webhook_id, auth_info.account_id, &mut conn).await {
+        tracker.return_connection(conn);
+        return e.into_response();
+    }
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let filter = DeliveryFilter {
+        status: params.status,
+        event_type: params.event_type,
+        created_after: params.created_after,
+        created_before: params.created_before,
+        cursor: params.cursor,
+        // Fetch one extra row to know whether another page exists
+        limit: limit + 1,
+    };
+
+    match get_deliveries_for_webhook(webhook_id, &filter, &mut conn).await {
+        Ok(mut deliveries) => {
+            tracker.return_connection(conn);
+
+            let has_more = deliveries.len() as i64 > limit;
+            deliveries.truncate(limit as usize);
+            let next_cursor = if has_more {
+                deliveries.last().map(|d| d.id)
+            } else {
+                None
+            };
+
+            (
+                StatusCode::OK,
+                Json(WebhookDeliveriesListResponse {
+                    deliveries: deliveries.into_iter().map(Into::into).collect(),
+                    has_more,
+                    next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracker.return_connection(conn);
+            e.into_response()
+        }
+    }
+}
+
+/// POST /api/v1/webhooks/deliveries/:id/redeliver
+/// Resend the stored payload of a delivery with a fresh signature
+#[instrument(fields(service = "/api/v1/webhooks/deliveries/:id/redeliver"))]
+pub async fn redeliver_webhook_delivery(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(delivery_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        delivery_id = %delivery_id,
+        "Redelivering webhook delivery"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let delivery =
+        match get_delivery_for_account(delivery_id, auth_info.account_id, &mut conn).await {
+            Ok(d) => d,
+            Err(e) => {
+                tracker.return_connection(conn);
+                return e.into_response();
+            }
+        };
+
+    let webhook =
+        match get_webhook_by_id(delivery.webhook_id, auth_info.account_id, &mut conn).await {
+            Ok(w) => w,
+            Err(e) => {
+                tracker.return_connection(conn);
+                return e.into_response();
+            }
+        };
+
+    tracker.return_connection(conn);
+
+    let webhook_id = webhook.id;
+    WebhookDispatcher::new().redeliver(webhook, delivery);
+
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "message": "Redelivery scheduled",
+            "delivery_id": delivery_id,
+            "webhook_id": webhook_id
+        })),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDeadLettersQuery {
+    pub webhook_id: Option<Uuid>,
+    pub dead_lettered_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub dead_lettered_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+/// Selects dead-lettered deliveries for bulk redrive or discard
+/// Omitted fields match every dead-lettered delivery of the account
+#[derive(Debug, Default, Deserialize)]
+pub struct DeadLetterSelectionRequest {
+    pub webhook_id: Option<Uuid>,
+    pub dead_lettered_after: Option<chrono::DateTime<chrono::Utc>>,
+    pub dead_lettered_before: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<&DeadLetterSelectionRequest> for DeadLetterFilter {
+    fn from(r: &DeadLetterSelectionRequest) -> Self {
+        Self {
+            webhook_id: r.webhook_id,
+            dead_lettered_after: r.dead_lettered_after,
+            dead_lettered_before: r.dead_lettered_before,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeadLetterStatsResponse {
+    pub total: i64,
+    pub webhooks: Vec<DeadLetterCount>,
+}
+
+/// GET /api/v1/webhooks/dead-letters
+/// List the account's dead-lettered deliveries, most recent first, with cursor pagination
+#[instrument(fields(service = "/api/v1/webhooks/dead-letters"))]
+pub async fn list_dead_letters(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Query(params): Query<ListDeadLettersQuery>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = ?params.webhook_id,
+        cursor = ?params.cursor,
+        "Listing dead-lettered deliveries"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let filter = DeadLetterFilter {
+        webhook_id: params.webhook_id,
+        dead_lettered_after: params.dead_lettered_after,
+        dead_lettered_before: params.dead_lettered_before,
+    };
+
+    // Fetch one extra row to know whether another page exists
+    let result = get_dead_letters_for_account(
+        auth_info.account_id,
+        &filter,
+        params.cursor,
+        limit + 1,
+        &mut conn,
+    )
+    .await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(mut deliveries) => {
+            let has_more = deliveries.len() as i64 > limit;
+            deliveries.truncate(limit as usize);
+            let next_cursor = if has_more {
+                deliveries.last().map(|d| d.id)
+            } else {
+                None
+            };
+
+            (
+                StatusCode::OK,
+                Json(WebhookDeliveriesListResponse {
+                    deliveries: deliveries
+                        .into_iter()
+                        .map(WebhookDeliveryResponse::from)
+                        .collect(),
+                    has_more,
+                    next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/v1/webhooks/dead-letters/stats
+/// Dead-letter queue size of the account, in total and per webhook
+#[instrument(fields(service = "/api/v1/webhooks/dead-letters/stats"))]
+pub async fn dead_letter_stats(Extension(auth_info): Extension<AuthenticatedApiKey>) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        "Fetching dead-letter queue stats"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result = count_dead_letters_for_account(auth_info.account_id, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(webhooks) => {
+            let total = webhooks.iter().map(|w| w.count).sum();
+            (
+                StatusCode::OK,
+                Json(DeadLetterStatsResponse { total, webhooks }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// POST /api/v1/webhooks/dead-letters/redrive
+/// Requeue dead-lettered deliveries matching the selection and deliver them again
+#[instrument(fields(service = "/api/v1/webhooks/dead-letters/redrive"))]
+pub async fn redrive_dead_letters(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Json(payload): Json<DeadLetterSelectionRequest>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = ?payload.webhook_id,
+        "Redriving dead-lettered deliveries"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    // Scoping to a single webhook requires owning it
+    if let Some(webhook_id) = payload.webhook_id {
+        if let Err(e) = get_webhook_by_id(webhook_id, auth_info.account_id, &mut conn).await {
+            tracker.return_connection(conn);
+            return e.into_response();
+        }
+    }
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let deliveries = requeue_dead_letters(
+                    auth_info.account_id,
+                    &DeadLetterFilter::from(&payload),
+                    REDRIVE_BATCH_LIMIT,
+                    &mut conn,
+                )
+                .await?;
+                let entry = dead_letters_audit_entry(
+                    &auth_info,
+                    "webhook.dead_letters_redriven",
+                    &payload,
+                    deliveries.len() as u64,
+                );
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(deliveries)
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    let deliveries = match result {
+        Ok(d) => d,
+        Err(e) => {
+            tracker.return_connection(conn);
+            return e.into_response();
+        }
+    };
+
+    // Load each webhook once; deliveries are redriven with its current URL and secret
+    let mut webhooks: HashMap<Uuid, Webhook> = HashMap::new();
+    for delivery in &deliveries {
+        if webhooks.contains_key(&delivery.webhook_id) {
+            continue;
+        }
+        match get_webhook_by_id(delivery.webhook_id, auth_info.account_id, &mut conn).await {
+            Ok(w) => {
+                webhooks.insert(w.id, w);
+            }
+            Err(e) => {
+                tracker.return_connection(conn);
+                return e.into_response();
+            }
+        }
+    }
+
+    tracker.return_connection(conn);
+
+    let redriven = deliveries.len() as i64;
+    let dispatcher = WebhookDispatcher::new();
+    for delivery in deliveries {
+        if let Some(webhook) = webhooks.get(&delivery.webhook_id) {
+            dispatcher.redrive(webhook.clone(), delivery);
+        }
+    }
+
+    (
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "message": "Redrive scheduled",
+            "redriven": redriven,
+            "has_more": redriven == REDRIVE_BATCH_LIMIT
+        })),
+    )
+        .into_response()
+}
+
+/// POST /api/v1/webhooks/dead-letters/discard
+/// Discard dead-lettered deliveries matching the selection without delivering them
+#[instrument(fields(service = "/api/v1/webhooks/dead-letters/discard"))]
+pub async fn discard_dead_letters(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Json(payload): Json<DeadLetterSelectionRequest>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = ?payload.webhook_id,
+        "Discarding dead-lettered deliveries"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    // Scoping to a single webhook requires owning it
+    if let Some(webhook_id) = payload.webhook_id {
+        if let Err(e) = get_webhook_by_id(webhook_id, auth_info.account_id, &mut conn).await {
+            tracker.return_connection(conn);
+            return e.into_response();
+        }
+    }
+
+    let result = match begin_transaction(&mut conn).await {
+        Ok(()) => {
+            let result = async {
+                let discarded = discard_dead_letters_db(
+                    auth_info.account_id,
+                    &DeadLetterFilter::from(&payload),
+                    &mut conn,
+                )
+                .await?;
+                let entry = dead_letters_audit_entry(
+                    &auth_info,
+                    "webhook.dead_letters_discarded",
+                    &payload,
+                    discarded,
+                );
+                create_audit_entry(entry, &mut conn).await?;
+                Ok(discarded)
+            }
+            .await;
+            finish_transaction(result, &mut conn).await
+        }
+        Err(e) => Err(e),
+    };
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(discarded) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "message": "Dead-lettered deliveries discarded",
+                "discarded": discarded
+            })),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookTestResponse {
+    pub webhook_id: Uuid,
+    pub success: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// POST /api/v1/webhooks/:id/test
+/// Send a signed `webhook.ping` event and report the receiver's status and latency
+#[instrument(fields(service = "/api/v1/webhooks/:id/test"))]
+pub async fn test_webhook(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(webhook_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = %webhook_id,
+        "Testing webhook"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let webhook = match get_webhook_by_id(webhook_id, auth_info.account_id, &mut conn).await {
+        Ok(w) => w,
+        Err(e) => {
+            tracker.return_connection(conn);
+            return e.into_response();
+        }
+    };
+
+    // Release the connection before waiting on the receiver
+    tracker.return_connection(conn);
+
+    let response = match WebhookDispatcher::new().send_ping(&webhook).await {
+        Ok(attempt) => WebhookTestResponse {
+            webhook_id,
+            success: attempt.success,
+            status_code: Some(attempt.status_code),
+            latency_ms: Some(attempt.latency_ms),
+            error: None,
+        },
+        Err(reason) => WebhookTestResponse {
+            webhook_id,
+            success: false,
+            status_code: None,
+            latency_ms: None,
+            error: Some(reason),
+        },
+    };
+
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+/// POST /api/v1/webhooks/:id/verify
+/// Re-run the verification handshake for a webhook that is pending verification
+#[instrument(fields(service = "/api/v1/webhooks/:id/verify"))]
+pub async fn verify_webhook(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(webhook_id): Path<Uuid>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = %webhook_id,
+        "Verifying webhook"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let webhook = match get_webhook_by_id(webhook_id, auth_info.account_id, &mut conn).await {
+        Ok(w) => w,
+        Err(e) => {
+            tracker.return_connection(conn);
+            return e.into_response();
+        }
+    };
+
+    if webhook.status != "pending_verification" {
+        tracker.return_connection(conn);
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "WEBHOOK_NOT_PENDING_VERIFICATION",
+                    "message": format!("Webhook is not pending verification (status: {})", webhook.status)
+                }
+            })),
+        )
+            .into_response();
+    }
+
+    // The endpoint can take its time to answer; don't hold a connection meanwhile
+    tracker.return_connection(conn);
+
+    let (webhook, verification_error) =
+        match WebhookDispatcher::new().verify_endpoint(&webhook).await {
+            Ok(()) => match activate_webhook(webhook_id, auth_info.account_id).await {
+                Ok(activated) => (activated, None),
+                Err(e) => return e.into_response(),
+            },
+            Err(reason) => (webhook, Some(reason)),
+        };
+
+    (
+        StatusCode::OK,
+        Json(WebhookResponse {
+            verification_error,
+            ..WebhookResponse::from(webhook)
+        }),
+    )
+        .into_response()
+}
+
+/// POST /api/v1/webhooks/:id/backfill
+/// Replay the account's historical events of a time range to a webhook
+#[instrument(fields(service = "/api/v1/webhooks/:id/backfill"))]
+pub async fn create_webhook_backfill(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path(webhook_id): Path<Uuid>,
+    Json(payload): Json<CreateBackfillRequest>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = %webhook_id,
+        start_at = %payload.start_at,
+        end_at = %payload.end_at,
+        "Creating webhook backfill"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let webhook = match get_webhook_by_id(webhook_id, auth_info.account_id, &mut conn).await {
+        Ok(w) => w,
+        Err(e) => {
+            tracker.return_connection(conn);
+            return e.into_response();
+        }
+    };
+
+    if webhook.status != "active" {
+        tracker.return_connection(conn);
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "WEBHOOK_NOT_ACTIVE",
+                    "message": format!("Only active webhooks can be backfilled (status: {})", webhook.status)
+                }
+            })),
+        )
+            .into_response();
+    }
+
+    let (event_types, end_at) = match validate_backfill_request(&webhook, &payload) {
+        Ok(validated) => validated,
+        Err(e) => {
+            tracker.return_connection(conn);
+            return e.into_response();
+        }
+    };
+
+    let result = create_backfill(
+        webhook_id,
+        auth_info.account_id,
+        &event_types,
+        payload.start_at,
+        end_at,
+        &mut conn,
+    )
+    .await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(backfill) => {
+            start_backfill(backfill.clone());
+            (
+                StatusCode::ACCEPTED,
+                Json(WebhookBackfillResponse::from(backfill)),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Check a backfill request against the webhook
+/// Returns the event types to replay and the end of the range, capped at now so
+/// events that happen during the backfill are only delivered live
+fn validate_backfill_request(
+    webhook: &Webhook,
+    payload: &CreateBackfillRequest,
+) -> Result<(Vec<String>, chrono::DateTime<chrono::Utc>), ServiceError> {
+    let end_at = payload.end_at.min(chrono::Utc::now());
+    if payload.start_at >= end_at {
+        return Err(ServiceError::ValidationError(
+            "start_at must be before end_at and in the past".to_string(),
+        ));
+    }
+
+    let requested = match &payload.event_types {
+        Some(types) if types.is_empty() => {
+            return Err(ServiceError::ValidationError(
+                "event_types must not be empty".to_string(),
+            ));
+        }
+        Some(types) => types.clone(),
+        None => vec!["*".to_string()],
+    };
+
+    let mut event_types: Vec<String> = Vec::new();
+    for requested_type in &requested {
+        if !WEBHOOK_EVENT_TYPES.contains(&requested_type.as_str()) {
+            return Err(ServiceError::ValidationError(format!(
+                "Unknown event type '{}'. Supported: {}",
+                requested_type,
+                WEBHOOK_EVENT_TYPES.join(", ")
+            )));
+        }
+
+        let expanded = WEBHOOK_EVENT_TYPES
+            .iter()
+            .filter(|t| **t != "*" && (requested_type == "*" || requested_type == *t));
+        for event_type in expanded {
+            if !webhook.subscribes_to(event_type) {
+                if requested_type == "*" {
+                    continue;
+                }
+                return Err(ServiceError::ValidationError(format!(
+                    "Webhook is not subscribed to '{}'",
+                    event_type
+                )));
+            }
+            if !event_types.iter().any(|t| t == event_type) {
+                event_types.push(event_type.to_string());
+            }
+        }
+    }
+
+    if event_types.is_empty() {
+        return Err(ServiceError::ValidationError(
+            "Webhook is not subscribed to any event type that can be backfilled".to_string(),
+        ));
+    }
+
+    Ok((event_types, end_at))
+}
+
+/// GET /api/v1/webhooks/:id/backfills/:backfill_id
+/// Progress of a backfill
+#[instrument(fields(service = "/api/v1/webhooks/:id/backfills/:backfill_id"))]
+pub async fn get_webhook_backfill(
+    Extension(auth_info): Extension<AuthenticatedApiKey>,
+    Path((webhook_id, backfill_id)): Path<(Uuid, Uuid)>,
+) -> Response {
+    tracing::info!(
+        account_id = %auth_info.account_id,
+        webhook_id = %webhook_id,
+        backfill_id = %backfill_id,
+        "Getting webhook backfill"
+    );
+
+    let tracker = match POOL_STATE_TRACKER.get() {
+        Some(t) => t,
+        None => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Database connection unavailable"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get database connection");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "DATABASE_ERROR",
+                        "message": "Failed to connect to database"
+                    }
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let result =
+        get_backfill_for_account(backfill_id, webhook_id, auth_info.account_id, &mut conn).await;
+    tracker.return_connection(conn);
+
+    match result {
+        Ok(backfill) => (
+            StatusCode::OK,
+            Json(WebhookBackfillResponse::from(backfill)),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /.well-known/webhook-keys.json
+/// Public keys for verifying `ed25519` webhook signatures, as a JSON Web Key Set
+#[instrument(fields(service = "/.well-known/webhook-keys.json"))]
+pub async fn webhook_signing_keys() -> Response {
+    (
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(signing_keys().jwks()),
+    )
+        .into_response()
+}
@@ -1,3 +1,4 @@
+pub mod propagation;
 pub mod telemetry;
 
 pub use telemetry::{init_telemetry, shutdown_telemetry};
@@ -0,0 +1,83 @@
+use opentelemetry::{Context, global};
+use std::collections::HashMap;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+tokio::task_local! {
+    /// Id of the request being handled by the current task, set by `request_id_middleware`
+    pub static REQUEST_ID: Uuid;
+}
+
+/// Where a piece of background work was started from
+///
+/// Background tasks outlive the request that started them. Rather than keeping the
+/// request's span open, they keep its OpenTelemetry context and parent their own
+/// spans to it, so the trace stays connected.
+#[derive(Debug, Clone)]
+pub struct TraceOrigin {
+    pub context: Context,
+    pub request_id: Option<Uuid>,
+}
+
+impl TraceOrigin {
+    /// Capture the current span's context and request id
+    pub fn current() -> Self {
+        Self {
+            context: Span::current().context(),
+            request_id: REQUEST_ID.try_with(|id| *id).ok(),
+        }
+    }
+
+    /// Make `span` a child of the originating span in the exported trace
+    pub fn adopt(&self, span: &Span) {
+        span.set_parent(self.context.clone());
+    }
+}
+
+/// W3C trace context headers (`traceparent`, `tracestate`) for the current span
+/// Empty when tracing is disabled or the span is not sampled
+pub fn trace_context_headers() -> HashMap<String, String> {
+    let context = Span::current().context();
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut headers));
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::TracerProvider};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_headers_carry_current_span() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(trace_context_headers().is_empty());
+
+            let span = tracing::info_span!("webhook.attempt");
+            let _guard = span.enter();
+            let trace_id = span.context().span().span_context().trace_id();
+
+            let headers = trace_context_headers();
+            let traceparent = &headers["traceparent"];
+            assert!(traceparent.starts_with(&format!("00-{}-", trace_id)));
+        });
+    }
+
+    #[tokio::test]
+    async fn test_origin_captures_request_id() {
+        let request_id = Uuid::new_v4();
+        let origin = REQUEST_ID
+            .scope(request_id, async { TraceOrigin::current() })
+            .await;
+        assert_eq!(origin.request_id, Some(request_id));
+        assert_eq!(TraceOrigin::current().request_id, None);
+    }
+}
@@ -1,7 +1,9 @@
 use opentelemetry::{KeyValue, global, trace::TracerProvider as _};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{
-    Resource, runtime,
+    Resource,
+    propagation::TraceContextPropagator,
+    runtime,
     trace::{RandomIdGenerator, Sampler},
 };
 use tracing::info;
@@ -121,6 +123,9 @@ pub fn init_telemetry(config: Option<TelemetryConfig>) -> Result<(), Box<dyn std
         ),
     ]);
 
+    // Outgoing webhooks carry W3C trace context headers
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
     // Initialize tracing if enabled
     let tracer = if config.enable_tracing {
         println!("📊 Setting up distributed tracing...");
@@ -1,5 +1,10 @@
 use payments_backend_dodo::{
-    datalayer::initialize_database, logging::init_telemetry, routes::create_router, state::AppState,
+    datalayer::initialize_database,
+    logging::init_telemetry,
+    middleware::admin_auth::load_admin_credentials,
+    routes::create_router,
+    services::{WebhookDispatcher, api_key_usage, webhook_backfill, webhook_secrets},
+    state::AppState,
 };
 
 #[tokio::main]
@@ -32,6 +37,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::info!("Redis connection established successfully");
 
+    // Operator credentials are parsed once; without any the admin API rejects every request
+    let admin_credentials = load_admin_credentials();
+    if admin_credentials == 0 {
+        tracing::warn!("No admin credentials configured, admin API is disabled");
+    } else {
+        tracing::info!(credentials = admin_credentials, "Admin credentials loaded");
+    }
+
+    // Continue webhook backfills interrupted by the last shutdown
+    tokio::spawn(webhook_backfill::resume_backfills());
+
+    // Retry failed webhook deliveries once due, including those interrupted by a restart
+    tokio::spawn(WebhookDispatcher::new().retry_due_deliveries_periodically());
+
+    // Move webhook and API key signing secrets off retired master keys
+    tokio::spawn(webhook_secrets::rewrap_webhook_secrets());
+    tokio::spawn(webhook_secrets::rewrap_signing_secrets());
+
+    // Write API key usage recorded by authentication in batches
+    tokio::spawn(api_key_usage::flush_usage_periodically());
+
     // Create router with all routes and app state
     let app = create_router(app_state);
 
@@ -65,6 +91,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     .with_graceful_shutdown(shutdown_signal())
     .await?;
 
+    // Keep the usage recorded since the last periodic flush
+    api_key_usage::flush_usage().await;
+
     // Shutdown telemetry gracefully
     payments_backend_dodo::logging::shutdown_telemetry();
 
@@ -0,0 +1,272 @@
+use crate::{
+    datalayer::CRUD::audit_log::AuditActor, errors::errors::create_error_response,
+    logging::propagation::REQUEST_ID, middleware::ip_allowlist::client_ip,
+};
+use axum::{
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::{self, FromFnLayer, Next},
+    response::Response,
+};
+use sha2::{Digest, Sha256};
+use std::{net::IpAddr, sync::OnceLock};
+use uuid::Uuid;
+
+/// Name the legacy `ADMIN_API_TOKEN` authenticates as
+const LEGACY_ADMIN_NAME: &str = "admin";
+
+/// Operator credentials, read from the environment once
+static ADMIN_CREDENTIALS: OnceLock<Vec<AdminCredential>> = OnceLock::new();
+
+/// What an operator may do on the admin API
+///
+/// Roles are ordered: each one holds everything the previous one does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AdminRole {
+    /// Look up accounts, transactions, circuit breakers and the audit log
+    Viewer,
+    /// Also suspend and close accounts, retry webhooks and reset rate limits
+    Operator,
+}
+
+impl AdminRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AdminRole::Viewer => "viewer",
+            AdminRole::Operator => "operator",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<AdminRole> {
+        match role {
+            "viewer" => Some(AdminRole::Viewer),
+            "operator" => Some(AdminRole::Operator),
+            _ => None,
+        }
+    }
+}
+
+/// The operator behind an admin request, stored in its extensions by `admin_auth_middleware`
+#[derive(Debug, Clone)]
+pub struct AdminPrincipal {
+    pub name: String,
+    pub role: AdminRole,
+    pub client_ip: Option<IpAddr>,
+}
+
+impl AdminPrincipal {
+    /// The operator as recorded in the audit log
+    pub fn audit_actor(&self) -> AuditActor {
+        AuditActor {
+            actor_type: "admin",
+            actor_id: None,
+            actor_name: Some(self.name.clone()),
+            request_id: REQUEST_ID.try_with(|id| *id).ok(),
+            ip_address: self.client_ip.map(|ip| ip.to_string()),
+        }
+    }
+}
+
+/// An operator credential from `ADMIN_CREDENTIALS`
+#[derive(Debug, PartialEq)]
+struct AdminCredential {
+    name: String,
+    role: AdminRole,
+    token: String,
+}
+
+/// Operator authentication for `/admin` routes
+///
+/// Requests must carry `Authorization: Bearer <token>` with a token from
+/// `ADMIN_CREDENTIALS`, or the legacy `ADMIN_API_TOKEN`, which acts as an operator
+/// named `admin`. With neither set the admin routes are disabled and always answer 401.
+pub async fn admin_auth_middleware(mut request: Request, next: Next) -> Result<Response, Response> {
+    let request_id = request.extensions().get::<Uuid>().map(|id| id.to_string());
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+    let credential = provided.and_then(|token| {
+        configured_credentials()
+            .iter()
+            .find(|credential| tokens_match(token, &credential.token))
+    });
+
+    match credential {
+        Some(credential) => {
+            tracing::info!(
+                admin = %credential.name,
+                role = credential.role.as_str(),
+                "Admin authenticated"
+            );
+            let principal = AdminPrincipal {
+                name: credential.name.clone(),
+                role: credential.role,
+                client_ip: client_ip(&request),
+            };
+            request.extensions_mut().insert(principal);
+            Ok(next.run(request).await)
+        }
+        None => {
+            tracing::warn!("Rejected admin request with missing or invalid token");
+            Err(create_error_response(
+                StatusCode::UNAUTHORIZED,
+                "invalid_admin_token",
+                "A valid admin token is required",
+                request_id,
+            ))
+        }
+    }
+}
+
+/// Layer rejecting admin requests from operators below `role`
+///
+/// Attach with `route_layer` on a method router, so the check runs after
+/// `admin_auth_middleware` has identified the operator.
+pub fn requires_admin(role: AdminRole) -> AdminRoleLayer {
+    middleware::from_fn_with_state(role, require_admin_role as fn(_, _, _) -> _)
+}
+
+type AdminRoleCheck =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, Response>> + Send>>;
+
+type AdminRoleLayer = FromFnLayer<
+    fn(State<AdminRole>, Request, Next) -> AdminRoleCheck,
+    AdminRole,
+    (State<AdminRole>, Request),
+>;
+
+fn require_admin_role(
+    State(role): State<AdminRole>,
+    request: Request,
+    next: Next,
+) -> AdminRoleCheck {
+    Box::pin(async move {
+        let granted = request
+            .extensions()
+            .get::<AdminPrincipal>()
+            .is_some_and(|principal| principal.role >= role);
+
+        if !granted {
+            let request_id = request.extensions().get::<Uuid>().map(|id| id.to_string());
+            tracing::warn!(role = role.as_str(), "Admin lacks required role");
+            return Err(create_error_response(
+                StatusCode::FORBIDDEN,
+                "insufficient_admin_role",
+                &format!("The '{}' admin role is required", role.as_str()),
+                request_id,
+            ));
+        }
+
+        Ok(next.run(request).await)
+    })
+}
+
+/// Read the operator credentials, returning how many are configured
+///
+/// Called at startup so malformed entries are reported once; changes to the
+/// environment afterwards need a restart.
+pub fn load_admin_credentials() -> usize {
+    configured_credentials().len()
+}
+
+/// Credentials from `ADMIN_CREDENTIALS` and `ADMIN_API_TOKEN`
+fn configured_credentials() -> &'static [AdminCredential] {
+    ADMIN_CREDENTIALS.get_or_init(|| {
+        let mut credentials =
+            parse_credentials(&std::env::var("ADMIN_CREDENTIALS").unwrap_or_default());
+
+        let legacy_token = std::env::var("ADMIN_API_TOKEN").unwrap_or_default();
+        if !legacy_token.is_empty() {
+            credentials.push(AdminCredential {
+                name: LEGACY_ADMIN_NAME.to_string(),
+                role: AdminRole::Operator,
+                token: legacy_token,
+            });
+        }
+
+        credentials
+    })
+}
+
+/// Parse comma-separated `name:role:token` entries
+///
+/// Malformed entries and entries with an unknown role or an empty token are
+/// skipped with a warning, so one typo cannot lock every operator out.
+fn parse_credentials(value: &str) -> Vec<AdminCredential> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let mut parts = entry.splitn(3, ':');
+            let (Some(name), Some(role), Some(token)) = (parts.next(), parts.next(), parts.next())
+            else {
+                tracing::warn!("Ignoring ADMIN_CREDENTIALS entry without name:role:token");
+                return None;
+            };
+            let Some(role) = AdminRole::parse(role.trim()) else {
+                tracing::warn!(admin = %name, role = %role, "Ignoring admin credential with unknown role");
+                return None;
+            };
+            if name.trim().is_empty() || token.is_empty() {
+                tracing::warn!(admin = %name, "Ignoring admin credential without name or token");
+                return None;
+            }
+            Some(AdminCredential {
+                name: name.trim().to_string(),
+                role,
+                token: token.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Compare tokens without leaking the position of the first difference
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    let provided = Sha256::digest(provided.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    provided
+        .iter()
+        .zip(expected.iter())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_credentials() {
+        let credentials = parse_credentials(
+            "alice:operator:s3cret, bob:viewer:tok:en,carol:owner:x,dave:viewer:,broken",
+        );
+        assert_eq!(
+            credentials,
+            vec![
+                AdminCredential {
+                    name: "alice".to_string(),
+                    role: AdminRole::Operator,
+                    token: "s3cret".to_string(),
+                },
+                AdminCredential {
+                    name: "bob".to_string(),
+                    role: AdminRole::Viewer,
+                    token: "tok:en".to_string(),
+                },
+            ]
+        );
+        assert!(parse_credentials("").is_empty());
+    }
+
+    #[test]
+    fn test_admin_roles_are_ordered() {
+        assert!(AdminRole::Operator >= AdminRole::Viewer);
+        assert!(AdminRole::Viewer < AdminRole::Operator);
+        assert_eq!(AdminRole::parse("operator"), Some(AdminRole::Operator));
+        assert_eq!(AdminRole::parse("admin"), None);
+    }
+}
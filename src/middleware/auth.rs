@@ -1,26 +1,124 @@
 use crate::{
-    datalayer::CRUD::api_key::ApiKeyBuilder, datalayer::db_ops::constants::POOL_STATE_TRACKER,
-    errors::errors::create_error_response, state::AppState,
+    datalayer::CRUD::api_key::{get_api_key_by_hashes, get_api_key_for_auth, rehash_api_key},
+    datalayer::CRUD::audit_log::AuditActor,
+    datalayer::CRUD::helper::apikey_generator::{
+        LIVE_PREFIX, TEST_PREFIX, candidate_hashes, hash_api_key, is_current_hash, is_live_prefix,
+        parse_key_prefix, verify_api_key,
+    },
+    datalayer::CRUD::helper::user_credentials::{SESSION_PREFIX, hash_session_token},
+    datalayer::CRUD::types::ApiKey,
+    datalayer::CRUD::users::get_session_principals,
+    datalayer::db_ops::constants::POOL_STATE_TRACKER,
+    errors::errors::create_error_response,
+    logging::propagation::REQUEST_ID,
+    middleware::{
+        ip_allowlist::{allowed_ips_from_json, client_ip, is_ip_allowed},
+        request_signing::{
+            MAX_SIGNED_BODY_BYTES, NONCE_HEADER, SIGNATURE_HEADER, SIGNATURE_SCHEME,
+            TIMESTAMP_HEADER, TIMESTAMP_TOLERANCE_SECONDS, canonical_request, claim_nonce,
+            is_timestamp_fresh, is_valid_nonce, verify_signature,
+        },
+        roles::Role,
+        scopes::{Scope, permissions_from_json},
+    },
+    services::{
+        access_tokens::{AccessTokenError, access_token_keys, looks_like_jwt, verify_access_token},
+        api_key_cache::API_KEY_CACHE,
+        api_key_usage::{record_api_key_use, record_ip_rejection},
+        webhook_secrets::secret_keys,
+    },
+    state::AppState,
 };
 use axum::{
+    body::Body,
     extract::{Request, State},
     http::{StatusCode, header},
     middleware::Next,
     response::Response,
 };
-use sha2::{Digest, Sha256};
+use std::net::IpAddr;
 use uuid::Uuid;
 
+/// Header choosing the account a user session acts on
+pub const ACCOUNT_HEADER: &str = "x-account-id";
+
 /// Authenticated API key information stored in request extensions
+///
+/// User sessions are described the same way: `api_key_id` is the session id,
+/// `key_prefix` the session prefix and `permissions` the scopes of the user's role.
 #[derive(Debug, Clone)]
 pub struct AuthenticatedApiKey {
     pub api_key_id: Uuid,
     pub account_id: Uuid,
     pub key_prefix: String,
+    /// Permissions held by the key, scopes or the legacy `read`/`write`
+    pub permissions: Vec<String>,
+    /// false for test-mode keys, which only reach test-mode data
+    pub livemode: bool,
+    /// Whether the request was signed rather than carrying the key
+    pub signed: bool,
+    /// The account only accepts signed requests for write scopes
+    pub require_signed_writes: bool,
+    /// Networks the key may be used from; empty allows any address
+    pub allowed_ips: Vec<String>,
+    /// The signed-in user, for session requests
+    pub user_id: Option<Uuid>,
+    /// The user's role on the account, for session requests
+    pub role: Option<Role>,
+    /// Address the request came from
+    pub client_ip: Option<IpAddr>,
+}
+
+impl AuthenticatedApiKey {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.permissions.iter().any(|p| scope.granted_by(p))
+    }
+
+    /// The caller as recorded in the audit log: the user of a session, otherwise the key
+    pub fn audit_actor(&self) -> AuditActor {
+        let (actor_type, actor_id) = match self.user_id {
+            Some(user_id) => ("user", user_id),
+            None => ("api_key", self.api_key_id),
+        };
+        AuditActor {
+            actor_type,
+            actor_id: Some(actor_id),
+            actor_name: None,
+            request_id: REQUEST_ID.try_with(|id| *id).ok(),
+            ip_address: self.client_ip.map(|ip| ip.to_string()),
+        }
+    }
+}
+
+/// Why a credential was refused, answered as an error response by the caller
+struct AuthFailure {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl AuthFailure {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn into_response(self, request_id: &Option<String>) -> Response {
+        create_error_response(self.status, self.code, &self.message, request_id.clone())
+    }
 }
 
 /// API key authentication middleware
-/// Extracts and validates API key from Authorization header
+///
+/// Accepts either a bearer key (`Authorization: Bearer dodo_live_...`) or a
+/// signed request (`Authorization: Signature <api key id>` with the headers of
+/// `request_signing`), which never sends the key itself. A bearer user session
+/// (`Bearer dodo_session_...`) acts on the account named by `X-Account-Id`, and
+/// a bearer access token (a JWT from `/api/v1/oauth/token`) is verified by its
+/// signature, then checked against the cached record of the key it was exchanged from.
 pub async fn auth_middleware(
     State(state): State<AppState>,
     mut request: Request,
@@ -32,18 +130,81 @@ pub async fn auth_middleware(
     let auth_header = request
         .headers()
         .get(header::AUTHORIZATION)
-        .and_then(|h| h.to_str().ok());
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string);
 
-    let api_key = match auth_header {
+    let bearer = auth_header
+        .as_deref()
+        .and_then(|h| h.strip_prefix("Bearer "));
+    if let Some(token) = bearer.filter(|token| token.starts_with(SESSION_PREFIX)) {
+        let account_header = request
+            .headers()
+            .get(ACCOUNT_HEADER)
+            .map(|h| h.to_str().ok().and_then(|v| v.trim().parse::<Uuid>().ok()));
+        let account_id = match account_header {
+            None => None,
+            Some(Some(account_id)) => Some(account_id),
+            Some(None) => {
+                return Err(create_error_response(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_account_id",
+                    "X-Account-Id must be an account id",
+                    request_id,
+                ));
+            }
+        };
+
+        let auth_info =
+            authenticate_session(token, account_id, client_ip(&request), &request_id).await?;
+        tracing::info!(
+            user_id = ?auth_info.user_id,
+            account_id = %auth_info.account_id,
+            role = ?auth_info.role,
+            "User session authenticated successfully"
+        );
+        request.extensions_mut().insert(auth_info);
+        return Ok(next.run(request).await);
+    }
+
+    if let Some(token) = bearer.filter(|token| looks_like_jwt(token)) {
+        let auth_info = authenticate_access_token(token, client_ip(&request), &request_id).await?;
+        tracing::debug!(
+            api_key_id = %auth_info.api_key_id,
+            account_id = %auth_info.account_id,
+            "Access token verified"
+        );
+        record_api_key_use(
+            auth_info.api_key_id,
+            client_ip(&request).map(|ip| ip.to_string()),
+            user_agent(&request).as_deref(),
+        );
+        request.extensions_mut().insert(auth_info);
+        return Ok(next.run(request).await);
+    }
+
+    let auth_info = match auth_header.as_deref() {
         Some(header_value) => {
-            // Expected format: "Bearer sk_live_..." or "Bearer sk_test_..."
             if let Some(key) = header_value.strip_prefix("Bearer ") {
-                key
+                // Expected format: "Bearer dodo_live_..." or "Bearer dodo_test_..."
+                let client_ip = client_ip(&request);
+                let user_agent = user_agent(&request);
+                authenticate_api_key(key, client_ip, user_agent.as_deref(), &request_id).await?
+            } else if let Some(key_id) = header_value.strip_prefix(SIGNATURE_SCHEME) {
+                let (api_key_record, signed_request) =
+                    authenticate_signed(&state, key_id, request, &request_id).await?;
+                request = signed_request;
+                authorize_api_key(
+                    &api_key_record,
+                    None,
+                    client_ip(&request),
+                    user_agent(&request).as_deref(),
+                )
+                .map_err(|failure| failure.into_response(&request_id))?
             } else {
                 return Err(create_error_response(
                     StatusCode::UNAUTHORIZED,
                     "invalid_authorization_header",
-                    "Authorization header must use Bearer scheme",
+                    "Authorization header must use the Bearer or Signature scheme",
                     request_id,
                 ));
             }
@@ -58,23 +219,422 @@ pub async fn auth_middleware(
         }
     };
 
-    // Validate API key format (should start with pk_live_ or pk_test_)
-    if !api_key.starts_with("pk_") {
+    tracing::info!(
+        api_key_id = %auth_info.api_key_id,
+        account_id = %auth_info.account_id,
+        key_prefix = %auth_info.key_prefix,
+        livemode = auth_info.livemode,
+        signed = auth_info.signed,
+        "API key authenticated successfully"
+    );
+
+    // Store authenticated API key info in request extensions
+    request.extensions_mut().insert(auth_info);
+
+    Ok(next.run(request).await)
+}
+
+/// Authenticate a bearer API key, as sent to `auth_middleware` or exchanged for an access token
+pub async fn authenticate_api_key(
+    api_key: &str,
+    client_ip: Option<IpAddr>,
+    user_agent: Option<&str>,
+    request_id: &Option<String>,
+) -> Result<AuthenticatedApiKey, Response> {
+    let api_key_record = authenticate_bearer(api_key, request_id).await?;
+    authorize_api_key(&api_key_record, Some(api_key), client_ip, user_agent)
+        .map_err(|failure| failure.into_response(request_id))
+}
+
+/// Check an identified key may be used now and from `client_ip`, and record the use
+///
+/// `bearer_key` is the plain-text key when the request carried it rather than a signature.
+fn authorize_api_key(
+    api_key_record: &ApiKey,
+    bearer_key: Option<&str>,
+    client_ip: Option<IpAddr>,
+    user_agent: Option<&str>,
+) -> Result<AuthenticatedApiKey, AuthFailure> {
+    check_api_key_usable(api_key_record)?;
+
+    // Keys with an allowlist only work from the listed networks
+    let allowed_ips = allowed_ips_from_json(api_key_record.allowed_ips.as_ref());
+    check_ip_allowed(
+        api_key_record.id,
+        api_key_record.account_id,
+        &allowed_ips,
+        client_ip,
+    )?;
+
+    // Move keys hashed with a retired pepper, or none, to the active pepper
+    if let Some(api_key) = bearer_key {
+        if !is_current_hash(&api_key_record.key_hash) {
+            tokio::spawn(rehash(
+                api_key_record.id,
+                api_key_record.key_hash.clone(),
+                hash_api_key(api_key),
+            ));
+        }
+    }
+
+    // Usage is buffered in memory and written in batches
+    record_api_key_use(
+        api_key_record.id,
+        client_ip.map(|ip| ip.to_string()),
+        user_agent,
+    );
+
+    Ok(AuthenticatedApiKey {
+        api_key_id: api_key_record.id,
+        account_id: api_key_record.account_id,
+        key_prefix: api_key_record.key_prefix.clone(),
+        permissions: permissions_from_json(api_key_record.permissions.as_ref()),
+        livemode: is_live_prefix(&api_key_record.key_prefix),
+        signed: bearer_key.is_none(),
+        require_signed_writes: api_key_record.account_requires_signed_writes,
+        allowed_ips,
+        user_id: None,
+        role: None,
+        client_ip,
+    })
+}
+
+/// Reject a key that is inactive, revoked or expired, or whose account is not active
+fn check_api_key_usable(api_key_record: &ApiKey) -> Result<(), AuthFailure> {
+    // Check if API key is active
+    if api_key_record.status != "active" {
+        return Err(AuthFailure::new(
+            StatusCode::UNAUTHORIZED,
+            "inactive_api_key",
+            format!("API key is not active (status: {})", api_key_record.status),
+        ));
+    }
+
+    // Check if API key is revoked
+    if api_key_record.revoked_at.is_some() {
+        return Err(AuthFailure::new(
+            StatusCode::UNAUTHORIZED,
+            "revoked_api_key",
+            "API key has been revoked",
+        ));
+    }
+
+    // Check if API key is expired
+    if let Some(expires_at) = api_key_record.expires_at {
+        if expires_at < chrono::Utc::now() {
+            return Err(AuthFailure::new(
+                StatusCode::UNAUTHORIZED,
+                "expired_api_key",
+                "API key has expired",
+            ));
+        }
+    }
+
+    // Operators may suspend or close the account behind the key
+    if let Some(status) = api_key_record
+        .account_status
+        .as_deref()
+        .filter(|status| *status != "active")
+    {
+        return Err(AuthFailure::new(
+            StatusCode::FORBIDDEN,
+            "inactive_account",
+            format!("Account is not active (status: {})", status),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verify an access token and check the key it was exchanged from is still usable
+///
+/// Revoking the key or suspending its account takes effect on the next request. The
+/// allowlist and signed-write requirement are read from the key's current record;
+/// scopes come from the token.
+async fn authenticate_access_token(
+    token: &str,
+    client_ip: Option<IpAddr>,
+    request_id: &Option<String>,
+) -> Result<AuthenticatedApiKey, Response> {
+    let claims = verify_access_token(access_token_keys(), token).map_err(|e| {
+        let (code, message) = match e {
+            AccessTokenError::Expired => ("expired_access_token", "Access token has expired"),
+            AccessTokenError::Invalid(reason) => {
+                tracing::warn!(reason = %reason, "Invalid access token");
+                ("invalid_access_token", "Access token is invalid")
+            }
+        };
+        AuthFailure::new(StatusCode::UNAUTHORIZED, code, message).into_response(request_id)
+    })?;
+
+    let api_key_record = find_api_key_by_id(claims.sub, request_id).await?;
+    if api_key_record.account_id != claims.account_id {
+        tracing::warn!(api_key_id = %claims.sub, "Access token names another account than its key");
+        return Err(create_error_response(
+            StatusCode::UNAUTHORIZED,
+            "invalid_access_token",
+            "Access token is invalid",
+            request_id.clone(),
+        ));
+    }
+    check_api_key_usable(&api_key_record).map_err(|failure| failure.into_response(request_id))?;
+
+    let allowed_ips = allowed_ips_from_json(api_key_record.allowed_ips.as_ref());
+    check_ip_allowed(claims.sub, claims.account_id, &allowed_ips, client_ip)
+        .map_err(|failure| failure.into_response(request_id))?;
+
+    Ok(AuthenticatedApiKey {
+        api_key_id: claims.sub,
+        account_id: claims.account_id,
+        key_prefix: if claims.livemode {
+            LIVE_PREFIX
+        } else {
+            TEST_PREFIX
+        }
+        .to_string(),
+        permissions: claims.scopes(),
+        livemode: claims.livemode,
+        signed: false,
+        require_signed_writes: api_key_record.account_requires_signed_writes,
+        allowed_ips,
+        user_id: None,
+        role: None,
+        client_ip,
+    })
+}
+
+/// Reject a key, or a token exchanged from it, used from outside its allowlist
+fn check_ip_allowed(
+    api_key_id: Uuid,
+    account_id: Uuid,
+    allowed_ips: &[String],
+    client_ip: Option<IpAddr>,
+) -> Result<(), AuthFailure> {
+    if is_ip_allowed(allowed_ips, client_ip) {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        api_key_id = %api_key_id,
+        account_id = %account_id,
+        client_ip = ?client_ip,
+        "API key used from an address outside its allowlist"
+    );
+    record_ip_rejection(api_key_id);
+    Err(AuthFailure::new(
+        StatusCode::FORBIDDEN,
+        "ip_not_allowed",
+        match client_ip {
+            Some(ip) => format!("API key is not allowed from {}", ip),
+            None => "API key is not allowed from an unknown address".to_string(),
+        },
+    ))
+}
+
+fn user_agent(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Find the record of a bearer key and check the key matches it
+async fn authenticate_bearer(
+    api_key: &str,
+    request_id: &Option<String>,
+) -> Result<ApiKey, Response> {
+    // Malformed or mistyped keys are rejected before any lookup
+    let Some(key_prefix) = parse_key_prefix(api_key) else {
         return Err(create_error_response(
             StatusCode::UNAUTHORIZED,
             "invalid_api_key_format",
-            "API key must start with 'pk_'",
-            request_id,
+            "API key is malformed",
+            request_id.clone(),
+        ));
+    };
+
+    // Hash the API key; the first candidate is the hash with the active pepper
+    let key_hashes = candidate_hashes(api_key);
+
+    // Look up API key in the cache, then in the database
+    let cache = API_KEY_CACHE.get();
+    let cached = match cache {
+        Some(cache) => cache.get(&key_hashes[0]).await,
+        None => None,
+    };
+    let api_key_record = match cached {
+        Some(api_key_record) => api_key_record,
+        None => {
+            let api_key_record = load_api_key(&key_hashes, key_prefix, request_id).await?;
+            // Records under an outdated hash are cached once re-hashed
+            if let Some(cache) = cache.filter(|_| is_current_hash(&api_key_record.key_hash)) {
+                cache.put(&api_key_record).await;
+            }
+            api_key_record
+        }
+    };
+
+    if !verify_api_key(api_key, &api_key_record.key_hash) {
+        tracing::warn!(key_prefix = %key_prefix, "API key does not match its stored hash");
+        return Err(create_error_response(
+            StatusCode::UNAUTHORIZED,
+            "invalid_api_key",
+            "API key is invalid or does not exist",
+            request_id.clone(),
+        ));
+    }
+
+    Ok(api_key_record)
+}
+
+/// Verify a signed request and find the record of the key that signed it
+///
+/// The body is read to check its hash and put back into the returned request.
+/// The nonce is only claimed once the signature is valid, so nobody without the
+/// secret can use up a client's nonces.
+async fn authenticate_signed(
+    state: &AppState,
+    key_id: &str,
+    request: Request,
+    request_id: &Option<String>,
+) -> Result<(ApiKey, Request), Response> {
+    let unauthorized = |code: &str, message: &str| {
+        create_error_response(StatusCode::UNAUTHORIZED, code, message, request_id.clone())
+    };
+
+    let Ok(api_key_id) = key_id.trim().parse::<Uuid>() else {
+        return Err(unauthorized(
+            "invalid_authorization_header",
+            "Signature scheme must be followed by an API key id",
+        ));
+    };
+
+    let headers = request.headers();
+    let signature_header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|h| h.to_str().ok())
+            .map(str::to_string)
+    };
+    let (Some(timestamp), Some(nonce), Some(signature)) = (
+        signature_header(TIMESTAMP_HEADER),
+        signature_header(NONCE_HEADER),
+        signature_header(SIGNATURE_HEADER),
+    ) else {
+        return Err(unauthorized(
+            "missing_signature_headers",
+            "Signed requests need X-Request-Timestamp, X-Request-Nonce and X-Request-Signature",
+        ));
+    };
+
+    let fresh = timestamp
+        .parse::<i64>()
+        .is_ok_and(|ts| is_timestamp_fresh(ts, chrono::Utc::now().timestamp()));
+    if !fresh {
+        return Err(unauthorized(
+            "stale_request_timestamp",
+            &format!(
+                "X-Request-Timestamp must be Unix seconds within {} seconds of server time",
+                TIMESTAMP_TOLERANCE_SECONDS
+            ),
+        ));
+    }
+    if !is_valid_nonce(&nonce) {
+        return Err(unauthorized(
+            "invalid_nonce",
+            "X-Request-Nonce must be 16 to 64 letters, digits, '-' or '_'",
         ));
     }
 
-    // Hash the API key
-    let mut hasher = Sha256::new();
-    hasher.update(api_key.as_bytes());
-    let key_hash = format!("{:x}", hasher.finalize());
-    let key_prefix: String = api_key.chars().take(8).collect();
+    let api_key_record = find_api_key_by_id(api_key_id, request_id).await?;
 
-    // Look up API key in database
+    let (Some(secret_key_id), Some(encrypted_secret)) = (
+        api_key_record.signing_secret_key_id.as_deref(),
+        api_key_record.encrypted_signing_secret.as_deref(),
+    ) else {
+        return Err(unauthorized(
+            "signing_not_enabled",
+            "API key has no signing secret",
+        ));
+    };
+    let secret = secret_keys()
+        .decrypt(secret_key_id, encrypted_secret)
+        .map_err(|reason| {
+            tracing::error!(api_key_id = %api_key_id, reason = %reason, "Failed to decrypt signing secret");
+            create_error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "signing_secret_unavailable",
+                "Signing secret could not be read",
+                request_id.clone(),
+            )
+        })?;
+
+    let (parts, body) = request.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, MAX_SIGNED_BODY_BYTES).await else {
+        return Err(create_error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload_too_large",
+            &format!(
+                "Signed request bodies are limited to {} bytes",
+                MAX_SIGNED_BODY_BYTES
+            ),
+            request_id.clone(),
+        ));
+    };
+
+    let path_and_query = parts
+        .uri
+        .path_and_query()
+        .map(|p| p.as_str())
+        .unwrap_or("/");
+    let canonical = canonical_request(
+        parts.method.as_str(),
+        path_and_query,
+        &timestamp,
+        &nonce,
+        &body,
+    );
+    if !verify_signature(&secret, &canonical, &signature) {
+        tracing::warn!(api_key_id = %api_key_id, "Request signature does not match");
+        return Err(unauthorized(
+            "invalid_signature",
+            "Request signature is invalid",
+        ));
+    }
+
+    match claim_nonce(&state.redis, api_key_id, &nonce).await {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::warn!(api_key_id = %api_key_id, "Signed request replayed");
+            return Err(unauthorized(
+                "replayed_request",
+                "X-Request-Nonce has already been used",
+            ));
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to record request nonce");
+            return Err(create_error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "nonce_store_unavailable",
+                "Signed requests cannot be checked for replays right now",
+                request_id.clone(),
+            ));
+        }
+    }
+
+    Ok((api_key_record, Request::from_parts(parts, Body::from(body))))
+}
+
+/// Find the account a session acts on and the user's role there
+///
+/// Without `account_id` the user must belong to exactly one account.
+async fn authenticate_session(
+    token: &str,
+    account_id: Option<Uuid>,
+    client_ip: Option<IpAddr>,
+    request_id: &Option<String>,
+) -> Result<AuthenticatedApiKey, Response> {
     let tracker = POOL_STATE_TRACKER.get().ok_or_else(|| {
         create_error_response(
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -84,19 +644,83 @@ pub async fn auth_middleware(
         )
     })?;
 
-    // Connection guard to automatically return connection when scope ends
-    struct ConnectionGuard(Option<sqlx::pool::PoolConnection<sqlx::Postgres>>);
-    impl Drop for ConnectionGuard {
-        fn drop(&mut self) {
-            if let Some(c) = self.0.take() {
-                if let Some(tracker) = POOL_STATE_TRACKER.get() {
-                    tracker.return_connection(c);
-                }
-            }
+    let mut conn = tracker.get_connection().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to get database connection");
+        create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            "Failed to connect to database",
+            request_id.clone(),
+        )
+    })?;
+
+    let principals =
+        get_session_principals(&hash_session_token(token), account_id, &mut conn).await;
+    tracker.return_connection(conn);
+
+    let principals = principals.map_err(|_| {
+        create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            "Failed to load session",
+            request_id.clone(),
+        )
+    })?;
+
+    let principal = match principals.as_slice() {
+        [principal] => principal,
+        [] => {
+            return Err(create_error_response(
+                StatusCode::UNAUTHORIZED,
+                "invalid_session",
+                "Session is invalid or expired, or the user is not a member of an active account",
+                request_id.clone(),
+            ));
         }
-    }
+        _ => {
+            return Err(create_error_response(
+                StatusCode::BAD_REQUEST,
+                "account_required",
+                "User belongs to several accounts; choose one with X-Account-Id",
+                request_id.clone(),
+            ));
+        }
+    };
 
-    let conn = tracker.get_connection().await.map_err(|e| {
+    // The schema only allows known roles; anything else gets the least access
+    let role = Role::parse(&principal.role).unwrap_or(Role::Viewer);
+
+    Ok(AuthenticatedApiKey {
+        api_key_id: principal.session_id,
+        account_id: principal.account_id,
+        key_prefix: SESSION_PREFIX.to_string(),
+        permissions: role.permissions(),
+        livemode: principal.livemode,
+        signed: false,
+        require_signed_writes: principal.require_signed_writes,
+        allowed_ips: Vec::new(),
+        user_id: Some(principal.user_id),
+        role: Some(role),
+        client_ip,
+    })
+}
+
+/// Read the API key stored under one of these hashes from the database
+async fn load_api_key(
+    key_hashes: &[String],
+    key_prefix: &str,
+    request_id: &Option<String>,
+) -> Result<ApiKey, Response> {
+    let tracker = POOL_STATE_TRACKER.get().ok_or_else(|| {
+        create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_unavailable",
+            "Database connection pool not initialized",
+            request_id.clone(),
+        )
+    })?;
+
+    let mut conn = tracker.get_connection().await.map_err(|e| {
         tracing::error!(error = %e, "Failed to get database connection");
         create_error_response(
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -106,28 +730,10 @@ pub async fn auth_middleware(
         )
     })?;
 
-    let mut guard = ConnectionGuard(Some(conn));
-    let db_conn = guard.0.as_mut().unwrap();
-
-    let api_key_record = ApiKeyBuilder::new()
-        .key_hash(key_hash)
-        .expect_id()
-        .expect_account_id()
-        .expect_key_hash()
-        .expect_key_prefix()
-        .expect_name()
-        .expect_status()
-        .expect_permissions()
-        .expect_last_used_at()
-        .expect_expires_at()
-        .expect_created_at()
-        .expect_revoked_at()
-        .read(Some(&mut *db_conn))
-        .await;
-
-    // Connection automatically returned when guard goes out of scope
-
-    let api_key_record = api_key_record.map_err(|e| {
+    let api_key_record = get_api_key_by_hashes(key_hashes, &mut conn).await;
+    tracker.return_connection(conn);
+
+    api_key_record.map_err(|e| {
         tracing::warn!(
             key_prefix = %key_prefix,
             error = ?e,
@@ -139,57 +745,86 @@ pub async fn auth_middleware(
             "API key is invalid or does not exist",
             request_id.clone(),
         )
-    })?;
+    })
+}
 
-    // Check if API key is active
-    if api_key_record.status != "active" {
-        return Err(create_error_response(
-            StatusCode::UNAUTHORIZED,
-            "inactive_api_key",
-            &format!("API key is not active (status: {})", api_key_record.status),
-            request_id,
-        ));
+/// Find the record of the key with this id, in the cache, then in the database
+async fn find_api_key_by_id(
+    api_key_id: Uuid,
+    request_id: &Option<String>,
+) -> Result<ApiKey, Response> {
+    let cache = API_KEY_CACHE.get();
+    let cached = match cache {
+        Some(cache) => cache.get_by_id(api_key_id).await,
+        None => None,
+    };
+    match cached {
+        Some(api_key_record) => Ok(api_key_record),
+        None => {
+            let api_key_record = load_api_key_by_id(api_key_id, request_id).await?;
+            if let Some(cache) = cache {
+                cache.put(&api_key_record).await;
+            }
+            Ok(api_key_record)
+        }
     }
+}
 
-    // Check if API key is revoked
-    if api_key_record.revoked_at.is_some() {
-        return Err(create_error_response(
-            StatusCode::UNAUTHORIZED,
-            "revoked_api_key",
-            "API key has been revoked",
-            request_id,
-        ));
-    }
+/// Read the API key with this id from the database
+async fn load_api_key_by_id(
+    api_key_id: Uuid,
+    request_id: &Option<String>,
+) -> Result<ApiKey, Response> {
+    let tracker = POOL_STATE_TRACKER.get().ok_or_else(|| {
+        create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_unavailable",
+            "Database connection pool not initialized",
+            request_id.clone(),
+        )
+    })?;
 
-    // Check if API key is expired
-    if let Some(expires_at) = api_key_record.expires_at {
-        if expires_at < chrono::Utc::now() {
-            return Err(create_error_response(
-                StatusCode::UNAUTHORIZED,
-                "expired_api_key",
-                "API key has expired",
-                request_id,
-            ));
-        }
-    }
+    let mut conn = tracker.get_connection().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to get database connection");
+        create_error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "database_error",
+            "Failed to connect to database",
+            request_id.clone(),
+        )
+    })?;
 
-    // Store authenticated API key info in request extensions
-    let auth_info = AuthenticatedApiKey {
-        api_key_id: api_key_record.id,
-        account_id: api_key_record.account_id,
-        key_prefix: api_key_record.key_prefix.clone(),
-    };
+    let api_key_record = get_api_key_for_auth(api_key_id, &mut conn).await;
+    tracker.return_connection(conn);
 
-    request.extensions_mut().insert(auth_info);
+    api_key_record.map_err(|e| {
+        tracing::warn!(api_key_id = %api_key_id, error = ?e, "API key not found or invalid");
+        create_error_response(
+            StatusCode::UNAUTHORIZED,
+            "invalid_api_key",
+            "API key is invalid or does not exist",
+            request_id.clone(),
+        )
+    })
+}
 
-    tracing::info!(
-        api_key_id = %api_key_record.id,
-        account_id = %api_key_record.account_id,
-        key_prefix = %key_prefix,
-        "API key authenticated successfully"
-    );
+/// Store the hash made with the active pepper in place of an outdated one
+async fn rehash(api_key_id: Uuid, old_hash: String, new_hash: String) {
+    let Some(tracker) = POOL_STATE_TRACKER.get() else {
+        return;
+    };
+    let Ok(mut conn) = tracker.get_connection().await else {
+        return;
+    };
+    let result = rehash_api_key(api_key_id, &old_hash, &new_hash, &mut conn).await;
+    tracker.return_connection(conn);
 
-    Ok(next.run(request).await)
+    if let Ok(Some(api_key)) = result {
+        tracing::info!(api_key_id = %api_key_id, "Re-hashed API key with the active pepper");
+        if let Some(cache) = API_KEY_CACHE.get() {
+            cache.put(&api_key).await;
+        }
+    }
 }
 
 /// Helper to extract authenticated API key from request
@@ -0,0 +1,198 @@
+use axum::{extract::ConnectInfo, extract::Request, http::HeaderMap};
+use ipnet::IpNet;
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::OnceLock,
+};
+
+/// Most entries an API key allowlist may hold
+pub const MAX_ALLOWED_IPS: usize = 100;
+
+/// Proxies in front of the server, loaded from the environment on first use
+static TRUSTED_PROXIES: OnceLock<TrustedProxies> = OnceLock::new();
+
+/// Networks of reverse proxies whose `X-Forwarded-For` entries are believed
+///
+/// Configured with `TRUSTED_PROXIES` as comma-separated CIDRs or addresses.
+/// Without it the peer address is always the client address.
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+    /// Parse a proxy list; malformed entries are skipped with a warning
+    pub fn parse(spec: &str) -> Self {
+        let networks = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let parsed = parse_network(entry);
+                if parsed.is_none() {
+                    tracing::warn!(entry = entry, "Ignoring malformed entry in TRUSTED_PROXIES");
+                }
+                parsed
+            })
+            .collect();
+        Self { networks }
+    }
+
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("TRUSTED_PROXIES").unwrap_or_default())
+    }
+
+    /// Proxies of this process, loaded on first use
+    pub fn global() -> &'static TrustedProxies {
+        TRUSTED_PROXIES.get_or_init(Self::from_env)
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        self.networks.iter().any(|network| network.contains(&ip))
+    }
+
+    /// Address of the client behind `peer`
+    ///
+    /// `X-Forwarded-For` is walked from the right, i.e. from the hop closest to
+    /// the server, for as long as each hop is a trusted proxy; the first hop that
+    /// is not, or the last one that can be read, is the client. Entries added
+    /// left of an untrusted hop may be forged and are never looked at.
+    pub fn resolve(&self, peer: IpAddr, forwarded_for: &[&str]) -> IpAddr {
+        let mut client = peer.to_canonical();
+        let hops = forwarded_for
+            .iter()
+            .flat_map(|header| header.split(','))
+            .rev();
+        for hop in hops {
+            if !self.contains(client) {
+                break;
+            }
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => client = ip.to_canonical(),
+                Err(_) => break,
+            }
+        }
+        client
+    }
+}
+
+/// Address of the client that sent the request, if the peer address is known
+pub fn client_ip(request: &Request) -> Option<IpAddr> {
+    let ConnectInfo(peer) = request.extensions().get::<ConnectInfo<SocketAddr>>()?;
+    Some(client_ip_from(*peer, request.headers()))
+}
+
+/// Address of the client behind the connection from `peer`, for handlers without the request
+pub fn client_ip_from(peer: SocketAddr, headers: &HeaderMap) -> IpAddr {
+    let forwarded_for: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    TrustedProxies::global().resolve(peer.ip(), &forwarded_for)
+}
+
+/// Parse a CIDR, or a single address as a network of one
+fn parse_network(entry: &str) -> Option<IpNet> {
+    entry
+        .parse::<IpNet>()
+        .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+        .ok()
+        .map(|network| network.trunc())
+}
+
+/// Validate and normalise an allowlist given through the API
+///
+/// # Returns
+/// The CIDRs in canonical form without duplicates, or the first invalid entry
+pub fn normalize_allowed_ips(entries: &[String]) -> Result<Vec<String>, String> {
+    let mut networks: Vec<String> = Vec::new();
+    for entry in entries {
+        let network = parse_network(entry.trim())
+            .ok_or_else(|| format!("'{}' is not an IP address or CIDR", entry))?
+            .to_string();
+        if !networks.contains(&network) {
+            networks.push(network);
+        }
+    }
+    Ok(networks)
+}
+
+/// Allowlist stored on a key as strings; anything but a JSON string array allows any address
+pub fn allowed_ips_from_json(allowed_ips: Option<&serde_json::Value>) -> Vec<String> {
+    allowed_ips
+        .and_then(|value| value.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a key with this allowlist may be used from `ip`
+///
+/// An empty allowlist allows every address; a non-empty one allows nothing when
+/// the client address is unknown.
+pub fn is_ip_allowed(allowed_ips: &[String], ip: Option<IpAddr>) -> bool {
+    if allowed_ips.is_empty() {
+        return true;
+    }
+    let Some(ip) = ip else {
+        return false;
+    };
+    allowed_ips
+        .iter()
+        .filter_map(|entry| parse_network(entry))
+        .any(|network| network.contains(&ip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_resolve_only_believes_trusted_proxies() {
+        let proxies = TrustedProxies::parse("10.0.0.0/8, 192.168.1.1");
+
+        // Untrusted peers cannot claim another address
+        assert_eq!(
+            proxies.resolve(ip("203.0.113.9"), &["198.51.100.1"]),
+            ip("203.0.113.9")
+        );
+        // The rightmost untrusted hop is the client; anything left of it is ignored
+        assert_eq!(
+            proxies.resolve(ip("10.1.2.3"), &["1.1.1.1, 198.51.100.7", "192.168.1.1"]),
+            ip("198.51.100.7")
+        );
+        // Unreadable hops stop the walk at the last trusted proxy
+        assert_eq!(
+            proxies.resolve(ip("10.1.2.3"), &["garbage"]),
+            ip("10.1.2.3")
+        );
+        assert_eq!(
+            proxies.resolve(ip("::ffff:203.0.113.9"), &[]),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn test_allowlist_matches_cidrs() {
+        let allowed =
+            normalize_allowed_ips(&["203.0.113.7/24".to_string(), "2001:db8::1".to_string()])
+                .unwrap();
+        assert_eq!(allowed, vec!["203.0.113.0/24", "2001:db8::1/128"]);
+
+        assert!(is_ip_allowed(&allowed, Some(ip("203.0.113.200"))));
+        assert!(is_ip_allowed(&allowed, Some(ip("2001:db8::1"))));
+        assert!(!is_ip_allowed(&allowed, Some(ip("198.51.100.1"))));
+        assert!(!is_ip_allowed(&allowed, None));
+        assert!(is_ip_allowed(&[], None));
+
+        assert!(normalize_allowed_ips(&["300.0.0.1".to_string()]).is_err());
+    }
+}
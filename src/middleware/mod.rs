@@ -1,5 +1,10 @@
+pub mod admin_auth;
 pub mod auth;
 pub mod error;
+pub mod ip_allowlist;
 pub mod ip_rate_limit;
 pub mod rate_limit;
 pub mod request_id;
+pub mod request_signing;
+pub mod roles;
+pub mod scopes;
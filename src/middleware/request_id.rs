@@ -4,8 +4,11 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use tracing::Instrument;
 use uuid::Uuid;
 
+use crate::logging::propagation::REQUEST_ID;
+
 /// Middleware to add a unique request ID to each request
 /// The request ID is added to both the request extensions and response headers
 pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
@@ -23,8 +26,12 @@ pub async fn request_id_middleware(mut request: Request, next: Next) -> Response
         "Incoming request"
     );
 
-    // Process the request
-    let mut response = next.run(request).await;
+    // Process the request inside a span carrying the request ID; background work
+    // started by the request (e.g. webhook deliveries) picks both up
+    let span = tracing::info_span!("request", request_id = %request_id);
+    let mut response = REQUEST_ID
+        .scope(request_id, next.run(request).instrument(span))
+        .await;
 
     // Add request ID to response headers
     response.headers_mut().insert(
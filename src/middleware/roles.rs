@@ -0,0 +1,89 @@
+use crate::middleware::scopes::Scope;
+use serde::{Deserialize, Serialize};
+
+/// Role of a user on an account
+///
+/// Roles are translated into scopes when a session authenticates, so the
+/// `requires(Scope)` layers on each route check users the same way they check
+/// API keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Everything, including granting and removing owners
+    Owner,
+    /// Everything except changing owners
+    Admin,
+    /// Integration work: transfers, webhooks and API keys, but not account
+    /// settings, balances, members or the audit log
+    Developer,
+    /// Read-only access, except to the audit log
+    Viewer,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Admin => "admin",
+            Role::Developer => "developer",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    pub fn parse(role: &str) -> Option<Role> {
+        match role {
+            "owner" => Some(Role::Owner),
+            "admin" => Some(Role::Admin),
+            "developer" => Some(Role::Developer),
+            "viewer" => Some(Role::Viewer),
+            _ => None,
+        }
+    }
+
+    /// Whether the role holds `scope`
+    pub fn grants(self, scope: Scope) -> bool {
+        match self {
+            Role::Owner | Role::Admin => true,
+            Role::Developer => !matches!(
+                scope,
+                Scope::AccountsWrite | Scope::MembersManage | Scope::AuditLogRead
+            ),
+            Role::Viewer => scope.is_read() && scope != Scope::AuditLogRead,
+        }
+    }
+
+    /// Scopes held by a session with this role, as permission strings
+    pub fn permissions(self) -> Vec<String> {
+        Scope::ALL
+            .iter()
+            .filter(|scope| self.grants(**scope))
+            .map(|scope| scope.as_str().to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roles_map_to_scopes() {
+        assert!(Role::Viewer.grants(Scope::TransfersRead));
+        assert!(!Role::Viewer.grants(Scope::AccountsWrite));
+        assert!(!Role::Viewer.grants(Scope::TransfersWrite));
+        assert!(Role::Developer.grants(Scope::TransfersWrite));
+        assert!(Role::Developer.grants(Scope::ApiKeysManage));
+        assert!(!Role::Developer.grants(Scope::AccountsWrite));
+        assert!(!Role::Developer.grants(Scope::MembersManage));
+        assert!(Role::Admin.grants(Scope::MembersManage));
+        assert!(Role::Admin.grants(Scope::AuditLogRead));
+        assert!(!Role::Developer.grants(Scope::AuditLogRead));
+        assert!(!Role::Viewer.grants(Scope::AuditLogRead));
+        assert_eq!(Role::Owner.permissions().len(), Scope::ALL.len());
+
+        for role in [Role::Owner, Role::Admin, Role::Developer, Role::Viewer] {
+            assert_eq!(Role::parse(role.as_str()), Some(role));
+        }
+        assert_eq!(Role::parse("superuser"), None);
+    }
+}
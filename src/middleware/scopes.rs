@@ -0,0 +1,224 @@
+use crate::{errors::errors::create_error_response, middleware::auth::AuthenticatedApiKey};
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::{self, FromFnLayer, Next},
+    response::Response,
+};
+use uuid::Uuid;
+
+/// Permission scope an API key or user session needs for a route
+///
+/// Keys created before scopes existed hold the coarse permissions `read` and `write`:
+/// `read` grants every read scope, `write` every other scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    AccountsRead,
+    AccountsWrite,
+    TransfersRead,
+    TransfersWrite,
+    WebhooksRead,
+    WebhooksManage,
+    EventsRead,
+    ApiKeysRead,
+    ApiKeysManage,
+    MembersRead,
+    MembersManage,
+    AuditLogRead,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 12] = [
+        Scope::AccountsRead,
+        Scope::AccountsWrite,
+        Scope::TransfersRead,
+        Scope::TransfersWrite,
+        Scope::WebhooksRead,
+        Scope::WebhooksManage,
+        Scope::EventsRead,
+        Scope::ApiKeysRead,
+        Scope::ApiKeysManage,
+        Scope::MembersRead,
+        Scope::MembersManage,
+        Scope::AuditLogRead,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::AccountsRead => "accounts:read",
+            Scope::AccountsWrite => "accounts:write",
+            Scope::TransfersRead => "transfers:read",
+            Scope::TransfersWrite => "transfers:write",
+            Scope::WebhooksRead => "webhooks:read",
+            Scope::WebhooksManage => "webhooks:manage",
+            Scope::EventsRead => "events:read",
+            Scope::ApiKeysRead => "api_keys:read",
+            Scope::ApiKeysManage => "api_keys:manage",
+            Scope::MembersRead => "members:read",
+            Scope::MembersManage => "members:manage",
+            Scope::AuditLogRead => "audit_log:read",
+        }
+    }
+
+    pub fn parse(scope: &str) -> Option<Scope> {
+        Scope::ALL.into_iter().find(|s| s.as_str() == scope)
+    }
+
+    pub fn is_read(self) -> bool {
+        self.as_str().ends_with(":read")
+    }
+
+    /// Whether holding `permission` grants this scope
+    pub fn granted_by(self, permission: &str) -> bool {
+        match permission {
+            "read" => self.is_read(),
+            "write" => !self.is_read(),
+            _ => permission == self.as_str(),
+        }
+    }
+}
+
+/// Whether `permission` is a value an API key may hold
+pub fn is_known_permission(permission: &str) -> bool {
+    permission == "read" || permission == "write" || Scope::parse(permission).is_some()
+}
+
+/// Permissions stored on a key as strings; anything but a JSON string array grants nothing
+pub fn permissions_from_json(permissions: Option<&serde_json::Value>) -> Vec<String> {
+    permissions
+        .and_then(|value| value.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a key holding `held` is allowed everything a key holding `requested` is
+pub fn covers(held: &[String], requested: &[String]) -> bool {
+    Scope::ALL.iter().all(|scope| {
+        !requested.iter().any(|p| scope.granted_by(p)) || held.iter().any(|p| scope.granted_by(p))
+    })
+}
+
+/// Layer rejecting requests whose API key lacks `scope`
+///
+/// Attach with `route_layer` on a method router, so the check runs after
+/// `auth_middleware` has identified the key.
+pub fn requires(scope: Scope) -> ScopeLayer {
+    middleware::from_fn_with_state(scope, require_scope as fn(_, _, _) -> _)
+}
+
+type ScopeCheck =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<Response, Response>> + Send>>;
+
+type ScopeLayer =
+    FromFnLayer<fn(State<Scope>, Request, Next) -> ScopeCheck, Scope, (State<Scope>, Request)>;
+
+/// Whether the account's `require_signed_writes` refuses this request
+///
+/// The setting guards against leaked bearer keys, so it applies to API keys
+/// and the access tokens exchanged from them, which then only reach `:read`
+/// scopes. Dashboard sessions log in with a password and cannot sign, so
+/// they are exempt.
+fn needs_signature(auth: &AuthenticatedApiKey, scope: Scope) -> bool {
+    auth.require_signed_writes && !auth.signed && auth.user_id.is_none() && !scope.is_read()
+}
+
+fn require_scope(State(scope): State<Scope>, request: Request, next: Next) -> ScopeCheck {
+    Box::pin(async move {
+        let auth = request.extensions().get::<AuthenticatedApiKey>();
+        let granted = auth.is_some_and(|auth| auth.has_scope(scope));
+
+        if !granted {
+            let request_id = request.extensions().get::<Uuid>().map(|id| id.to_string());
+            tracing::warn!(scope = scope.as_str(), "API key lacks required scope");
+            return Err(create_error_response(
+                StatusCode::FORBIDDEN,
+                "insufficient_permissions",
+                &format!("API key lacks the '{}' scope", scope.as_str()),
+                request_id,
+            ));
+        }
+
+        if auth.is_some_and(|auth| needs_signature(auth, scope)) {
+            let request_id = request.extensions().get::<Uuid>().map(|id| id.to_string());
+            tracing::warn!(scope = scope.as_str(), "Unsigned request for a write scope");
+            return Err(create_error_response(
+                StatusCode::FORBIDDEN,
+                "signature_required",
+                &format!(
+                    "The account requires signed requests for the '{}' scope",
+                    scope.as_str()
+                ),
+                request_id,
+            ));
+        }
+
+        Ok(next.run(request).await)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn legacy_permissions_split_read_and_write() {
+        assert!(Scope::TransfersRead.granted_by("read"));
+        assert!(!Scope::TransfersWrite.granted_by("read"));
+        assert!(Scope::WebhooksManage.granted_by("write"));
+        assert!(!Scope::EventsRead.granted_by("write"));
+        assert!(Scope::EventsRead.granted_by("events:read"));
+        assert!(!Scope::EventsRead.granted_by("transfers:read"));
+    }
+
+    #[test]
+    fn covers_rejects_broader_requests() {
+        let held = strings(&["read", "transfers:write"]);
+        assert!(covers(
+            &held,
+            &strings(&["accounts:read", "transfers:write"])
+        ));
+        assert!(covers(&strings(&["read", "write"]), &held));
+        assert!(!covers(&held, &strings(&["write"])));
+        assert!(!covers(&held, &strings(&["api_keys:manage"])));
+    }
+
+    #[test]
+    fn signed_writes_apply_to_keys_not_sessions() {
+        let key = AuthenticatedApiKey {
+            api_key_id: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            key_prefix: "sk_test_abc".to_string(),
+            permissions: strings(&["read", "write"]),
+            livemode: false,
+            signed: false,
+            require_signed_writes: true,
+            allowed_ips: Vec::new(),
+            user_id: None,
+            role: None,
+            client_ip: None,
+        };
+        assert!(needs_signature(&key, Scope::TransfersWrite));
+        assert!(!needs_signature(&key, Scope::TransfersRead));
+
+        let signed = AuthenticatedApiKey {
+            signed: true,
+            ..key.clone()
+        };
+        assert!(!needs_signature(&signed, Scope::TransfersWrite));
+
+        let session = AuthenticatedApiKey {
+            user_id: Some(Uuid::new_v4()),
+            ..key
+        };
+        assert!(!needs_signature(&session, Scope::TransfersWrite));
+    }
+}
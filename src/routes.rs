@@ -1,14 +1,20 @@
 use axum::{
     Router, middleware,
-    routing::{get, patch, post},
+    routing::{delete, get, patch, post, put},
 };
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 
 use crate::{
-    handlers::{accounts, health, transfer, webhooks},
+    handlers::{
+        accounts, admin, api_keys, audit_log, events, health, oauth, transfer, users, webhooks,
+    },
     middleware::{
-        auth::auth_middleware, ip_rate_limit::ip_rate_limit_middleware,
-        rate_limit::rate_limit_middleware, request_id::request_id_middleware,
+        admin_auth::{AdminRole, admin_auth_middleware, requires_admin},
+        auth::auth_middleware,
+        ip_rate_limit::ip_rate_limit_middleware,
+        rate_limit::rate_limit_middleware,
+        request_id::request_id_middleware,
+        scopes::{Scope, requires},
     },
     state::AppState,
 };
@@ -19,39 +25,200 @@ pub fn create_router(state: AppState) -> Router {
     let public_routes = Router::new()
         .route("/health", get(health::health_check))
         .route("/api/v1/accounts", post(accounts::create_account))
+        .route("/api/v1/users", post(users::register_user))
+        .route("/api/v1/auth/login", post(users::login))
+        .route("/api/v1/auth/logout", post(users::logout))
+        .route("/api/v1/oauth/token", post(oauth::issue_token))
+        .route("/.well-known/jwks.json", get(oauth::access_token_jwks))
+        .route(
+            "/.well-known/webhook-keys.json",
+            get(webhooks::webhook_signing_keys),
+        )
         .layer(middleware::from_fn_with_state(
             state.clone(),
             ip_rate_limit_middleware,
         ));
 
     // Protected routes (authentication required, API-key-based rate limiting)
+    // Each endpoint declares the scope its API key needs; see `middleware::scopes`.
+    // User sessions hold the scopes of their role; see `middleware::roles`
     let protected_routes_accounts = Router::new()
-        .route("/api/v1/accounts/putbalance", post(accounts::put_balance))
-        .route("/api/v1/accounts/:id", get(accounts::get_account))
-        .route("/api/v1/accounts/:id", patch(accounts::update_account))
-        .route("/api/v1/accounts/:id/balance", get(accounts::get_balance));
+        .route(
+            "/api/v1/accounts/putbalance",
+            post(accounts::put_balance).route_layer(requires(Scope::AccountsWrite)),
+        )
+        .route(
+            "/api/v1/accounts/:id",
+            get(accounts::get_account).route_layer(requires(Scope::AccountsRead)),
+        )
+        .route(
+            "/api/v1/accounts/:id",
+            patch(accounts::update_account).route_layer(requires(Scope::AccountsWrite)),
+        )
+        .route(
+            "/api/v1/accounts/:id/balance",
+            get(accounts::get_balance).route_layer(requires(Scope::AccountsRead)),
+        );
 
     let protected_routes_webhooks = Router::new()
-        .route("/api/v1/webhooks/set", post(webhooks::create_webhook))
-        .route("/api/v1/webhooks/unset", post(webhooks::delete_webhook))
-        .route("/api/v1/webhooks/info", get(webhooks::get_webhooks));
+        .route(
+            "/api/v1/webhooks/set",
+            post(webhooks::create_webhook).route_layer(requires(Scope::WebhooksManage)),
+        )
+        .route(
+            "/api/v1/webhooks/unset",
+            post(webhooks::delete_webhook).route_layer(requires(Scope::WebhooksManage)),
+        )
+        .route(
+            "/api/v1/webhooks/info",
+            get(webhooks::get_webhooks).route_layer(requires(Scope::WebhooksRead)),
+        )
+        .route(
+            "/api/v1/webhooks/:id",
+            get(webhooks::get_webhook).route_layer(requires(Scope::WebhooksRead)),
+        )
+        .route(
+            "/api/v1/webhooks/:id",
+            patch(webhooks::update_webhook).route_layer(requires(Scope::WebhooksManage)),
+        )
+        .route(
+            "/api/v1/webhooks/:id/deliveries",
+            get(webhooks::list_webhook_deliveries).route_layer(requires(Scope::WebhooksRead)),
+        )
+        .route(
+            "/api/v1/webhooks/dead-letters",
+            get(webhooks::list_dead_letters).route_layer(requires(Scope::WebhooksRead)),
+        )
+        .route(
+            "/api/v1/webhooks/dead-letters/stats",
+            get(webhooks::dead_letter_stats).route_layer(requires(Scope::WebhooksRead)),
+        )
+        .route(
+            "/api/v1/webhooks/dead-letters/redrive",
+            post(webhooks::redrive_dead_letters).route_layer(requires(Scope::WebhooksManage)),
+        )
+        .route(
+            "/api/v1/webhooks/dead-letters/discard",
+            post(webhooks::discard_dead_letters).route_layer(requires(Scope::WebhooksManage)),
+        )
+        .route(
+            "/api/v1/webhooks/:id/test",
+            post(webhooks::test_webhook).route_layer(requires(Scope::WebhooksManage)),
+        )
+        .route(
+            "/api/v1/webhooks/:id/verify",
+            post(webhooks::verify_webhook).route_layer(requires(Scope::WebhooksManage)),
+        )
+        .route(
+            "/api/v1/webhooks/:id/backfill",
+            post(webhooks::create_webhook_backfill).route_layer(requires(Scope::WebhooksManage)),
+        )
+        .route(
+            "/api/v1/webhooks/:id/backfills/:backfill_id",
+            get(webhooks::get_webhook_backfill).route_layer(requires(Scope::WebhooksRead)),
+        )
+        .route(
+            "/api/v1/webhooks/deliveries/:id/redeliver",
+            post(webhooks::redeliver_webhook_delivery).route_layer(requires(Scope::WebhooksManage)),
+        );
 
     let protected_routes_transfer = Router::new()
-        .route("/api/v1/transfer", post(transfer::transfer))
-        .route("/api/v1/transfer/list", get(transfer::list_transfers))
+        .route(
+            "/api/v1/transfer",
+            post(transfer::transfer).route_layer(requires(Scope::TransfersWrite)),
+        )
+        .route(
+            "/api/v1/transfer/list",
+            get(transfer::list_transfers).route_layer(requires(Scope::TransfersRead)),
+        )
         .route(
             "/api/v1/transfer/info/:id",
-            get(transfer::get_transfer_byid),
+            get(transfer::get_transfer_byid).route_layer(requires(Scope::TransfersRead)),
         )
         .route(
             "/api/v1/transfer/:id",
-            get(transfer::get_transfer_byparentkey),
+            get(transfer::get_transfer_byparentkey).route_layer(requires(Scope::TransfersRead)),
+        );
+
+    let protected_routes_events = Router::new()
+        .route(
+            "/api/v1/events",
+            get(events::list_events).route_layer(requires(Scope::EventsRead)),
+        )
+        .route(
+            "/api/v1/events/stream",
+            get(events::stream_events).route_layer(requires(Scope::EventsRead)),
+        )
+        .route(
+            "/api/v1/events/ws",
+            get(events::stream_events_ws).route_layer(requires(Scope::EventsRead)),
+        )
+        .route(
+            "/api/v1/events/:id",
+            get(events::get_event).route_layer(requires(Scope::EventsRead)),
+        );
+
+    let protected_routes_api_keys = Router::new()
+        .route(
+            "/api/v1/api-keys",
+            get(api_keys::list_api_keys).route_layer(requires(Scope::ApiKeysRead)),
+        )
+        .route(
+            "/api/v1/api-keys",
+            post(api_keys::create_api_key).route_layer(requires(Scope::ApiKeysManage)),
+        )
+        .route(
+            "/api/v1/api-keys/:id/usage",
+            get(api_keys::get_api_key_usage).route_layer(requires(Scope::ApiKeysRead)),
+        )
+        .route(
+            "/api/v1/api-keys/:id/revoke",
+            post(api_keys::revoke_api_key).route_layer(requires(Scope::ApiKeysManage)),
+        )
+        .route(
+            "/api/v1/api-keys/:id/rotate",
+            post(api_keys::rotate_api_key).route_layer(requires(Scope::ApiKeysManage)),
+        )
+        .route(
+            "/api/v1/api-keys/:id/allowed-ips",
+            put(api_keys::set_allowed_ips).route_layer(requires(Scope::ApiKeysManage)),
+        )
+        .route(
+            "/api/v1/api-keys/:id/signing-secret",
+            post(api_keys::create_signing_secret).route_layer(requires(Scope::ApiKeysManage)),
+        );
+
+    let protected_routes_members = Router::new()
+        .route(
+            "/api/v1/members",
+            get(users::list_members).route_layer(requires(Scope::MembersRead)),
+        )
+        .route(
+            "/api/v1/members",
+            post(users::create_member).route_layer(requires(Scope::MembersManage)),
+        )
+        .route(
+            "/api/v1/members/:user_id",
+            patch(users::update_member).route_layer(requires(Scope::MembersManage)),
+        )
+        .route(
+            "/api/v1/members/:user_id",
+            delete(users::delete_member).route_layer(requires(Scope::MembersManage)),
         );
 
+    let protected_routes_audit_log = Router::new().route(
+        "/api/v1/audit-log",
+        get(audit_log::list_audit_log).route_layer(requires(Scope::AuditLogRead)),
+    );
+
     let protected_routes = Router::new()
         .merge(protected_routes_accounts)
         .merge(protected_routes_webhooks)
         .merge(protected_routes_transfer)
+        .merge(protected_routes_events)
+        .merge(protected_routes_api_keys)
+        .merge(protected_routes_members)
+        .merge(protected_routes_audit_log)
         .layer(middleware::from_fn_with_state(
             state.clone(),
             rate_limit_middleware,
@@ -61,10 +228,60 @@ pub fn create_router(state: AppState) -> Router {
             auth_middleware,
         ));
 
+    // Operator routes (admin credentials required, separate from account API keys, IP-based rate limiting)
+    // Each endpoint declares the admin role it needs; see `middleware::admin_auth`
+    let admin_routes = Router::new()
+        .route(
+            "/admin/v1/accounts",
+            get(admin::search_all_accounts).route_layer(requires_admin(AdminRole::Viewer)),
+        )
+        .route(
+            "/admin/v1/accounts/:id",
+            get(admin::get_any_account).route_layer(requires_admin(AdminRole::Viewer)),
+        )
+        .route(
+            "/admin/v1/accounts/:id/suspend",
+            post(admin::suspend_account).route_layer(requires_admin(AdminRole::Operator)),
+        )
+        .route(
+            "/admin/v1/accounts/:id/reactivate",
+            post(admin::reactivate_account).route_layer(requires_admin(AdminRole::Operator)),
+        )
+        .route(
+            "/admin/v1/accounts/:id/close",
+            post(admin::close_account).route_layer(requires_admin(AdminRole::Operator)),
+        )
+        .route(
+            "/admin/v1/transactions/:id",
+            get(admin::get_any_transaction).route_layer(requires_admin(AdminRole::Viewer)),
+        )
+        .route(
+            "/admin/v1/webhooks/circuit-breakers",
+            get(admin::list_circuit_breakers).route_layer(requires_admin(AdminRole::Viewer)),
+        )
+        .route(
+            "/admin/v1/webhooks/deliveries/:id/retry",
+            post(admin::retry_webhook_delivery).route_layer(requires_admin(AdminRole::Operator)),
+        )
+        .route(
+            "/admin/v1/rate-limits/reset",
+            post(admin::reset_rate_limit).route_layer(requires_admin(AdminRole::Operator)),
+        )
+        .route(
+            "/admin/v1/audit-log",
+            get(admin::list_all_audit_log).route_layer(requires_admin(AdminRole::Viewer)),
+        )
+        .layer(middleware::from_fn(admin_auth_middleware))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            ip_rate_limit_middleware,
+        ));
+
     // Combine all routes with shared middleware
     Router::new()
         .merge(public_routes)
         .merge(protected_routes)
+        .merge(admin_routes)
         .layer(middleware::from_fn(request_id_middleware))
         .layer(CorsLayer::permissive())
         .layer(TraceLayer::new_for_http())
@@ -0,0 +1,199 @@
+use crate::services::webhook_signing::SigningKeys;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+    errors::ErrorKind,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// `iss` of every access token
+pub const ISSUER: &str = "dodo-payments";
+
+/// `aud` of every access token
+pub const AUDIENCE: &str = "dodo-payments-api";
+
+/// How long an access token works after it is issued
+pub const ACCESS_TOKEN_TTL_SECONDS: i64 = 900;
+
+/// Access token signing keys, loaded from the environment on first use
+static ACCESS_TOKEN_KEYS: OnceLock<SigningKeys> = OnceLock::new();
+
+/// Claims of an access token
+///
+/// Everything needed to authorise a request travels in the token, so it can be
+/// verified with the published public keys alone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessTokenClaims {
+    pub iss: String,
+    pub aud: String,
+    /// The API key the token was exchanged from
+    pub sub: Uuid,
+    pub account_id: Uuid,
+    /// Space-separated scopes
+    pub scope: String,
+    pub livemode: bool,
+    /// The key's IP allowlist; empty allows any address
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_ips: Vec<String>,
+    /// The account only accepts signed requests for write scopes, which tokens are not
+    #[serde(default)]
+    pub require_signed_writes: bool,
+    pub iat: i64,
+    pub exp: i64,
+    pub jti: Uuid,
+}
+
+impl AccessTokenClaims {
+    pub fn scopes(&self) -> Vec<String> {
+        self.scope.split_whitespace().map(str::to_string).collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AccessTokenError {
+    Expired,
+    Invalid(String),
+}
+
+/// Keys signing access tokens
+///
+/// Configured like webhook signing keys, with `ACCESS_TOKEN_SIGNING_KEYS` and
+/// `ACCESS_TOKEN_SIGNING_KEY_ID`, and published at `/.well-known/jwks.json`.
+pub fn access_token_keys() -> &'static SigningKeys {
+    ACCESS_TOKEN_KEYS.get_or_init(|| {
+        SigningKeys::from_vars("ACCESS_TOKEN_SIGNING_KEYS", "ACCESS_TOKEN_SIGNING_KEY_ID")
+    })
+}
+
+/// Whether a bearer credential has the shape of a JWT rather than an API key
+pub fn looks_like_jwt(token: &str) -> bool {
+    token.matches('.').count() == 2
+}
+
+/// Sign claims with the active key as an EdDSA JWT
+pub fn issue_access_token(
+    keys: &SigningKeys,
+    claims: &AccessTokenClaims,
+) -> Result<String, String> {
+    let key = keys
+        .active()
+        .ok_or_else(|| "no access token signing key is configured".to_string())?;
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some(key.kid.clone());
+    encode(&header, claims, &EncodingKey::from_ed_der(&key.pkcs8_der())).map_err(|e| e.to_string())
+}
+
+/// Check an access token's signature, issuer, audience and expiry
+///
+/// Tokens signed by any published key are accepted, so tokens outlive a switch
+/// of the active key.
+pub fn verify_access_token(
+    keys: &SigningKeys,
+    token: &str,
+) -> Result<AccessTokenClaims, AccessTokenError> {
+    let header = decode_header(token).map_err(|e| AccessTokenError::Invalid(e.to_string()))?;
+    if header.alg != Algorithm::EdDSA {
+        return Err(AccessTokenError::Invalid(
+            "unexpected algorithm".to_string(),
+        ));
+    }
+    let key = header
+        .kid
+        .as_deref()
+        .and_then(|kid| keys.get(kid))
+        .ok_or_else(|| AccessTokenError::Invalid("unknown key id".to_string()))?;
+
+    let mut validation = Validation::new(Algorithm::EdDSA);
+    validation.set_issuer(&[ISSUER]);
+    validation.set_audience(&[AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+    decode::<AccessTokenClaims>(
+        token,
+        &DecodingKey::from_ed_der(&key.public_key()),
+        &validation,
+    )
+    .map(|data| data.claims)
+    .map_err(|e| match e.kind() {
+        ErrorKind::ExpiredSignature => AccessTokenError::Expired,
+        _ => AccessTokenError::Invalid(e.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine, engine::general_purpose::STANDARD};
+
+    fn keys(spec: &[(&str, u8)], active: Option<&str>) -> SigningKeys {
+        let spec: Vec<String> = spec
+            .iter()
+            .map(|(kid, byte)| format!("{}:{}", kid, STANDARD.encode([*byte; 32])))
+            .collect();
+        SigningKeys::parse(&spec.join(","), active)
+    }
+
+    fn claims(exp_in: i64) -> AccessTokenClaims {
+        let now = chrono::Utc::now().timestamp();
+        AccessTokenClaims {
+            iss: ISSUER.to_string(),
+            aud: AUDIENCE.to_string(),
+            sub: Uuid::new_v4(),
+            account_id: Uuid::new_v4(),
+            scope: "transfers:read transfers:write".to_string(),
+            livemode: true,
+            allowed_ips: Vec::new(),
+            require_signed_writes: false,
+            iat: now,
+            exp: now + exp_in,
+            jti: Uuid::new_v4(),
+        }
+    }
+
+    #[test]
+    fn test_token_round_trip_and_key_rotation() {
+        let old = keys(&[("k1", 1)], None);
+        let claims = claims(ACCESS_TOKEN_TTL_SECONDS);
+        let token = issue_access_token(&old, &claims).unwrap();
+        assert!(looks_like_jwt(&token));
+        assert!(!looks_like_jwt("dodo_live_0123_abcd"));
+
+        assert_eq!(verify_access_token(&old, &token).unwrap(), claims);
+        assert_eq!(
+            verify_access_token(&old, &token).unwrap().scopes(),
+            vec!["transfers:read", "transfers:write"]
+        );
+
+        // Still valid once a new key signs, while the old one is published
+        let rotated = keys(&[("k1", 1), ("k2", 2)], Some("k2"));
+        assert!(verify_access_token(&rotated, &token).is_ok());
+        let retired = keys(&[("k2", 2)], None);
+        assert!(matches!(
+            verify_access_token(&retired, &token),
+            Err(AccessTokenError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_expired_forged_and_tampered_tokens() {
+        let keys_a = keys(&[("k1", 1)], None);
+        let expired = issue_access_token(&keys_a, &claims(-120)).unwrap();
+        assert_eq!(
+            verify_access_token(&keys_a, &expired),
+            Err(AccessTokenError::Expired)
+        );
+
+        // Same kid, different key
+        let forged = issue_access_token(&keys(&[("k1", 9)], None), &claims(60)).unwrap();
+        assert!(verify_access_token(&keys_a, &forged).is_err());
+
+        let token = issue_access_token(&keys_a, &claims(60)).unwrap();
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let other = issue_access_token(&keys_a, &claims(60)).unwrap();
+        parts[1] = other.split('.').nth(1).unwrap();
+        assert!(verify_access_token(&keys_a, &parts.join(".")).is_err());
+
+        assert!(issue_access_token(&SigningKeys::parse("", None), &claims(60)).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::datalayer::CRUD::types::ApiKey;
+use futures_util::StreamExt;
+use lru::LruCache;
+use opentelemetry::{KeyValue, global, metrics::Counter};
+use redis::{AsyncCommands, Client, aio::ConnectionManager};
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+/// Redis channel every instance publishes API key invalidations to and subscribes on
+const INVALIDATION_CHANNEL: &str = "auth:api_keys:invalidate";
+
+/// Redis key of a cached record, followed by the entry name
+const REDIS_KEY_PREFIX: &str = "auth:api_key:";
+
+/// Entry name of a record cached by id, followed by the id; other entries are key hashes
+const ID_ENTRY_PREFIX: &str = "id:";
+
+/// Records kept by the in-process tier of each instance
+const LOCAL_CAPACITY: usize = 10_000;
+
+/// Lifetime of an entry in the in-process tier
+const LOCAL_TTL: Duration = Duration::from_secs(10);
+
+/// Lifetime of an entry in the Redis tier
+const REDIS_TTL_SECONDS: u64 = 60;
+
+/// Delay before re-subscribing after the Redis subscription drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+/// Process-wide cache, initialized together with the Redis connection in `AppState`
+pub static API_KEY_CACHE: OnceLock<ApiKeyCache> = OnceLock::new();
+
+static LOOKUPS: OnceLock<Counter<u64>> = OnceLock::new();
+
+type LocalTier = Arc<Mutex<LruCache<String, LocalEntry>>>;
+
+struct LocalEntry {
+    api_key: ApiKey,
+    cached_at: Instant,
+}
+
+/// Two-tier cache of API key records for authentication
+///
+/// Each record is stored twice, under its key hash for bearer requests and under
+/// its id for signed requests. Lookups try the in-process LRU, then Redis; on a
+/// miss the caller reads the database and stores the record. Both tiers expire entries after a short TTL.
+/// A cached record is still checked on every request, so a key whose
+/// `expires_at` passes is rejected without invalidation. Any other change to a
+/// key (revocation, a new expiry on rotation) must be followed by `invalidate`,
+/// which deletes the Redis entry and tells every instance over pub/sub to drop
+/// its local copy.
+///
+/// Lookups are counted by the `api_key_cache_lookups_total` counter, with
+/// `result` set to `local_hit`, `redis_hit` or `miss`.
+pub struct ApiKeyCache {
+    redis: ConnectionManager,
+    local: LocalTier,
+}
+
+impl ApiKeyCache {
+    /// Create the cache and start the Redis invalidation subscriber task
+    pub fn start(client: Client, redis: ConnectionManager) -> Self {
+        let capacity = NonZeroUsize::new(LOCAL_CAPACITY).expect("capacity is non-zero");
+        let local: LocalTier = Arc::new(Mutex::new(LruCache::new(capacity)));
+
+        let subscriber_local = local.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = Self::invalidate_from_redis(&client, &subscriber_local).await {
+                    tracing::error!(error = %e, "API key cache subscription failed");
+                }
+                tracing::warn!("API key cache subscription ended, resubscribing");
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        });
+
+        Self { redis, local }
+    }
+
+    /// Initialize the process-wide cache; later calls keep the first instance
+    pub fn init_global(client: Client, redis: ConnectionManager) -> &'static ApiKeyCache {
+        API_KEY_CACHE.get_or_init(|| Self::start(client, redis))
+    }
+
+    /// Cached record of the key with this hash, if any
+    pub async fn get(&self, key_hash: &str) -> Option<ApiKey> {
+        self.lookup(key_hash).await
+    }
+
+    /// Cached record of the key with this id, if any
+    pub async fn get_by_id(&self, api_key_id: Uuid) -> Option<ApiKey> {
+        self.lookup(&id_entry(api_key_id)).await
+    }
+
+    async fn lookup(&self, entry: &str) -> Option<ApiKey> {
+        if let Some(api_key) = self.get_local(entry) {
+            record_lookup("local_hit");
+            return Some(api_key);
+        }
+
+        let mut redis = self.redis.clone();
+        let cached: Result<Option<String>, redis::RedisError> = redis.get(redis_key(entry)).await;
+        let api_key = match cached {
+            Ok(Some(json)) => serde_json::from_str::<ApiKey>(&json)
+                .inspect_err(|e| tracing::warn!(error = %e, "Dropping malformed cached API key"))
+                .ok(),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to read API key from Redis cache");
+                None
+            }
+        };
+
+        match api_key {
+            Some(api_key) => {
+                record_lookup("redis_hit");
+                self.put_local(entry, api_key.clone());
+                Some(api_key)
+            }
+            None => {
+                record_lookup("miss");
+                None
+            }
+        }
+    }
+
+    /// Store a record read from the database in both tiers
+    pub async fn put(&self, api_key: &ApiKey) {
+        let json = match serde_json::to_string(api_key) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!(error = %e, api_key_id = %api_key.id, "Failed to serialize API key");
+                return;
+            }
+        };
+
+        let mut redis = self.redis.clone();
+        for entry in entries(api_key) {
+            self.put_local(&entry, api_key.clone());
+
+            let result: Result<(), redis::RedisError> = redis
+                .set_ex(redis_key(&entry), json.as_str(), REDIS_TTL_SECONDS)
+                .await;
+            if let Err(e) = result {
+                tracing::warn!(error = %e, api_key_id = %api_key.id, "Failed to cache API key in Redis");
+            }
+        }
+    }
+
+    /// Drop the key from both tiers on every instance
+    pub async fn invalidate(&self, api_key: &ApiKey) {
+        let mut redis = self.redis.clone();
+        for entry in entries(api_key) {
+            self.local.lock().unwrap().pop(&entry);
+
+            let deleted: Result<(), redis::RedisError> = redis.del(redis_key(&entry)).await;
+            if let Err(e) = deleted {
+                tracing::error!(error = %e, "Failed to delete API key from Redis cache");
+            }
+
+            let published: Result<i64, redis::RedisError> =
+                redis.publish(INVALIDATION_CHANNEL, &entry).await;
+            if let Err(e) = published {
+                tracing::error!(error = %e, "Failed to publish API key invalidation");
+            }
+        }
+    }
+
+    fn get_local(&self, entry: &str) -> Option<ApiKey> {
+        let mut local = self.local.lock().unwrap();
+        match local.get(entry) {
+            Some(cached) if cached.cached_at.elapsed() < LOCAL_TTL => Some(cached.api_key.clone()),
+            Some(_) => {
+                local.pop(entry);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn put_local(&self, entry: &str, api_key: ApiKey) {
+        let cached = LocalEntry {
+            api_key,
+            cached_at: Instant::now(),
+        };
+        self.local.lock().unwrap().put(entry.to_string(), cached);
+    }
+
+    async fn invalidate_from_redis(
+        client: &Client,
+        local: &LocalTier,
+    ) -> Result<(), redis::RedisError> {
+        let mut pubsub = client.get_async_connection().await?.into_pubsub();
+        pubsub.subscribe(INVALIDATION_CHANNEL).await?;
+
+        // Invalidations sent while unsubscribed were missed; start from an empty tier
+        local.lock().unwrap().clear();
+
+        tracing::info!(
+            channel = INVALIDATION_CHANNEL,
+            "API key cache subscribed to Redis"
+        );
+
+        let mut messages = pubsub.on_message();
+        while let Some(message) = messages.next().await {
+            match message.get_payload::<String>() {
+                Ok(entry) => {
+                    local.lock().unwrap().pop(&entry);
+                }
+                Err(e) => tracing::warn!(error = %e, "Dropping unreadable invalidation message"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Invalidate changed API keys in the process-wide cache, if one is running
+pub async fn invalidate_api_keys(api_keys: &[&ApiKey]) {
+    let Some(cache) = API_KEY_CACHE.get() else {
+        return;
+    };
+    for api_key in api_keys {
+        cache.invalidate(api_key).await;
+    }
+}
+
+/// Names a record is cached under
+fn entries(api_key: &ApiKey) -> [String; 2] {
+    [api_key.key_hash.clone(), id_entry(api_key.id)]
+}
+
+fn id_entry(api_key_id: Uuid) -> String {
+    format!("{}{}", ID_ENTRY_PREFIX, api_key_id)
+}
+
+fn redis_key(entry: &str) -> String {
+    format!("{}{}", REDIS_KEY_PREFIX, entry)
+}
+
+fn record_lookup(result: &'static str) {
+    LOOKUPS
+        .get_or_init(|| {
+            global::meter("payments-backend")
+                .u64_counter("api_key_cache_lookups_total")
+                .with_description("API key cache lookups during authentication, by result")
+                .init()
+        })
+        .add(1, &[KeyValue::new("result", result)]);
+}
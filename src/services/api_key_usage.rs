@@ -0,0 +1,225 @@
+use crate::datalayer::{
+    CRUD::{
+        api_key::{add_api_key_daily_usage, record_api_key_last_use},
+        types::{ApiKeyDailyUsage, ApiKeyLastUse},
+    },
+    db_ops::constants::POOL_STATE_TRACKER,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// How often buffered usage is written to the database
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest user agent stored; longer ones are truncated
+const MAX_USER_AGENT_LEN: usize = 512;
+
+static BUFFER: OnceLock<Mutex<UsageBuffer>> = OnceLock::new();
+
+/// API key usage recorded since the last flush
+#[derive(Debug, Default)]
+pub struct UsageBuffer {
+    last_use: HashMap<Uuid, ApiKeyLastUse>,
+    daily: HashMap<(Uuid, NaiveDate), DailyCounts>,
+}
+
+#[derive(Debug, Default)]
+struct DailyCounts {
+    requests: i64,
+    ip_rejections: i64,
+}
+
+impl UsageBuffer {
+    /// Count one request and remember it as the key's latest use
+    pub fn record(&mut self, last_use: ApiKeyLastUse) {
+        self.daily
+            .entry((last_use.api_key_id, last_use.used_at.date_naive()))
+            .or_default()
+            .requests += 1;
+        self.keep_latest(last_use);
+    }
+
+    /// Count one request refused by the key's IP allowlist
+    pub fn record_ip_rejection(&mut self, api_key_id: Uuid, at: DateTime<Utc>) {
+        self.daily
+            .entry((api_key_id, at.date_naive()))
+            .or_default()
+            .ip_rejections += 1;
+    }
+
+    /// Take everything recorded so far, leaving the buffer empty
+    pub fn drain(&mut self) -> (Vec<ApiKeyLastUse>, Vec<ApiKeyDailyUsage>) {
+        let last_use = self.last_use.drain().map(|(_, u)| u).collect();
+        let daily = self
+            .daily
+            .drain()
+            .map(|((api_key_id, usage_date), counts)| ApiKeyDailyUsage {
+                api_key_id,
+                usage_date,
+                request_count: counts.requests,
+                ip_rejected_count: counts.ip_rejections,
+            })
+            .collect();
+        (last_use, daily)
+    }
+
+    /// Put back a batch that could not be written
+    pub fn restore(&mut self, last_use: Vec<ApiKeyLastUse>, daily: Vec<ApiKeyDailyUsage>) {
+        for u in last_use {
+            self.keep_latest(u);
+        }
+        for d in daily {
+            let counts = self.daily.entry((d.api_key_id, d.usage_date)).or_default();
+            counts.requests += d.request_count;
+            counts.ip_rejections += d.ip_rejected_count;
+        }
+    }
+
+    fn keep_latest(&mut self, last_use: ApiKeyLastUse) {
+        match self.last_use.get(&last_use.api_key_id) {
+            Some(existing) if existing.used_at >= last_use.used_at => {}
+            _ => {
+                self.last_use.insert(last_use.api_key_id, last_use);
+            }
+        }
+    }
+}
+
+fn buffer() -> &'static Mutex<UsageBuffer> {
+    BUFFER.get_or_init(Default::default)
+}
+
+/// Record that an API key authenticated a request
+///
+/// Only touches memory; `flush_usage_periodically` writes the usage out.
+pub fn record_api_key_use(api_key_id: Uuid, ip: Option<String>, user_agent: Option<&str>) {
+    let last_use = ApiKeyLastUse {
+        api_key_id,
+        used_at: Utc::now(),
+        ip,
+        user_agent: user_agent.map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect()),
+    };
+    buffer().lock().unwrap().record(last_use);
+}
+
+/// Record that an API key was refused because of its IP allowlist
+pub fn record_ip_rejection(api_key_id: Uuid) {
+    buffer()
+        .lock()
+        .unwrap()
+        .record_ip_rejection(api_key_id, Utc::now());
+}
+
+/// Write buffered usage to the database; on failure it stays buffered for the next flush
+pub async fn flush_usage() {
+    let (last_use, daily) = buffer().lock().unwrap().drain();
+    if last_use.is_empty() && daily.is_empty() {
+        return;
+    }
+
+    let Some(tracker) = POOL_STATE_TRACKER.get() else {
+        buffer().lock().unwrap().restore(last_use, daily);
+        return;
+    };
+    let mut conn = match tracker.get_connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            tracing::warn!(error = %e, "No connection to flush API key usage");
+            buffer().lock().unwrap().restore(last_use, daily);
+            return;
+        }
+    };
+
+    let last_use_written = record_api_key_last_use(&last_use, &mut conn).await.is_ok();
+    let daily_written = add_api_key_daily_usage(&daily, &mut conn).await.is_ok();
+    tracker.return_connection(conn);
+
+    tracing::debug!(
+        keys = last_use.len(),
+        daily_rows = daily.len(),
+        "Flushed API key usage"
+    );
+
+    buffer().lock().unwrap().restore(
+        if last_use_written {
+            Vec::new()
+        } else {
+            last_use
+        },
+        if daily_written { Vec::new() } else { daily },
+    );
+}
+
+/// Flush buffered usage every few seconds, for the lifetime of the process
+pub async fn flush_usage_periodically() {
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        flush_usage().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn use_at(api_key_id: Uuid, used_at: DateTime<Utc>, ip: &str) -> ApiKeyLastUse {
+        ApiKeyLastUse {
+            api_key_id,
+            used_at,
+            ip: Some(ip.to_string()),
+            user_agent: None,
+        }
+    }
+
+    #[test]
+    fn test_record_counts_per_day_and_keeps_latest_use() {
+        let key = Uuid::new_v4();
+        let day_one = "2026-01-01T23:59:00Z".parse::<DateTime<Utc>>().unwrap();
+        let day_two = "2026-01-02T00:01:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let mut buffer = UsageBuffer::default();
+        buffer.record(use_at(key, day_two, "10.0.0.2"));
+        buffer.record(use_at(key, day_one, "10.0.0.1"));
+        buffer.record(use_at(key, day_one, "10.0.0.1"));
+        buffer.record_ip_rejection(key, day_two);
+
+        let (last_use, mut daily) = buffer.drain();
+        daily.sort_by_key(|d| d.usage_date);
+
+        assert_eq!(last_use, vec![use_at(key, day_two, "10.0.0.2")]);
+        assert_eq!(
+            daily
+                .iter()
+                .map(|d| (d.request_count, d.ip_rejected_count))
+                .collect::<Vec<_>>(),
+            vec![(2, 0), (1, 1)]
+        );
+        assert_eq!(buffer.drain(), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn test_restore_merges_with_newer_usage() {
+        let key = Uuid::new_v4();
+        let earlier = "2026-01-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let later = "2026-01-01T10:00:10Z".parse::<DateTime<Utc>>().unwrap();
+
+        let mut buffer = UsageBuffer::default();
+        buffer.record(use_at(key, earlier, "10.0.0.1"));
+        let (last_use, daily) = buffer.drain();
+
+        buffer.record(use_at(key, later, "10.0.0.2"));
+        buffer.restore(last_use, daily);
+
+        let (last_use, daily) = buffer.drain();
+        assert_eq!(last_use, vec![use_at(key, later, "10.0.0.2")]);
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].request_count, 2);
+    }
+}
@@ -0,0 +1,403 @@
+use chrono::{DateTime, Utc};
+use opentelemetry::{KeyValue, global, metrics::ObservableGauge};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// Process-wide breakers for webhook destinations, created on first use
+static CIRCUIT_BREAKERS: OnceLock<CircuitBreakers> = OnceLock::new();
+
+/// Keeps the breaker state gauge registered with the meter provider
+static STATE_GAUGE: OnceLock<ObservableGauge<u64>> = OnceLock::new();
+
+/// Breaker state of a destination host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Requests flow; outcomes are counted in the rolling window
+    Closed,
+    /// Requests are short-circuited until the cooldown ends
+    Open,
+    /// One probe request is let through to decide whether to close again
+    HalfOpen,
+}
+
+impl BreakerState {
+    /// Value exported by the `webhook_circuit_breaker_state` gauge
+    fn metric_value(self) -> u64 {
+        match self {
+            BreakerState::Closed => 0,
+            BreakerState::HalfOpen => 1,
+            BreakerState::Open => 2,
+        }
+    }
+}
+
+/// Thresholds shared by every destination's breaker
+#[derive(Debug, Clone)]
+pub struct BreakerConfig {
+    /// Failure rate (0.0-1.0) in the window at which the breaker opens
+    pub failure_rate_threshold: f64,
+    /// Outcomes needed in the window before the failure rate is considered
+    pub minimum_requests: usize,
+    /// Length of the rolling window outcomes are counted in
+    pub window: Duration,
+    /// How long an open breaker short-circuits before letting a probe through
+    pub cooldown: Duration,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_rate_threshold: 0.5,
+            minimum_requests: 5,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl BreakerConfig {
+    /// Read overrides from `WEBHOOK_BREAKER_*` environment variables
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let var = |name: &str| std::env::var(name).ok();
+
+        Self {
+            failure_rate_threshold: var("WEBHOOK_BREAKER_FAILURE_RATE")
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .unwrap_or(default.failure_rate_threshold),
+            minimum_requests: var("WEBHOOK_BREAKER_MIN_REQUESTS")
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(default.minimum_requests),
+            window: var("WEBHOOK_BREAKER_WINDOW_SECS")
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|s| *s > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(default.window),
+            cooldown: var("WEBHOOK_BREAKER_COOLDOWN_SECS")
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|s| *s > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(default.cooldown),
+        }
+    }
+}
+
+/// Point-in-time view of one destination's breaker, for the admin API
+#[derive(Debug, Clone, Serialize)]
+pub struct BreakerSnapshot {
+    pub host: String,
+    pub state: BreakerState,
+    pub requests_in_window: usize,
+    pub failures_in_window: usize,
+    pub opened_at: Option<DateTime<Utc>>,
+    /// Seconds until an open breaker lets a probe through
+    pub retry_after_seconds: Option<u64>,
+}
+
+#[derive(Debug)]
+struct HostBreaker {
+    state: BreakerState,
+    /// Outcomes in the rolling window; `true` is a failure
+    outcomes: VecDeque<(Instant, bool)>,
+    opened_at: Option<(Instant, DateTime<Utc>)>,
+    probe_started_at: Option<Instant>,
+}
+
+impl HostBreaker {
+    fn new() -> Self {
+        Self {
+            state: BreakerState::Closed,
+            outcomes: VecDeque::new(),
+            opened_at: None,
+            probe_started_at: None,
+        }
+    }
+
+    fn prune(&mut self, window: Duration, now: Instant) {
+        while let Some((at, _)) = self.outcomes.front() {
+            if now.duration_since(*at) <= window {
+                break;
+            }
+            self.outcomes.pop_front();
+        }
+    }
+
+    fn failures(&self) -> usize {
+        self.outcomes.iter().filter(|(_, failed)| *failed).count()
+    }
+
+    fn open(&mut self, now: Instant) {
+        self.state = BreakerState::Open;
+        self.opened_at = Some((now, Utc::now()));
+        self.probe_started_at = None;
+    }
+
+    fn close(&mut self) {
+        self.state = BreakerState::Closed;
+        self.outcomes.clear();
+        self.opened_at = None;
+        self.probe_started_at = None;
+    }
+
+    fn remaining_cooldown(&self, cooldown: Duration, now: Instant) -> Duration {
+        self.opened_at
+            .map(|(at, _)| cooldown.saturating_sub(now.duration_since(at)))
+            .unwrap_or_default()
+    }
+}
+
+/// Circuit breakers for webhook destinations, keyed by host
+///
+/// Deliveries ask `try_acquire` before connecting and report the outcome with
+/// `record`. When the failure rate over the window reaches the threshold the
+/// host's breaker opens and attempts are short-circuited for the cooldown; then a
+/// single probe is let through, closing the breaker on success or reopening it.
+pub struct CircuitBreakers {
+    config: BreakerConfig,
+    hosts: Mutex<HashMap<String, HostBreaker>>,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Ask whether an attempt against `host` may be made now
+    /// Returns how long to wait when the breaker short-circuits the attempt
+    pub fn try_acquire(&self, host: &str) -> Result<(), Duration> {
+        self.try_acquire_at(host, Instant::now())
+    }
+
+    /// Report the outcome of an attempt allowed by `try_acquire`
+    pub fn record(&self, host: &str, failed: bool) {
+        self.record_at(host, failed, Instant::now())
+    }
+
+    /// State of every destination seen so far, sorted by host
+    pub fn snapshot(&self) -> Vec<BreakerSnapshot> {
+        let now = Instant::now();
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut snapshot: Vec<BreakerSnapshot> = hosts
+            .iter_mut()
+            .map(|(host, breaker)| {
+                breaker.prune(self.config.window, now);
+                BreakerSnapshot {
+                    host: host.clone(),
+                    state: breaker.state,
+                    requests_in_window: breaker.outcomes.len(),
+                    failures_in_window: breaker.failures(),
+                    opened_at: breaker.opened_at.map(|(_, at)| at),
+                    retry_after_seconds: (breaker.state == BreakerState::Open).then(|| {
+                        breaker
+                            .remaining_cooldown(self.config.cooldown, now)
+                            .as_secs()
+                    }),
+                }
+            })
+            .collect();
+        snapshot.sort_by(|a, b| a.host.cmp(&b.host));
+        snapshot
+    }
+
+    fn try_acquire_at(&self, host: &str, now: Instant) -> Result<(), Duration> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let breaker = hosts
+            .entry(host.to_string())
+            .or_insert_with(HostBreaker::new);
+
+        match breaker.state {
+            BreakerState::Closed => Ok(()),
+            BreakerState::Open => {
+                let remaining = breaker.remaining_cooldown(self.config.cooldown, now);
+                if !remaining.is_zero() {
+                    return Err(remaining);
+                }
+                tracing::info!(host = %host, "Circuit breaker half-open, sending probe");
+                breaker.state = BreakerState::HalfOpen;
+                breaker.probe_started_at = Some(now);
+                Ok(())
+            }
+            BreakerState::HalfOpen => {
+                // A probe that never reported back is replaced after one cooldown
+                let probe_pending = breaker
+                    .probe_started_at
+                    .map(|at| self.config.cooldown.saturating_sub(now.duration_since(at)))
+                    .filter(|remaining| !remaining.is_zero());
+                match probe_pending {
+                    Some(remaining) => Err(remaining),
+                    None => {
+                        breaker.probe_started_at = Some(now);
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    fn record_at(&self, host: &str, failed: bool, now: Instant) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let breaker = hosts
+            .entry(host.to_string())
+            .or_insert_with(HostBreaker::new);
+
+        match breaker.state {
+            BreakerState::HalfOpen => {
+                if failed {
+                    tracing::warn!(host = %host, "Circuit breaker probe failed, reopening");
+                    breaker.open(now);
+                } else {
+                    tracing::info!(host = %host, "Circuit breaker probe succeeded, closing");
+                    breaker.close();
+                }
+            }
+            // An attempt allowed before the breaker opened; the outcome no longer matters
+            BreakerState::Open => {}
+            BreakerState::Closed => {
+                breaker.outcomes.push_back((now, failed));
+                breaker.prune(self.config.window, now);
+
+                let requests = breaker.outcomes.len();
+                let failures = breaker.failures();
+                if requests >= self.config.minimum_requests
+                    && failures as f64 / requests as f64 >= self.config.failure_rate_threshold
+                {
+                    tracing::warn!(
+                        host = %host,
+                        requests = requests,
+                        failures = failures,
+                        cooldown_secs = self.config.cooldown.as_secs(),
+                        "Circuit breaker opened for webhook destination"
+                    );
+                    breaker.open(now);
+                }
+            }
+        }
+    }
+}
+
+/// Breakers shared by all webhook deliveries of this process
+///
+/// The first call also registers the `webhook_circuit_breaker_state` gauge
+/// (0 closed, 1 half-open, 2 open, per `host`).
+pub fn circuit_breakers() -> &'static CircuitBreakers {
+    let breakers = CIRCUIT_BREAKERS.get_or_init(|| CircuitBreakers::new(BreakerConfig::from_env()));
+
+    STATE_GAUGE.get_or_init(|| {
+        global::meter("payments-backend")
+            .u64_observable_gauge("webhook_circuit_breaker_state")
+            .with_description("Circuit breaker state per webhook destination host")
+            .with_callback(|observer| {
+                if let Some(breakers) = CIRCUIT_BREAKERS.get() {
+                    for breaker in breakers.snapshot() {
+                        observer.observe(
+                            breaker.state.metric_value(),
+                            &[KeyValue::new("host", breaker.host)],
+                        );
+                    }
+                }
+            })
+            .init()
+    });
+
+    breakers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breakers() -> CircuitBreakers {
+        CircuitBreakers::new(BreakerConfig {
+            failure_rate_threshold: 0.5,
+            minimum_requests: 4,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn test_opens_at_failure_rate_after_minimum_requests() {
+        let breakers = breakers();
+        let now = Instant::now();
+
+        breakers.record_at("hooks.example.com", true, now);
+        breakers.record_at("hooks.example.com", true, now);
+        breakers.record_at("hooks.example.com", true, now);
+        assert!(breakers.try_acquire_at("hooks.example.com", now).is_ok());
+
+        breakers.record_at("hooks.example.com", false, now);
+        let wait = breakers
+            .try_acquire_at("hooks.example.com", now)
+            .unwrap_err();
+        assert_eq!(wait, Duration::from_secs(30));
+        assert!(breakers.try_acquire_at("other.example.com", now).is_ok());
+    }
+
+    #[test]
+    fn test_old_outcomes_leave_the_window() {
+        let breakers = breakers();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            breakers.record_at("hooks.example.com", true, start);
+        }
+        let later = start + Duration::from_secs(61);
+        breakers.record_at("hooks.example.com", true, later);
+
+        assert!(breakers.try_acquire_at("hooks.example.com", later).is_ok());
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_or_reopens() {
+        let breakers = breakers();
+        let start = Instant::now();
+        for _ in 0..4 {
+            breakers.record_at("hooks.example.com", true, start);
+        }
+
+        let after_cooldown = start + Duration::from_secs(30);
+        assert!(
+            breakers
+                .try_acquire_at("hooks.example.com", after_cooldown)
+                .is_ok()
+        );
+        // Only one probe at a time
+        assert!(
+            breakers
+                .try_acquire_at("hooks.example.com", after_cooldown)
+                .is_err()
+        );
+
+        breakers.record_at("hooks.example.com", true, after_cooldown);
+        assert!(
+            breakers
+                .try_acquire_at("hooks.example.com", after_cooldown)
+                .is_err()
+        );
+
+        let second_probe = after_cooldown + Duration::from_secs(30);
+        assert!(
+            breakers
+                .try_acquire_at("hooks.example.com", second_probe)
+                .is_ok()
+        );
+        breakers.record_at("hooks.example.com", false, second_probe);
+        assert!(
+            breakers
+                .try_acquire_at("hooks.example.com", second_probe)
+                .is_ok()
+        );
+        assert_eq!(breakers.snapshot()[0].state, BreakerState::Closed);
+    }
+}
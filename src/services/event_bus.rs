@@ -0,0 +1,161 @@
+use crate::datalayer::CRUD::{
+    events::Event,
+    types::{Account, ApiKey, Transaction},
+};
+use futures_util::StreamExt;
+use redis::{AsyncCommands, Client, aio::ConnectionManager};
+use serde_json::json;
+use std::{sync::OnceLock, time::Duration};
+use tokio::sync::broadcast;
+
+/// Redis channel every instance publishes account events to and subscribes on
+const EVENTS_CHANNEL: &str = "events:stream";
+
+/// Events buffered per local subscriber before it is considered lagging
+const LOCAL_BUFFER: usize = 1024;
+
+/// Delay before re-subscribing after the Redis subscription drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+/// Process-wide event bus, initialized together with the Redis connection in `AppState`
+pub static EVENT_BUS: OnceLock<EventBus> = OnceLock::new();
+
+/// Fans account events out to every connected stream across all server instances
+///
+/// Events are published to Redis on the shared `ConnectionManager`. Each instance
+/// holds one dedicated subscriber connection (pub/sub cannot share a multiplexed
+/// connection) and forwards received events to its local stream subscribers.
+#[derive(Clone)]
+pub struct EventBus {
+    redis: ConnectionManager,
+    local: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+    /// Create the bus and start the Redis subscriber task
+    pub fn start(client: Client, redis: ConnectionManager) -> Self {
+        let (local, _) = broadcast::channel(LOCAL_BUFFER);
+        let bus = Self { redis, local };
+
+        let forward = bus.local.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = Self::forward_from_redis(&client, &forward).await {
+                    tracing::error!(error = %e, "Event bus subscription failed");
+                }
+                tracing::warn!("Event bus subscription ended, resubscribing");
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        });
+
+        bus
+    }
+
+    /// Initialize the process-wide bus; later calls keep the first instance
+    pub fn init_global(client: Client, redis: ConnectionManager) -> &'static EventBus {
+        EVENT_BUS.get_or_init(|| Self::start(client, redis))
+    }
+
+    /// Subscribe to the events received by this instance, for all accounts
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.local.subscribe()
+    }
+
+    /// Publish a committed event to every instance
+    /// Falls back to local subscribers only when Redis is unavailable
+    pub async fn publish(&self, event: &Event) {
+        let message = match serde_json::to_string(event) {
+            Ok(m) => m,
+            Err(e) => {
+                tracing::error!(error = %e, event_id = %event.id, "Failed to serialize event");
+                return;
+            }
+        };
+
+        let mut redis = self.redis.clone();
+        let result: Result<i64, redis::RedisError> = redis.publish(EVENTS_CHANNEL, message).await;
+
+        if let Err(e) = result {
+            tracing::warn!(
+                error = %e,
+                event_id = %event.id,
+                "Failed to publish event to Redis, delivering locally"
+            );
+            let _ = self.local.send(event.clone());
+        }
+    }
+
+    async fn forward_from_redis(
+        client: &Client,
+        forward: &broadcast::Sender<Event>,
+    ) -> Result<(), redis::RedisError> {
+        let mut pubsub = client.get_async_connection().await?.into_pubsub();
+        pubsub.subscribe(EVENTS_CHANNEL).await?;
+
+        tracing::info!(channel = EVENTS_CHANNEL, "Event bus subscribed to Redis");
+
+        let mut messages = pubsub.on_message();
+        while let Some(message) = messages.next().await {
+            let payload: String = match message.get_payload() {
+                Ok(p) => p,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Dropping unreadable event message");
+                    continue;
+                }
+            };
+
+            match serde_json::from_str::<Event>(&payload) {
+                // No receivers simply means no stream is open on this instance
+                Ok(event) => {
+                    let _ = forward.send(event);
+                }
+                Err(e) => tracing::warn!(error = %e, "Dropping malformed event message"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Publish committed events on the process-wide bus, if one is running
+pub async fn publish_events(events: &[Event]) {
+    let Some(bus) = EVENT_BUS.get() else {
+        return;
+    };
+    for event in events {
+        bus.publish(event).await;
+    }
+}
+
+/// Event `data` for a transaction, shared by webhooks and event streams
+pub fn transaction_event_data(transaction: &Transaction) -> serde_json::Value {
+    json!({
+        "transaction_id": transaction.id,
+        "amount": transaction.amount,
+        "currency": transaction.currency,
+        "description": transaction.description,
+        "parent_tx_key": transaction.parent_tx_key,
+    })
+}
+
+/// Event `data` for an account
+pub fn account_event_data(account: &Account) -> serde_json::Value {
+    json!({
+        "account_id": account.id,
+        "business_name": account.business_name,
+        "email": account.email,
+        "balance": account.balance,
+        "currency": account.currency,
+        "status": account.status,
+    })
+}
+
+/// Event `data` for an API key; never includes the key or its hash
+pub fn api_key_event_data(api_key: &ApiKey) -> serde_json::Value {
+    json!({
+        "api_key_id": api_key.id,
+        "key_prefix": api_key.key_prefix,
+        "name": api_key.name,
+        "status": api_key.status,
+    })
+}
@@ -1,3 +1,12 @@
+pub mod access_tokens;
+pub mod api_key_cache;
+pub mod api_key_usage;
+pub mod circuit_breaker;
+pub mod egress_guard;
+pub mod event_bus;
+pub mod webhook_backfill;
 pub mod webhook_dispatcher;
+pub mod webhook_secrets;
+pub mod webhook_signing;
 
 pub use webhook_dispatcher::WebhookDispatcher;
@@ -0,0 +1,189 @@
+use crate::datalayer::{
+    CRUD::{
+        events::{Event, get_events_in_range},
+        webhook::{Webhook, get_webhook_by_id},
+        webhook_backfill::{
+            WebhookBackfill, finish_backfill, get_running_backfills, record_backfill_progress,
+        },
+    },
+    db_ops::constants::POOL_STATE_TRACKER,
+};
+use crate::errors::errors::ServiceError;
+use crate::logging::propagation::TraceOrigin;
+use crate::services::webhook_dispatcher::WebhookDispatcher;
+use std::time::Duration;
+use tracing::Instrument;
+
+/// Events read from the log per batch; progress is saved after each batch
+const BATCH_SIZE: i64 = 100;
+
+/// Deliveries a backfill lets wait in the webhook's queue before it pauses
+///
+/// Live events join the same queue, so they never wait behind more than this
+/// many replayed ones.
+const MAX_QUEUED_DELIVERIES: usize = 10;
+
+/// How long a backfill waits before checking the queue again
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Start replaying a backfill's events in the background
+pub fn start_backfill(backfill: WebhookBackfill) {
+    let span = tracing::info_span!(
+        parent: None,
+        "webhook.backfill",
+        backfill_id = %backfill.id,
+        webhook_id = %backfill.webhook_id,
+    );
+    TraceOrigin::current().adopt(&span);
+    tokio::spawn(run(backfill).instrument(span));
+}
+
+/// Pick up backfills that were running when the process stopped
+///
+/// Each continues after the last event it had queued. Events queued but not yet
+/// delivered at shutdown are not replayed again.
+pub async fn resume_backfills() {
+    let Some(tracker) = POOL_STATE_TRACKER.get() else {
+        tracing::error!("Failed to get pool tracker for resuming webhook backfills");
+        return;
+    };
+
+    let mut conn = match tracker.get_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to get connection for resuming webhook backfills");
+            return;
+        }
+    };
+
+    let result = get_running_backfills(&mut conn).await;
+    tracker.return_connection(conn);
+
+    if let Ok(backfills) = result {
+        for backfill in backfills {
+            tracing::info!(
+                backfill_id = %backfill.id,
+                webhook_id = %backfill.webhook_id,
+                last_sequence = backfill.last_sequence,
+                "Resuming webhook backfill"
+            );
+            start_backfill(backfill);
+        }
+    }
+}
+
+/// Queue the backfill's events in log order, throttled by the webhook's queue depth
+async fn run(backfill: WebhookBackfill) {
+    let dispatcher = WebhookDispatcher::new();
+    let event_types = backfill.event_types();
+    let mut queued_events = backfill.queued_events;
+    let mut last_sequence = backfill.last_sequence;
+
+    loop {
+        let (webhook, events) = match next_batch(&backfill, &event_types, last_sequence).await {
+            Ok(batch) => batch,
+            Err(reason) => {
+                tracing::warn!(backfill_id = %backfill.id, reason = %reason, "Webhook backfill failed");
+                finish(&backfill, "failed", Some(&reason)).await;
+                return;
+            }
+        };
+
+        if events.is_empty() {
+            finish(&backfill, "completed", None).await;
+            return;
+        }
+
+        for event in events {
+            while WebhookDispatcher::queued_deliveries(webhook.id) >= MAX_QUEUED_DELIVERIES {
+                tokio::time::sleep(QUEUE_POLL_INTERVAL).await;
+            }
+
+            last_sequence = event.sequence;
+            queued_events += 1;
+            dispatcher.dispatch_event(webhook.clone(), event);
+        }
+
+        save_progress(&backfill, queued_events, last_sequence).await;
+    }
+}
+
+/// Load the webhook and the next batch of events after `after_sequence`
+///
+/// The webhook is read again for every batch, so configuration changes apply and a
+/// webhook that was disabled or deleted stops the backfill.
+async fn next_batch(
+    backfill: &WebhookBackfill,
+    event_types: &[String],
+    after_sequence: i64,
+) -> Result<(Webhook, Vec<Event>), String> {
+    let tracker = POOL_STATE_TRACKER
+        .get()
+        .ok_or_else(|| "Database pool not initialized".to_string())?;
+    let mut conn = tracker
+        .get_connection()
+        .await
+        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+    let result = async {
+        let webhook =
+            get_webhook_by_id(backfill.webhook_id, backfill.account_id, &mut conn).await?;
+        let events = if webhook.status == "active" {
+            get_events_in_range(
+                backfill.account_id,
+                event_types,
+                backfill.start_at,
+                backfill.end_at,
+                after_sequence,
+                BATCH_SIZE,
+                &mut conn,
+            )
+            .await?
+        } else {
+            Vec::new()
+        };
+        Ok::<_, ServiceError>((webhook, events))
+    }
+    .await;
+    tracker.return_connection(conn);
+
+    let (webhook, events) = result.map_err(|e| e.to_string())?;
+    if webhook.status != "active" {
+        return Err(format!("Webhook is {}", webhook.status));
+    }
+
+    Ok((webhook, events))
+}
+
+async fn save_progress(backfill: &WebhookBackfill, queued_events: i32, last_sequence: i64) {
+    let Some(tracker) = POOL_STATE_TRACKER.get() else {
+        return;
+    };
+
+    match tracker.get_connection().await {
+        Ok(mut conn) => {
+            let _ = record_backfill_progress(backfill.id, queued_events, last_sequence, &mut conn)
+                .await;
+            tracker.return_connection(conn);
+        }
+        Err(e) => {
+            tracing::error!(error = %e, backfill_id = %backfill.id, "Failed to get connection for backfill progress");
+        }
+    }
+}
+
+async fn finish(backfill: &WebhookBackfill, status: &str, error_message: Option<&str>) {
+    let Some(tracker) = POOL_STATE_TRACKER.get() else {
+        return;
+    };
+
+    match tracker.get_connection().await {
+        Ok(mut conn) => {
+            let _ = finish_backfill(backfill.id, status, error_message, &mut conn).await;
+            tracker.return_connection(conn);
+        }
+        Err(e) => {
+            tracing::error!(error = %e, backfill_id = %backfill.id, "Failed to get connection for finishing backfill");
+        }
+    }
+}
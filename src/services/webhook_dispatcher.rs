@@ -1,108 +1,826 @@
 use crate::datalayer::{
-    CRUD::{types::Transaction, webhook::Webhook},
+    CRUD::{
+        events::{Event, create_event},
+        types::WebhookDelivery,
+        webhook::{
+            DELIVERY_LEASE_SECONDS, MAX_DELIVERY_CONCURRENCY, Webhook, claim_due_deliveries,
+            get_webhook,
+        },
+    },
     db_ops::constants::POOL_STATE_TRACKER,
+    helper::backoff::ExponentialBackoff,
+};
+use crate::logging::propagation::{TraceOrigin, trace_context_headers};
+use crate::services::{
+    circuit_breaker::circuit_breakers,
+    egress_guard::{GuardedResolver, validate_webhook_url},
+    event_bus::publish_events,
+    webhook_secrets::secret_keys,
+    webhook_signing::signing_keys,
 };
 use hmac::{Hmac, Mac};
 use serde_json::json;
 use sha2::Sha256;
-use std::time::Duration;
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+use tokio::sync::{
+    Semaphore,
+    mpsc::{self, error::TrySendError},
+};
+use tracing::{Instrument, field};
 use uuid::Uuid;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Maximum number of bytes of a receiver's response body kept in `response_body`
+const MAX_RESPONSE_BODY_BYTES: usize = 16 * 1024;
+
+/// Maximum number of redirects followed for a single delivery
+const MAX_REDIRECTS: usize = 3;
+
+/// Retry settings used when a webhook row has no value of its own
+const DEFAULT_MAX_RETRIES: i32 = 3;
+const DEFAULT_RETRY_BACKOFF_SECONDS: i32 = 60;
+
+/// Upper bound for the delay between two attempts of a delivery
+const MAX_RETRY_DELAY_MS: u64 = 60 * 60 * 1000;
+
+/// Deliveries waiting per webhook before new ones are shed to the dead-letter queue
+const QUEUE_CAPACITY: usize = 1000;
+
+/// How long an idle queue worker waits for work before it shuts down
+const QUEUE_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often the sweeper looks for deliveries whose retry is due
+const RETRY_SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Deliveries claimed by one sweep
+const RETRY_SWEEP_BATCH: i64 = 100;
+
+/// Queues of the webhooks that have deliveries waiting or in flight, keyed by webhook id
+///
+/// Jobs are only pushed while this lock is held, which lets an idle worker remove
+/// its queue without losing a job sent concurrently.
+static DELIVERY_QUEUES: OnceLock<Mutex<HashMap<Uuid, mpsc::Sender<QueuedDelivery>>>> =
+    OnceLock::new();
+
+/// Existing delivery rows waiting in a queue or in flight in this process
+///
+/// The sweeper skips them when their lease runs out while they are still queued.
+static CLAIMED_DELIVERIES: OnceLock<Mutex<HashSet<Uuid>>> = OnceLock::new();
+
+/// Outcome of a single HTTP attempt against a webhook endpoint
+#[derive(Debug, Clone)]
+pub struct DeliveryAttempt {
+    pub status_code: u16,
+    pub latency_ms: u64,
+    pub success: bool,
+}
+
+/// Outcome of one attempt, as recorded on the delivery row
+#[derive(Debug, Clone)]
+struct AttemptRecord {
+    http_status_code: Option<i32>,
+    response_body: Option<String>,
+    error_message: Option<String>,
+    latency_ms: u64,
+    success: bool,
+    /// Set when the destination's circuit breaker is open: nothing was sent,
+    /// and the attempt can be made again after this delay
+    short_circuited: Option<Duration>,
+}
+
+impl AttemptRecord {
+    fn error(message: String, latency_ms: u64) -> Self {
+        Self {
+            http_status_code: None,
+            response_body: None,
+            error_message: Some(message),
+            latency_ms,
+            success: false,
+            short_circuited: None,
+        }
+    }
+}
+
+/// Work item of a webhook's delivery queue
+struct QueuedDelivery {
+    webhook: Webhook,
+    kind: DeliveryKind,
+    /// Trace context of the code that queued the delivery
+    origin: TraceOrigin,
+}
+
+enum DeliveryKind {
+    /// A new event; its sequence number is assigned when it leaves the queue
+    Event {
+        payload: serde_json::Value,
+        event_type: &'static str,
+    },
+    /// A logged delivery sent again on a new delivery row, keeping its payload
+    Redeliver(WebhookDelivery),
+    /// A dead-lettered delivery retried on its own row
+    Redrive(WebhookDelivery),
+    /// A pending delivery whose retry is due, claimed by the sweeper
+    Retry(WebhookDelivery),
+}
+
+/// A dequeued delivery with its row created, ready to be attempted
+struct PreparedDelivery {
+    delivery_id: Option<Uuid>,
+    payload: serde_json::Value,
+    event_type: String,
+    /// Attempts already recorded on the row
+    attempt_count: i32,
+    max_attempts: i32,
+}
+
 /// Simple webhook dispatcher - sends webhooks asynchronously
+///
+/// Deliveries go through a queue per webhook, drained by a single worker that keeps
+/// at most `max_concurrency` attempts in flight. With the default of 1, first attempts
+/// reach an endpoint strictly in order, each with the next `sequence`.
+///
+/// A failed attempt does not wait for its retry: the retry is scheduled on the delivery
+/// row through `next_retry_at` and put back on the queue by the retry sweeper once due.
 pub struct WebhookDispatcher {
     client: reqwest::Client,
 }
 
 impl WebhookDispatcher {
     pub fn new() -> Self {
+        // Every hop is re-validated: redirects to internal hosts are refused and
+        // names resolving to internal addresses fail in the resolver
+        let redirect_policy = reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            match validate_webhook_url(attempt.url().as_str()) {
+                Ok(_) => attempt.follow(),
+                Err(reason) => attempt.error(reason),
+            }
+        });
+
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(10))
+            .redirect(redirect_policy)
+            .dns_resolver(Arc::new(GuardedResolver))
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
 
         Self { client }
     }
 
+    /// Dispatch a logged event to a webhook, if it is an event type webhooks carry
+    pub fn dispatch_event(&self, webhook: Webhook, event: Event) {
+        match event.event_type.as_str() {
+            "transaction.debited" => self.dispatch_debit_webhook(webhook, event),
+            "transaction.credited" => self.dispatch_credit_webhook(webhook, event),
+            _ => {}
+        }
+    }
+
+    /// Number of deliveries waiting in a webhook's queue, not counting those in flight
+    pub fn queued_deliveries(webhook_id: Uuid) -> usize {
+        let queues = DELIVERY_QUEUES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        queues
+            .get(&webhook_id)
+            .map_or(0, |sender| sender.max_capacity() - sender.capacity())
+    }
+
     /// Dispatch webhook for debit transaction (amount debited from account)
-    pub fn dispatch_debit_webhook(&self, webhook: Webhook, transaction: Transaction) {
-        let client = self.client.clone();
-        let webhook = webhook.clone();
-        let transaction = transaction.clone();
-
-        // Spawn async task (fire and forget)
-        tokio::spawn(async move {
-            let payload = json!({
-                "event": "transaction.debited",
-                "message": "Amount has been debited from your account",
-                "data": {
-                    "transaction_id": transaction.id,
-                    "amount": transaction.amount,
-                    "currency": transaction.currency,
-                    "description": transaction.description,
-                    "parent_tx_key": transaction.parent_tx_key,
-                },
-                "timestamp": chrono::Utc::now().to_rfc3339(),
-            });
+    /// The payload carries the logged event's id so receivers can deduplicate
+    pub fn dispatch_debit_webhook(&self, webhook: Webhook, event: Event) {
+        if !webhook.subscribes_to("transaction.debited") {
+            return;
+        }
 
-            if let Err(e) =
-                Self::send_webhook(&client, &webhook, &payload, "transaction.debited").await
-            {
-                tracing::error!(
-                    webhook_id = %webhook.id,
-                    url = %webhook.url,
-                    error = %e,
-                    "Failed to send debit webhook"
-                );
-            }
+        let payload = json!({
+            "id": event.id,
+            "event": "transaction.debited",
+            "version": event.version,
+            "livemode": webhook.livemode,
+            "message": "Amount has been debited from your account",
+            "data": event.data,
+            "timestamp": event.created_at.to_rfc3339(),
+        });
+
+        self.enqueue(QueuedDelivery {
+            webhook,
+            origin: TraceOrigin::current(),
+            kind: DeliveryKind::Event {
+                payload,
+                event_type: "transaction.debited",
+            },
         });
     }
 
     /// Dispatch webhook for credit transaction (amount credited to account)
-    pub fn dispatch_credit_webhook(&self, webhook: Webhook, transaction: Transaction) {
-        let client = self.client.clone();
-        let webhook = webhook.clone();
-        let transaction = transaction.clone();
-
-        // Spawn async task (fire and forget)
-        tokio::spawn(async move {
-            let payload = json!({
-                "event": "transaction.credited",
-                "message": "Amount has been credited to your account",
-                "data": {
-                    "transaction_id": transaction.id,
-                    "amount": transaction.amount,
-                    "currency": transaction.currency,
-                    "description": transaction.description,
-                    "parent_tx_key": transaction.parent_tx_key,
-                },
-                "timestamp": chrono::Utc::now().to_rfc3339(),
+    /// The payload carries the logged event's id so receivers can deduplicate
+    pub fn dispatch_credit_webhook(&self, webhook: Webhook, event: Event) {
+        if !webhook.subscribes_to("transaction.credited") {
+            return;
+        }
+
+        let payload = json!({
+            "id": event.id,
+            "event": "transaction.credited",
+            "version": event.version,
+            "livemode": webhook.livemode,
+            "message": "Amount has been credited to your account",
+            "data": event.data,
+            "timestamp": event.created_at.to_rfc3339(),
+        });
+
+        self.enqueue(QueuedDelivery {
+            webhook,
+            origin: TraceOrigin::current(),
+            kind: DeliveryKind::Event {
+                payload,
+                event_type: "transaction.credited",
+            },
+        });
+    }
+
+    /// Redeliver a previously logged delivery
+    /// The stored payload is sent unchanged, signed with the webhook's current secret
+    pub fn redeliver(&self, webhook: Webhook, delivery: WebhookDelivery) {
+        tracing::info!(
+            webhook_id = %webhook.id,
+            original_delivery_id = %delivery.id,
+            event = %delivery.event_type,
+            "Queueing webhook redelivery"
+        );
+
+        self.enqueue(QueuedDelivery {
+            webhook,
+            origin: TraceOrigin::current(),
+            kind: DeliveryKind::Redeliver(delivery),
+        });
+    }
+
+    /// Redrive a delivery taken out of the dead-letter queue
+    /// Attempts are recorded on the original delivery row, which has been reset to `pending`
+    pub fn redrive(&self, webhook: Webhook, delivery: WebhookDelivery) {
+        tracing::info!(
+            webhook_id = %webhook.id,
+            delivery_id = %delivery.id,
+            event = %delivery.event_type,
+            "Queueing dead-lettered webhook delivery for redrive"
+        );
+
+        Self::claim(delivery.id);
+        self.enqueue(QueuedDelivery {
+            webhook,
+            origin: TraceOrigin::current(),
+            kind: DeliveryKind::Redrive(delivery),
+        });
+    }
+
+    /// Put pending deliveries whose retry is due back on their webhook's queue, every few
+    /// seconds for the lifetime of the process
+    ///
+    /// Retries live in the database, so deliveries interrupted by a restart are picked up
+    /// here too, once their lease has run out.
+    pub async fn retry_due_deliveries_periodically(self) {
+        let mut interval = tokio::time::interval(RETRY_SWEEP_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.retry_due_deliveries().await;
+        }
+    }
+
+    /// Claim the deliveries whose retry is due and queue them
+    async fn retry_due_deliveries(&self) {
+        let tracker = match POOL_STATE_TRACKER.get() {
+            Some(t) => t,
+            None => {
+                tracing::error!("Failed to get pool tracker for webhook retries");
+                return;
+            }
+        };
+
+        let mut conn = match tracker.get_connection().await {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to get connection for webhook retries");
+                return;
+            }
+        };
+
+        let deliveries = match claim_due_deliveries(RETRY_SWEEP_BATCH, &mut conn).await {
+            Ok(deliveries) => deliveries,
+            Err(_) => {
+                tracker.return_connection(conn);
+                return;
+            }
+        };
+
+        let mut webhooks: HashMap<Uuid, Option<Webhook>> = HashMap::new();
+        for delivery in deliveries {
+            // Still queued from an earlier claim; the new lease only keeps it hidden longer
+            if !Self::claim(delivery.id) {
+                continue;
+            }
+
+            let webhook = match webhooks.entry(delivery.webhook_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    entry.insert(get_webhook(delivery.webhook_id, &mut conn).await.ok())
+                }
+            };
+            let Some(webhook) = webhook.clone() else {
+                Self::release(delivery.id);
+                continue;
+            };
+
+            tracing::debug!(
+                webhook_id = %webhook.id,
+                delivery_id = %delivery.id,
+                attempt_count = ?delivery.attempt_count,
+                "Queueing due webhook retry"
+            );
+            self.enqueue(QueuedDelivery {
+                webhook,
+                origin: TraceOrigin::current(),
+                kind: DeliveryKind::Retry(delivery),
             });
+        }
 
-            if let Err(e) =
-                Self::send_webhook(&client, &webhook, &payload, "transaction.credited").await
-            {
-                tracing::error!(
-                    webhook_id = %webhook.id,
-                    url = %webhook.url,
-                    error = %e,
-                    "Failed to send credit webhook"
-                );
+        tracker.return_connection(conn);
+    }
+
+    /// Mark an existing delivery row as queued in this process
+    /// Returns false when it already was
+    fn claim(delivery_id: Uuid) -> bool {
+        CLAIMED_DELIVERIES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(delivery_id)
+    }
+
+    /// Forget a delivery row once its attempt is over
+    fn release(delivery_id: Uuid) {
+        CLAIMED_DELIVERIES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&delivery_id);
+    }
+
+    /// Push a delivery onto its webhook's queue, starting the queue worker if needed
+    ///
+    /// Never waits: when the queue is full the delivery is dead-lettered instead, so a
+    /// slow receiver cannot make work pile up in memory or hold up the caller.
+    fn enqueue(&self, job: QueuedDelivery) {
+        let webhook_id = job.webhook.id;
+        let mut queues = DELIVERY_QUEUES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let sender = queues.entry(webhook_id).or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+            tokio::spawn(Self::run_queue(self.client.clone(), webhook_id, receiver));
+            sender
+        });
+
+        let rejected = match sender.try_send(job) {
+            Ok(()) => return,
+            Err(TrySendError::Full(job)) => job,
+            Err(TrySendError::Closed(job)) => {
+                // The worker died; the next delivery starts a fresh one
+                queues.remove(&webhook_id);
+                job
             }
+        };
+        drop(queues);
+
+        tracing::warn!(
+            webhook_id = %webhook_id,
+            capacity = QUEUE_CAPACITY,
+            "Webhook delivery queue is full, dead-lettering delivery"
+        );
+        let span = Self::delivery_span(&rejected);
+        tokio::spawn(Self::shed(rejected).instrument(span));
+    }
+
+    /// Drain one webhook's queue, keeping at most `max_concurrency` deliveries in flight
+    ///
+    /// Jobs are prepared one at a time in queue order, so sequence numbers follow the
+    /// order events were dispatched in.
+    async fn run_queue(
+        client: reqwest::Client,
+        webhook_id: Uuid,
+        mut receiver: mpsc::Receiver<QueuedDelivery>,
+    ) {
+        let mut limit = 0;
+        let mut slots = Arc::new(Semaphore::new(0));
+
+        loop {
+            let job = match tokio::time::timeout(QUEUE_IDLE_TIMEOUT, receiver.recv()).await {
+                Ok(Some(job)) => job,
+                Ok(None) => break,
+                Err(_) => {
+                    // Keep the queue while deliveries are still in flight so a new worker
+                    // cannot exceed the concurrency limit
+                    if slots.available_permits() < limit {
+                        continue;
+                    }
+                    let mut queues = DELIVERY_QUEUES
+                        .get_or_init(Default::default)
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner());
+                    match receiver.try_recv() {
+                        Ok(job) => job,
+                        Err(_) => {
+                            queues.remove(&webhook_id);
+                            break;
+                        }
+                    }
+                }
+            };
+
+            let wanted = job
+                .webhook
+                .max_concurrency
+                .clamp(1, MAX_DELIVERY_CONCURRENCY) as usize;
+            if wanted != limit {
+                // Let in-flight deliveries finish so the old and new limits never overlap
+                let _ = slots.acquire_many(limit as u32).await;
+                slots = Arc::new(Semaphore::new(wanted));
+                limit = wanted;
+            }
+
+            let Ok(permit) = slots.clone().acquire_owned().await else {
+                break;
+            };
+
+            let span = Self::delivery_span(&job);
+            let prepared = Self::prepare(&job).instrument(span.clone()).await;
+            span.record("event_type", prepared.event_type.as_str());
+            if let Some(delivery_id) = prepared.delivery_id {
+                span.record("delivery_id", field::display(delivery_id));
+            }
+
+            let client = client.clone();
+            tokio::spawn(
+                async move {
+                    let record =
+                        Self::run_attempt(&client, &job.webhook, &prepared, "dead_lettered").await;
+                    drop(permit);
+                    if let Some(delivery_id) = prepared.delivery_id {
+                        Self::release(delivery_id);
+                    }
+
+                    if !record.success {
+                        tracing::error!(
+                            webhook_id = %job.webhook.id,
+                            url = %job.webhook.url,
+                            event = %prepared.event_type,
+                            error = ?record.error_message,
+                            status = ?record.http_status_code,
+                            "Failed to send webhook"
+                        );
+                    }
+                }
+                .instrument(span),
+            );
+        }
+
+        tracing::debug!(webhook_id = %webhook_id, "Webhook delivery queue closed");
+    }
+
+    /// Span covering one delivery and all of its attempts
+    ///
+    /// It is not nested under the span that queued the delivery, which has usually
+    /// ended by the time retries run, but parented to it in the exported trace.
+    fn delivery_span(job: &QueuedDelivery) -> tracing::Span {
+        let span = tracing::info_span!(
+            parent: None,
+            "webhook.delivery",
+            webhook_id = %job.webhook.id,
+            request_id = field::Empty,
+            delivery_id = field::Empty,
+            event_type = field::Empty,
+        );
+        if let Some(request_id) = job.origin.request_id {
+            span.record("request_id", field::display(request_id));
+        }
+        job.origin.adopt(&span);
+        span
+    }
+
+    /// Create the delivery row for a dequeued job, assigning new events their sequence
+    async fn prepare(job: &QueuedDelivery) -> PreparedDelivery {
+        let max_attempts = job
+            .webhook
+            .max_retries
+            .unwrap_or(DEFAULT_MAX_RETRIES)
+            .max(0)
+            + 1;
+
+        match &job.kind {
+            DeliveryKind::Event {
+                payload,
+                event_type,
+            } => {
+                let (delivery_id, payload) = Self::create_delivery(
+                    job.webhook.id,
+                    payload,
+                    event_type,
+                    max_attempts,
+                    true,
+                    true,
+                )
+                .await;
+                PreparedDelivery {
+                    delivery_id,
+                    payload,
+                    event_type: event_type.to_string(),
+                    attempt_count: 0,
+                    max_attempts,
+                }
+            }
+            DeliveryKind::Redeliver(delivery) => {
+                let (delivery_id, payload) = Self::create_delivery(
+                    job.webhook.id,
+                    &delivery.payload,
+                    &delivery.event_type,
+                    max_attempts,
+                    false,
+                    true,
+                )
+                .await;
+                PreparedDelivery {
+                    delivery_id,
+                    payload,
+                    event_type: delivery.event_type.clone(),
+                    attempt_count: 0,
+                    max_attempts,
+                }
+            }
+            DeliveryKind::Redrive(delivery) | DeliveryKind::Retry(delivery) => PreparedDelivery {
+                delivery_id: Some(delivery.id),
+                payload: delivery.payload.clone(),
+                event_type: delivery.event_type.clone(),
+                attempt_count: delivery.attempt_count.unwrap_or(0).max(0),
+                max_attempts: delivery
+                    .max_attempts
+                    .unwrap_or(DEFAULT_MAX_RETRIES + 1)
+                    .max(1),
+            },
+        }
+    }
+
+    /// Dead-letter a delivery that did not fit in its queue, without attempting it
+    /// It can be redriven once the receiver has caught up
+    async fn shed(job: QueuedDelivery) {
+        let prepared = Self::prepare(&job).await;
+        let record = AttemptRecord::error("Webhook delivery queue is full".to_string(), 0);
+
+        if let Some(delivery_id) = prepared.delivery_id {
+            Self::log_delivery(
+                delivery_id,
+                prepared.attempt_count,
+                "dead_lettered",
+                &record,
+                None,
+            )
+            .await;
+            Self::release(delivery_id);
+        }
+        Self::record_failure_event(
+            &job.webhook,
+            prepared.delivery_id,
+            &prepared.payload,
+            prepared.attempt_count,
+            &record,
+        )
+        .await;
+    }
+
+    /// Send a signed `webhook.ping` event and wait for the receiver's answer
+    /// Used by the test endpoint so integrators can check reachability on demand
+    pub async fn send_ping(&self, webhook: &Webhook) -> Result<DeliveryAttempt, String> {
+        let payload = json!({
+            "event": "webhook.ping",
+            "message": "Test event sent from the webhook test endpoint",
+            "data": {
+                "webhook_id": webhook.id,
+            },
+            "timestamp": chrono::Utc::now().to_rfc3339(),
         });
+
+        // Pings are attempted once, never dead-lettered and not sequenced
+        let (delivery_id, payload) =
+            Self::create_delivery(webhook.id, &payload, "webhook.ping", 1, false, false).await;
+        let prepared = PreparedDelivery {
+            delivery_id,
+            payload,
+            event_type: "webhook.ping".to_string(),
+            attempt_count: 0,
+            max_attempts: 1,
+        };
+        let record = Self::run_attempt(&self.client, webhook, &prepared, "failed").await;
+
+        match record.http_status_code {
+            Some(status_code) => Ok(DeliveryAttempt {
+                status_code: status_code as u16,
+                latency_ms: record.latency_ms,
+                success: record.success,
+            }),
+            None => Err(record.error_message.unwrap_or_default()),
+        }
     }
 
+    /// Run the verification handshake against a webhook endpoint
+    ///
+    /// A random challenge is POSTed as a signed `webhook.verification` event.
+    /// The endpoint passes if it answers 2xx and echoes the challenge, either as
+    /// the raw body or as `{"challenge": "..."}`.
+    pub async fn verify_endpoint(&self, webhook: &Webhook) -> Result<(), String> {
+        let challenge = hex::encode(rand::random::<[u8; 16]>());
+        let payload = json!({
+            "event": "webhook.verification",
+            "message": "Echo the challenge to verify this endpoint",
+            "data": {
+                "webhook_id": webhook.id,
+                "challenge": challenge,
+            },
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        let payload_str = payload.to_string();
+
+        tracing::info!(
+            webhook_id = %webhook.id,
+            url = %webhook.url,
+            "Verifying webhook endpoint"
+        );
+
+        validate_webhook_url(&webhook.url)?;
+
+        let request = Self::with_custom_headers(self.client.post(&webhook.url), webhook);
+        let response =
+            Self::with_signature(Self::with_trace_context(request), webhook, &payload_str)?
+                .header("Content-Type", "application/json")
+                .header("X-Webhook-Event", "webhook.verification")
+                .body(payload_str)
+                .send()
+                .await
+                .map_err(|e| format!("Endpoint unreachable: {}", Self::describe_error(&e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!(
+                "Endpoint responded with status {}",
+                status.as_u16()
+            ));
+        }
+
+        let body = Self::read_body_capped(response)
+            .await
+            .map_err(|e| format!("Failed to read endpoint response: {}", e))?;
+
+        let echoed = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|v| v["challenge"].as_str().map(str::to_string))
+            .unwrap_or_else(|| body.trim().to_string());
+
+        if echoed != challenge {
+            return Err("Endpoint did not echo the verification challenge".to_string());
+        }
+
+        tracing::info!(webhook_id = %webhook.id, "Webhook endpoint verified");
+        Ok(())
+    }
+
+    /// Make the next attempt of a delivery and record its outcome
+    ///
+    /// A failed attempt with attempts left is scheduled for retry through `next_retry_at`
+    /// instead of being waited for. `exhausted_status` is recorded when the last attempt fails.
+    async fn run_attempt(
+        client: &reqwest::Client,
+        webhook: &Webhook,
+        delivery: &PreparedDelivery,
+        exhausted_status: &str,
+    ) -> AttemptRecord {
+        let attempt = delivery.attempt_count + 1;
+        let span = tracing::info_span!(
+            "webhook.attempt",
+            otel.kind = "client",
+            http.request.method = "POST",
+            url.full = %webhook.url,
+            attempt,
+            http.response.status_code = field::Empty,
+            latency_ms = field::Empty,
+            otel.status_code = field::Empty,
+        );
+        let record = Self::send_webhook(client, webhook, &delivery.payload, &delivery.event_type)
+            .instrument(span.clone())
+            .await;
+        if let Some(status_code) = record.http_status_code {
+            span.record("http.response.status_code", status_code);
+        }
+        span.record("latency_ms", record.latency_ms);
+        if !record.success {
+            span.record("otel.status_code", "ERROR");
+        }
+
+        // A short-circuited attempt is not counted; it is made again once the breaker
+        // lets traffic through. Pings report the open breaker instead of waiting.
+        if let Some(wait) = record.short_circuited {
+            if exhausted_status == "dead_lettered" {
+                if let Some(delivery_id) = delivery.delivery_id {
+                    Self::log_delivery(
+                        delivery_id,
+                        delivery.attempt_count,
+                        "pending",
+                        &record,
+                        Some(wait),
+                    )
+                    .await;
+                }
+                return record;
+            }
+        }
+
+        let exhausted = !record.success && attempt >= delivery.max_attempts;
+
+        let retry_delay = if record.success || exhausted {
+            None
+        } else {
+            let base_delay_ms = webhook
+                .retry_backoff_seconds
+                .unwrap_or(DEFAULT_RETRY_BACKOFF_SECONDS)
+                .max(1) as u64
+                * 1000;
+            let mut backoff = ExponentialBackoff::new();
+            backoff
+                .set_base_delay_ms(base_delay_ms)
+                .set_max_delay_ms(MAX_RETRY_DELAY_MS.max(base_delay_ms));
+            Some(Duration::from_millis(
+                backoff.calculate((attempt - 1) as u32),
+            ))
+        };
+
+        let status = if record.success {
+            "delivered"
+        } else if exhausted {
+            exhausted_status
+        } else {
+            "pending"
+        };
+
+        if let Some(delivery_id) = delivery.delivery_id {
+            Self::log_delivery(delivery_id, attempt, status, &record, retry_delay).await;
+        }
+
+        if let Some(delay) = retry_delay {
+            tracing::info!(
+                webhook_id = %webhook.id,
+                attempt = attempt,
+                max_attempts = delivery.max_attempts,
+                retry_in_ms = delay.as_millis() as u64,
+                "Scheduling webhook retry"
+            );
+        } else if exhausted && exhausted_status == "dead_lettered" {
+            tracing::warn!(
+                webhook_id = %webhook.id,
+                delivery_id = ?delivery.delivery_id,
+                attempts = attempt,
+                "Webhook delivery exhausted its retries and was dead-lettered"
+            );
+            Self::record_failure_event(
+                webhook,
+                delivery.delivery_id,
+                &delivery.payload,
+                attempt,
+                &record,
+            )
+            .await;
+        }
+
+        record
+    }
+
+    /// Perform a single signed HTTP attempt against the webhook endpoint
     async fn send_webhook(
         client: &reqwest::Client,
         webhook: &Webhook,
         payload: &serde_json::Value,
         event_type: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let payload_str = serde_json::to_string(payload)?;
-
-        // Generate HMAC signature
-        let signature = Self::generate_signature(&payload_str, &webhook.secret);
+    ) -> AttemptRecord {
+        let payload_str = payload.to_string();
 
         tracing::info!(
             webhook_id = %webhook.id,
@@ -111,40 +829,77 @@ impl WebhookDispatcher {
             "Sending webhook"
         );
 
-        // Extract transaction_id from payload if present
-        let transaction_id = payload["data"]["transaction_id"]
-            .as_str()
-            .and_then(|s| Uuid::parse_str(s).ok());
+        // Stored URLs predating the egress checks are re-validated before every attempt
+        let host = match validate_webhook_url(&webhook.url) {
+            Ok(url) => url.host_str().unwrap_or_default().to_string(),
+            Err(reason) => {
+                tracing::warn!(
+                    webhook_id = %webhook.id,
+                    reason = %reason,
+                    "Webhook destination rejected"
+                );
+
+                return AttemptRecord::error(reason, 0);
+            }
+        };
+
+        let request = Self::with_custom_headers(client.post(&webhook.url), webhook);
+        let request = match Self::with_signature(request, webhook, &payload_str) {
+            Ok(request) => request,
+            Err(reason) => {
+                tracing::error!(
+                    webhook_id = %webhook.id,
+                    reason = %reason,
+                    "Failed to sign webhook"
+                );
+                return AttemptRecord::error(reason, 0);
+            }
+        };
+
+        let request = Self::with_trace_context(request);
+
+        if let Err(wait) = circuit_breakers().try_acquire(&host) {
+            tracing::info!(
+                webhook_id = %webhook.id,
+                host = %host,
+                retry_in_ms = wait.as_millis() as u64,
+                "Circuit breaker open, short-circuiting webhook attempt"
+            );
+
+            return AttemptRecord {
+                short_circuited: Some(wait),
+                ..AttemptRecord::error(
+                    format!("Circuit breaker open for {}, attempt not sent", host),
+                    0,
+                )
+            };
+        }
 
         // Send HTTP POST
-        let response_result = client
-            .post(&webhook.url)
+        let started_at = std::time::Instant::now();
+        let response_result = request
             .header("Content-Type", "application/json")
-            .header("X-Webhook-Signature", signature)
             .header("X-Webhook-Event", event_type)
-            .body(payload_str.clone())
+            .body(payload_str)
             .send()
             .await;
+        let latency_ms = started_at.elapsed().as_millis() as u64;
+
+        // Only unreachable or overloaded endpoints count against the destination
+        let breaker_failure = match &response_result {
+            Ok(response) => {
+                response.status().is_server_error()
+                    || response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(_) => true,
+        };
+        circuit_breakers().record(&host, breaker_failure);
 
-        // Log delivery attempt
         match response_result {
             Ok(response) => {
                 let status_code = response.status().as_u16() as i32;
                 let is_success = response.status().is_success();
-                let response_body = response.text().await.ok();
-
-                // Log to database
-                Self::log_delivery(
-                    webhook.id,
-                    transaction_id,
-                    event_type,
-                    payload.clone(),
-                    if is_success { "delivered" } else { "failed" },
-                    Some(status_code),
-                    response_body.as_deref(),
-                    None,
-                )
-                .await;
+                let response_body = Self::read_body_capped(response).await.ok();
 
                 if is_success {
                     tracing::info!(
@@ -160,48 +915,39 @@ impl WebhookDispatcher {
                     );
                 }
 
-                Ok(())
+                AttemptRecord {
+                    http_status_code: Some(status_code),
+                    response_body,
+                    error_message: None,
+                    latency_ms,
+                    success: is_success,
+                    short_circuited: None,
+                }
             }
             Err(e) => {
-                // Log failed delivery
-                Self::log_delivery(
-                    webhook.id,
-                    transaction_id,
-                    event_type,
-                    payload.clone(),
-                    "failed",
-                    None,
-                    None,
-                    Some(&e.to_string()),
-                )
-                .await;
-
                 tracing::error!(
                     webhook_id = %webhook.id,
                     error = %e,
                     "Webhook delivery error"
                 );
 
-                Err(Box::new(e))
+                AttemptRecord::error(Self::describe_error(&e), latency_ms)
             }
         }
     }
 
-    /// Log webhook delivery attempt to database
-    async fn log_delivery(
-        webhook_id: Uuid,
-        transaction_id: Option<Uuid>,
-        event_type: &str,
-        payload: serde_json::Value,
-        status: &str,
-        http_status_code: Option<i32>,
-        response_body: Option<&str>,
-        error_message: Option<&str>,
+    /// Log a `webhook.failed` event for a dead-lettered delivery and publish it to streams
+    async fn record_failure_event(
+        webhook: &Webhook,
+        delivery_id: Option<Uuid>,
+        payload: &serde_json::Value,
+        attempts: i32,
+        record: &AttemptRecord,
     ) {
         let tracker = match POOL_STATE_TRACKER.get() {
             Some(t) => t,
             None => {
-                tracing::error!("Failed to get pool tracker for delivery logging");
+                tracing::error!("Failed to get pool tracker for webhook failure event");
                 return;
             }
         };
@@ -209,43 +955,174 @@ impl WebhookDispatcher {
         let mut conn = match tracker.get_connection().await {
             Ok(c) => c,
             Err(e) => {
-                tracing::error!(error = %e, "Failed to get connection for delivery logging");
+                tracing::error!(error = %e, "Failed to get connection for webhook failure event");
                 return;
             }
         };
 
-        let delivered_at = if status == "delivered" {
-            Some(chrono::Utc::now())
-        } else {
-            None
+        let data = json!({
+            "webhook_id": webhook.id,
+            "delivery_id": delivery_id,
+            "event_id": payload.get("id"),
+            "event_type": payload.get("event"),
+            "attempts": attempts,
+            "http_status_code": record.http_status_code,
+            "error_message": record.error_message,
+        });
+
+        let result = create_event(webhook.account_id, "webhook.failed", data, &mut conn).await;
+        tracker.return_connection(conn);
+
+        if let Ok(event) = result {
+            publish_events(&[event]).await;
+        }
+    }
+
+    /// Insert the delivery row that all attempts of one delivery are recorded on
+    ///
+    /// With `sequenced`, the webhook's next sequence number is taken in the same
+    /// statement and added to the payload as `sequence`. With `leased`, the row starts
+    /// out leased so the retry sweeper resumes it if the process dies before the first
+    /// attempt is recorded. Returns the row id, if it could be created, and the payload
+    /// to send.
+    async fn create_delivery(
+        webhook_id: Uuid,
+        payload: &serde_json::Value,
+        event_type: &str,
+        max_attempts: i32,
+        sequenced: bool,
+        leased: bool,
+    ) -> (Option<Uuid>, serde_json::Value) {
+        let tracker = match POOL_STATE_TRACKER.get() {
+            Some(t) => t,
+            None => {
+                tracing::error!("Failed to get pool tracker for delivery logging");
+                return (None, payload.clone());
+            }
         };
 
-        let failed_at = if status == "failed" {
-            Some(chrono::Utc::now())
-        } else {
-            None
+        let mut conn = match tracker.get_connection().await {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to get connection for delivery logging");
+                return (None, payload.clone());
+            }
         };
 
-        let result = sqlx::query(
+        // Extract transaction_id from payload if present
+        let transaction_id = payload["data"]["transaction_id"]
+            .as_str()
+            .and_then(|s| Uuid::parse_str(s).ok());
+        let event_id = payload["id"].as_str().and_then(|s| Uuid::parse_str(s).ok());
+
+        let result = sqlx::query_as::<_, (Uuid, serde_json::Value)>(
             r#"
+            WITH next AS (
+                UPDATE webhooks
+                SET delivery_sequence = delivery_sequence + 1
+                WHERE id = $1 AND $7
+                RETURNING delivery_sequence
+            )
             INSERT INTO webhook_deliveries (
-                webhook_id, transaction_id, event_type, payload, status,
-                attempt_count, http_status_code, response_body, error_message,
-                delivered_at, failed_at
+                webhook_id, transaction_id, event_id, event_type, payload, sequence, status,
+                attempt_count, max_attempts, next_retry_at
             )
-            VALUES ($1, $2, $3, $4, $5, 1, $6, $7, $8, $9, $10)
+            SELECT $1, $2, $3, $4,
+                   CASE
+                       WHEN next.delivery_sequence IS NULL THEN $5
+                       ELSE $5 || jsonb_build_object('sequence', next.delivery_sequence)
+                   END,
+                   next.delivery_sequence, 'pending', 0, $6,
+                   CASE WHEN $8 THEN NOW() + $9 * INTERVAL '1 second' END
+            FROM (SELECT 1) AS one
+            LEFT JOIN next ON TRUE
+            RETURNING id, payload
             "#,
         )
         .bind(webhook_id)
         .bind(transaction_id)
+        .bind(event_id)
         .bind(event_type)
         .bind(payload)
+        .bind(max_attempts)
+        .bind(sequenced)
+        .bind(leased)
+        .bind(DELIVERY_LEASE_SECONDS)
+        .fetch_one(&mut *conn)
+        .await;
+
+        tracker.return_connection(conn);
+
+        match result {
+            Ok((id, payload)) => (Some(id), payload),
+            Err(e) => {
+                tracing::error!(
+                    error = %e,
+                    webhook_id = %webhook_id,
+                    "Failed to create webhook delivery"
+                );
+                (None, payload.clone())
+            }
+        }
+    }
+
+    /// Log webhook delivery attempt to database
+    async fn log_delivery(
+        delivery_id: Uuid,
+        attempt_count: i32,
+        status: &str,
+        record: &AttemptRecord,
+        retry_delay: Option<Duration>,
+    ) {
+        let tracker = match POOL_STATE_TRACKER.get() {
+            Some(t) => t,
+            None => {
+                tracing::error!("Failed to get pool tracker for delivery logging");
+                return;
+            }
+        };
+
+        let mut conn = match tracker.get_connection().await {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to get connection for delivery logging");
+                return;
+            }
+        };
+
+        let now = chrono::Utc::now();
+        let next_retry_at = retry_delay
+            .and_then(|delay| chrono::Duration::from_std(delay).ok())
+            .map(|delay| now + delay);
+        let delivered_at = (status == "delivered").then_some(now);
+        let failed_at = matches!(status, "failed" | "dead_lettered").then_some(now);
+        let dead_lettered_at = (status == "dead_lettered").then_some(now);
+
+        let result = sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempt_count = $2,
+                status = $3,
+                http_status_code = $4,
+                response_body = $5,
+                error_message = $6,
+                next_retry_at = $7,
+                delivered_at = $8,
+                failed_at = $9,
+                dead_lettered_at = $10
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery_id)
+        .bind(attempt_count)
         .bind(status)
-        .bind(http_status_code)
-        .bind(response_body)
-        .bind(error_message)
+        .bind(record.http_status_code)
+        .bind(record.response_body.as_deref())
+        .bind(record.error_message.as_deref())
+        .bind(next_retry_at)
         .bind(delivered_at)
         .bind(failed_at)
+        .bind(dead_lettered_at)
         .execute(&mut *conn)
         .await;
 
@@ -254,7 +1131,7 @@ impl WebhookDispatcher {
         match result {
             Ok(_) => {
                 tracing::debug!(
-                    webhook_id = %webhook_id,
+                    delivery_id = %delivery_id,
                     status = %status,
                     "Webhook delivery logged"
                 );
@@ -262,13 +1139,99 @@ impl WebhookDispatcher {
             Err(e) => {
                 tracing::error!(
                     error = %e,
-                    webhook_id = %webhook_id,
+                    delivery_id = %delivery_id,
                     "Failed to log webhook delivery"
                 );
             }
         }
     }
 
+    /// Attach the webhook's custom static headers
+    /// Reserved names (Content-Type, X-Webhook-*) are rejected when the webhook is configured
+    fn with_custom_headers(
+        request: reqwest::RequestBuilder,
+        webhook: &Webhook,
+    ) -> reqwest::RequestBuilder {
+        let mut request = request;
+        if let Some(headers) = webhook.headers.as_object() {
+            for (name, value) in headers {
+                if let Some(value) = value.as_str() {
+                    request = request.header(name.as_str(), value);
+                }
+            }
+        }
+        request
+    }
+
+    /// Sign the payload with the webhook's algorithm
+    ///
+    /// `hmac-sha256` uses the webhook's secret, decrypted just for this signature;
+    /// `ed25519` uses the platform's active key and names it in `X-Webhook-Key-Id`
+    /// so receivers can pick the public key from the published key set.
+    fn with_signature(
+        request: reqwest::RequestBuilder,
+        webhook: &Webhook,
+        payload: &str,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        match webhook.signing_algorithm.as_str() {
+            "ed25519" => {
+                let (kid, signature) = signing_keys()
+                    .sign(payload.as_bytes())
+                    .ok_or_else(|| "No Ed25519 webhook signing key is configured".to_string())?;
+                Ok(request
+                    .header("X-Webhook-Signature", signature)
+                    .header("X-Webhook-Signature-Alg", "ed25519")
+                    .header("X-Webhook-Key-Id", kid))
+            }
+            _ => {
+                let secret =
+                    secret_keys().decrypt(&webhook.secret_key_id, &webhook.encrypted_secret)?;
+                Ok(request
+                    .header(
+                        "X-Webhook-Signature",
+                        Self::generate_signature(payload, &secret),
+                    )
+                    .header("X-Webhook-Signature-Alg", "hmac-sha256"))
+            }
+        }
+    }
+
+    /// Add W3C trace context headers so receivers can continue the trace
+    fn with_trace_context(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        trace_context_headers()
+            .into_iter()
+            .fold(request, |request, (name, value)| {
+                request.header(name, value)
+            })
+    }
+
+    /// Read at most `MAX_RESPONSE_BODY_BYTES` of a response body
+    /// The rest of the body is never buffered, so a hostile endpoint cannot exhaust memory
+    async fn read_body_capped(mut response: reqwest::Response) -> Result<String, reqwest::Error> {
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            let remaining = MAX_RESPONSE_BODY_BYTES - body.len();
+            if chunk.len() >= remaining {
+                body.extend_from_slice(&chunk[..remaining]);
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Render an error with its sources, so resolver and redirect rejections are visible
+    fn describe_error(error: &dyn std::error::Error) -> String {
+        let mut message = error.to_string();
+        let mut source = error.source();
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        message
+    }
+
     fn generate_signature(payload: &str, secret: &str) -> String {
         let mut mac =
             HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
@@ -0,0 +1,473 @@
+use crate::{
+    datalayer::{
+        CRUD::{
+            api_key::{get_api_keys_with_other_signing_key, update_api_key_signing_secret},
+            webhook::{get_webhooks_with_other_secret_key, update_webhook_secret},
+        },
+        db_ops::constants::POOL_STATE_TRACKER,
+    },
+    services::api_key_cache::invalidate_api_keys,
+};
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore},
+};
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+/// Prefix of generated webhook secrets
+const SECRET_PREFIX: &str = "whsec_";
+
+/// Prefix of generated API key signing secrets
+const SIGNING_SECRET_PREFIX: &str = "dodo_sig_";
+
+/// Length of an AES-GCM nonce
+const NONCE_LEN: usize = 12;
+
+/// Length of a wrapped data key: the 32-byte key plus the 16-byte tag
+const WRAPPED_KEY_LEN: usize = 48;
+
+/// Webhooks re-wrapped per database round trip
+const REWRAP_BATCH_SIZE: i64 = 100;
+
+/// Master keys, loaded from the environment on first use
+static SECRET_KEYS: OnceLock<SecretKeys> = OnceLock::new();
+
+/// A webhook secret as stored in the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedSecret {
+    /// Id of the master key that wrapped the data key
+    pub key_id: String,
+    /// Base64 of `key nonce | wrapped data key | secret nonce | encrypted secret`
+    pub ciphertext: String,
+}
+
+/// Master keys that encrypt webhook secrets and API key signing secrets at rest
+///
+/// Secrets use envelope encryption: each one is encrypted with its own random data
+/// key, and the data key is wrapped with a master key. Master keys come from
+/// `WEBHOOK_SECRET_KEYS` as comma-separated `kid:base64-key` pairs of 32-byte keys;
+/// `WEBHOOK_SECRET_KEY_ID` picks the one new secrets are wrapped with (default:
+/// the first). Rotating a master key only re-wraps data keys, see `rewrap`.
+pub struct SecretKeys {
+    keys: Vec<(String, Key<Aes256Gcm>)>,
+    active: Option<usize>,
+}
+
+impl SecretKeys {
+    /// Parse a key list; malformed entries are skipped with a warning
+    pub fn parse(spec: &str, active_kid: Option<&str>) -> Self {
+        let keys: Vec<(String, Key<Aes256Gcm>)> = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let parsed = entry.split_once(':').and_then(|(kid, key)| {
+                    let key: [u8; 32] = STANDARD.decode(key.trim()).ok()?.try_into().ok()?;
+                    Some((kid.trim().to_string(), Key::<Aes256Gcm>::from(key)))
+                });
+                if parsed.is_none() {
+                    tracing::warn!("Ignoring malformed entry in WEBHOOK_SECRET_KEYS");
+                }
+                parsed
+            })
+            .collect();
+
+        let active = match active_kid {
+            Some(kid) => keys.iter().position(|(k, _)| k == kid),
+            None => (!keys.is_empty()).then_some(0),
+        };
+        if active.is_none() {
+            tracing::warn!(
+                kid = ?active_kid,
+                "No active WEBHOOK_SECRET_KEYS entry, webhooks cannot be created"
+            );
+        }
+
+        Self { keys, active }
+    }
+
+    pub fn from_env() -> Self {
+        let spec = std::env::var("WEBHOOK_SECRET_KEYS").unwrap_or_default();
+        let active_kid = std::env::var("WEBHOOK_SECRET_KEY_ID")
+            .ok()
+            .filter(|kid| !kid.is_empty());
+        Self::parse(&spec, active_kid.as_deref())
+    }
+
+    /// Id of the key new secrets are wrapped with
+    pub fn active_key_id(&self) -> Option<&str> {
+        self.active.map(|i| self.keys[i].0.as_str())
+    }
+
+    fn key(&self, key_id: &str) -> Result<Aes256Gcm, String> {
+        self.keys
+            .iter()
+            .find(|(kid, _)| kid == key_id)
+            .map(|(_, key)| Aes256Gcm::new(key))
+            .ok_or_else(|| format!("Webhook secret key '{}' is not configured", key_id))
+    }
+
+    /// Encrypt a secret under a fresh data key wrapped with the active master key
+    pub fn encrypt(&self, secret: &str) -> Result<EncryptedSecret, String> {
+        let key_id = self
+            .active_key_id()
+            .ok_or_else(|| "No webhook secret key is configured".to_string())?;
+        let master = self.key(key_id)?;
+
+        let mut data_key = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(data_key.as_mut_slice());
+        let key_nonce = Aes256Gcm::generate_nonce(OsRng);
+        let wrapped_key = master
+            .encrypt(&key_nonce, data_key.as_slice())
+            .map_err(|_| "Failed to wrap data key".to_string())?;
+
+        let secret_nonce = Aes256Gcm::generate_nonce(OsRng);
+        let encrypted = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(data_key.as_slice()))
+            .encrypt(&secret_nonce, secret.as_bytes())
+            .map_err(|_| "Failed to encrypt secret".to_string())?;
+
+        let blob = [
+            key_nonce.as_slice(),
+            &wrapped_key,
+            secret_nonce.as_slice(),
+            &encrypted,
+        ]
+        .concat();
+
+        Ok(EncryptedSecret {
+            key_id: key_id.to_string(),
+            ciphertext: STANDARD.encode(blob),
+        })
+    }
+
+    /// Decrypt a stored secret
+    pub fn decrypt(&self, key_id: &str, ciphertext: &str) -> Result<Zeroizing<String>, String> {
+        let blob = STANDARD
+            .decode(ciphertext)
+            .map_err(|_| "Malformed webhook secret".to_string())?;
+        let (data_key, rest) = self.unwrap_data_key(key_id, &blob)?;
+        if rest.len() < NONCE_LEN {
+            return Err("Malformed webhook secret".to_string());
+        }
+        let (secret_nonce, encrypted) = rest.split_at(NONCE_LEN);
+
+        let secret = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key))
+            .decrypt(Nonce::from_slice(secret_nonce), encrypted)
+            .map_err(|_| "Failed to decrypt webhook secret".to_string())?;
+
+        String::from_utf8(secret)
+            .map(Zeroizing::new)
+            .map_err(|_| "Malformed webhook secret".to_string())
+    }
+
+    /// Re-wrap a stored secret's data key with the active master key
+    /// Returns `None` when it is already wrapped with the active key
+    pub fn rewrap(
+        &self,
+        key_id: &str,
+        ciphertext: &str,
+    ) -> Result<Option<EncryptedSecret>, String> {
+        let active_id = self
+            .active_key_id()
+            .ok_or_else(|| "No webhook secret key is configured".to_string())?;
+        if key_id == active_id {
+            return Ok(None);
+        }
+
+        let blob = STANDARD
+            .decode(ciphertext)
+            .map_err(|_| "Malformed webhook secret".to_string())?;
+        let (data_key, rest) = self.unwrap_data_key(key_id, &blob)?;
+
+        let key_nonce = Aes256Gcm::generate_nonce(OsRng);
+        let wrapped_key = self
+            .key(active_id)?
+            .encrypt(&key_nonce, data_key.as_slice())
+            .map_err(|_| "Failed to wrap data key".to_string())?;
+
+        Ok(Some(EncryptedSecret {
+            key_id: active_id.to_string(),
+            ciphertext: STANDARD.encode([key_nonce.as_slice(), &wrapped_key, rest].concat()),
+        }))
+    }
+
+    /// Unwrap the data key at the start of a blob, returning it and the rest of the blob
+    fn unwrap_data_key<'a>(
+        &self,
+        key_id: &str,
+        blob: &'a [u8],
+    ) -> Result<(Zeroizing<Vec<u8>>, &'a [u8]), String> {
+        if blob.len() < NONCE_LEN + WRAPPED_KEY_LEN {
+            return Err("Malformed webhook secret".to_string());
+        }
+        let (key_nonce, rest) = blob.split_at(NONCE_LEN);
+        let (wrapped_key, rest) = rest.split_at(WRAPPED_KEY_LEN);
+
+        let data_key = self
+            .key(key_id)?
+            .decrypt(Nonce::from_slice(key_nonce), wrapped_key)
+            .map_err(|_| "Failed to unwrap webhook secret data key".to_string())?;
+
+        Ok((Zeroizing::new(data_key), rest))
+    }
+}
+
+/// Master keys of this process
+pub fn secret_keys() -> &'static SecretKeys {
+    SECRET_KEYS.get_or_init(SecretKeys::from_env)
+}
+
+/// Re-wrap every webhook secret still wrapped with a retired master key
+///
+/// Runs at startup. Only data keys are re-wrapped; the secrets themselves do not
+/// change. Once no row references an old key, it can be removed from
+/// `WEBHOOK_SECRET_KEYS`.
+pub async fn rewrap_webhook_secrets() {
+    let keys = secret_keys();
+    let Some(active_id) = keys.active_key_id() else {
+        return;
+    };
+    let Some(tracker) = POOL_STATE_TRACKER.get() else {
+        tracing::error!("Failed to get pool tracker for webhook secret re-wrap");
+        return;
+    };
+
+    let mut after_id = None;
+    let mut rewrapped = 0;
+    loop {
+        let mut conn = match tracker.get_connection().await {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to get connection for webhook secret re-wrap");
+                return;
+            }
+        };
+
+        let webhooks = match get_webhooks_with_other_secret_key(
+            active_id,
+            after_id,
+            REWRAP_BATCH_SIZE,
+            &mut conn,
+        )
+        .await
+        {
+            Ok(webhooks) if !webhooks.is_empty() => webhooks,
+            _ => {
+                tracker.return_connection(conn);
+                break;
+            }
+        };
+
+        for webhook in &webhooks {
+            match keys.rewrap(&webhook.secret_key_id, &webhook.encrypted_secret) {
+                Ok(Some(encrypted)) => {
+                    if update_webhook_secret(
+                        webhook.id,
+                        &webhook.secret_key_id,
+                        &encrypted.ciphertext,
+                        &encrypted.key_id,
+                        &mut conn,
+                    )
+                    .await
+                    .is_ok()
+                    {
+                        rewrapped += 1;
+                    }
+                }
+                Ok(None) => {}
+                Err(reason) => {
+                    tracing::error!(
+                        webhook_id = %webhook.id,
+                        key_id = %webhook.secret_key_id,
+                        reason = %reason,
+                        "Failed to re-wrap webhook secret"
+                    );
+                }
+            }
+        }
+        tracker.return_connection(conn);
+        after_id = webhooks.last().map(|w| w.id);
+    }
+
+    if rewrapped > 0 {
+        tracing::info!(
+            count = rewrapped,
+            key_id = %active_id,
+            "Re-wrapped webhook secrets with the active key"
+        );
+    }
+}
+
+/// Re-wrap every API key signing secret still wrapped with a retired master key
+///
+/// Runs at startup next to `rewrap_webhook_secrets`; old master keys can only be
+/// removed once both are done.
+pub async fn rewrap_signing_secrets() {
+    let keys = secret_keys();
+    let Some(active_id) = keys.active_key_id() else {
+        return;
+    };
+    let Some(tracker) = POOL_STATE_TRACKER.get() else {
+        tracing::error!("Failed to get pool tracker for signing secret re-wrap");
+        return;
+    };
+
+    let mut after_id = None;
+    let mut rewrapped = 0;
+    loop {
+        let mut conn = match tracker.get_connection().await {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to get connection for signing secret re-wrap");
+                return;
+            }
+        };
+
+        let api_keys = match get_api_keys_with_other_signing_key(
+            active_id,
+            after_id,
+            REWRAP_BATCH_SIZE,
+            &mut conn,
+        )
+        .await
+        {
+            Ok(api_keys) if !api_keys.is_empty() => api_keys,
+            _ => {
+                tracker.return_connection(conn);
+                break;
+            }
+        };
+
+        let mut updated = Vec::new();
+        for api_key in &api_keys {
+            let (Some(key_id), Some(ciphertext)) = (
+                api_key.signing_secret_key_id.as_deref(),
+                api_key.encrypted_signing_secret.as_deref(),
+            ) else {
+                continue;
+            };
+            match keys.rewrap(key_id, ciphertext) {
+                Ok(Some(encrypted)) => {
+                    if let Ok(Some(api_key)) = update_api_key_signing_secret(
+                        api_key.id,
+                        key_id,
+                        &encrypted.ciphertext,
+                        &encrypted.key_id,
+                        &mut conn,
+                    )
+                    .await
+                    {
+                        updated.push(api_key);
+                    }
+                }
+                Ok(None) => {}
+                Err(reason) => {
+                    tracing::error!(
+                        api_key_id = %api_key.id,
+                        key_id = %key_id,
+                        reason = %reason,
+                        "Failed to re-wrap API key signing secret"
+                    );
+                }
+            }
+        }
+        tracker.return_connection(conn);
+
+        // Cached records still hold the secret wrapped with the old key
+        invalidate_api_keys(&updated.iter().collect::<Vec<_>>()).await;
+        rewrapped += updated.len();
+        after_id = api_keys.last().map(|k| k.id);
+    }
+
+    if rewrapped > 0 {
+        tracing::info!(
+            count = rewrapped,
+            key_id = %active_id,
+            "Re-wrapped API key signing secrets with the active key"
+        );
+    }
+}
+
+/// Generate a new API key signing secret: `dodo_sig_` followed by 32 random bytes
+pub fn generate_signing_secret() -> Zeroizing<String> {
+    Zeroizing::new(format!(
+        "{}{}",
+        SIGNING_SECRET_PREFIX,
+        URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+    ))
+}
+
+/// Generate a new webhook secret: `whsec_` followed by 32 random bytes
+pub fn generate_webhook_secret() -> Zeroizing<String> {
+    Zeroizing::new(format!(
+        "{}{}",
+        SECRET_PREFIX,
+        URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> String {
+        STANDARD.encode([byte; 32])
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let keys = SecretKeys::parse(&format!("k1:{}", key(1)), None);
+        let secret = generate_webhook_secret();
+        assert!(secret.starts_with("whsec_"));
+
+        let encrypted = keys.encrypt(&secret).unwrap();
+        assert_eq!(encrypted.key_id, "k1");
+        assert!(!encrypted.ciphertext.contains(secret.as_str()));
+        assert_eq!(
+            keys.decrypt(&encrypted.key_id, &encrypted.ciphertext)
+                .unwrap()
+                .as_str(),
+            secret.as_str()
+        );
+
+        // Any change to the stored value is detected
+        let mut tampered = STANDARD.decode(&encrypted.ciphertext).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(keys.decrypt("k1", &STANDARD.encode(tampered)).is_err());
+        assert!(keys.decrypt("k2", &encrypted.ciphertext).is_err());
+        assert!(SecretKeys::parse("", None).encrypt("whsec_x").is_err());
+    }
+
+    #[test]
+    fn test_rewrap_moves_secret_to_active_key() {
+        let old = SecretKeys::parse(&format!("k1:{}", key(1)), None);
+        let encrypted = old.encrypt("whsec_rotate_me").unwrap();
+
+        let rotated = SecretKeys::parse(&format!("k1:{},k2:{}", key(1), key(2)), Some("k2"));
+        let rewrapped = rotated
+            .rewrap(&encrypted.key_id, &encrypted.ciphertext)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rewrapped.key_id, "k2");
+        assert!(
+            rotated
+                .rewrap("k2", &rewrapped.ciphertext)
+                .unwrap()
+                .is_none()
+        );
+
+        let retired = SecretKeys::parse(&format!("k2:{}", key(2)), None);
+        assert_eq!(
+            retired
+                .decrypt(&rewrapped.key_id, &rewrapped.ciphertext)
+                .unwrap()
+                .as_str(),
+            "whsec_rotate_me"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use base64::{
+    Engine,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::json;
+use std::sync::OnceLock;
+
+/// Signing algorithms a webhook can use
+pub const SIGNING_ALGORITHMS: &[&str] = &["hmac-sha256", "ed25519"];
+
+/// Platform keys, loaded from the environment on first use
+static SIGNING_KEYS: OnceLock<SigningKeys> = OnceLock::new();
+
+/// A platform Ed25519 key with the id it is published under
+pub struct PlatformKey {
+    pub kid: String,
+    key: SigningKey,
+}
+
+/// DER prefix of a PKCS#8 v1 Ed25519 private key (RFC 8410); the 32-byte seed follows
+const PKCS8_ED25519_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+impl PlatformKey {
+    /// The private key as PKCS#8 DER, the form JWT libraries load Ed25519 keys from
+    pub fn pkcs8_der(&self) -> Vec<u8> {
+        let mut der = PKCS8_ED25519_PREFIX.to_vec();
+        der.extend_from_slice(self.key.as_bytes());
+        der
+    }
+
+    /// The raw 32-byte public key
+    pub fn public_key(&self) -> [u8; 32] {
+        self.key.verifying_key().to_bytes()
+    }
+}
+
+/// Platform Ed25519 keys used for asymmetric webhook signatures
+///
+/// Keys come from `WEBHOOK_SIGNING_KEYS` as comma-separated `kid:base64-seed` pairs,
+/// where each seed is 32 random bytes. `WEBHOOK_SIGNING_KEY_ID` picks the key that
+/// signs; it defaults to the first one. Every listed key is published, so a key
+/// can be rotated by adding its successor, switching the active id, and removing
+/// the old key once receivers have refreshed their copy of the key set.
+///
+/// Access tokens are signed with a key set of the same form; see `access_tokens`.
+pub struct SigningKeys {
+    keys: Vec<PlatformKey>,
+    active: Option<usize>,
+}
+
+impl SigningKeys {
+    /// Parse a key list; malformed entries are skipped with a warning
+    pub fn parse(spec: &str, active_kid: Option<&str>) -> Self {
+        let keys: Vec<PlatformKey> = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let parsed = entry.split_once(':').and_then(|(kid, seed)| {
+                    let seed: [u8; 32] = STANDARD.decode(seed.trim()).ok()?.try_into().ok()?;
+                    Some(PlatformKey {
+                        kid: kid.trim().to_string(),
+                        key: SigningKey::from_bytes(&seed),
+                    })
+                });
+                if parsed.is_none() {
+                    tracing::warn!("Ignoring malformed signing key entry");
+                }
+                parsed
+            })
+            .collect();
+
+        let active = match active_kid {
+            Some(kid) => keys.iter().position(|k| k.kid == kid),
+            None => (!keys.is_empty()).then_some(0),
+        };
+        if active.is_none() && !keys.is_empty() {
+            tracing::warn!(
+                kid = ?active_kid,
+                "Active signing key id does not match any key, Ed25519 signing disabled"
+            );
+        }
+
+        Self { keys, active }
+    }
+
+    pub fn from_env() -> Self {
+        Self::from_vars("WEBHOOK_SIGNING_KEYS", "WEBHOOK_SIGNING_KEY_ID")
+    }
+
+    /// Load keys from the environment variable `keys_var`, with the active id in `kid_var`
+    pub fn from_vars(keys_var: &str, kid_var: &str) -> Self {
+        let spec = std::env::var(keys_var).unwrap_or_default();
+        let active_kid = std::env::var(kid_var).ok().filter(|kid| !kid.is_empty());
+        Self::parse(&spec, active_kid.as_deref())
+    }
+
+    /// The key published under `kid`
+    pub fn get(&self, kid: &str) -> Option<&PlatformKey> {
+        self.keys.iter().find(|key| key.kid == kid)
+    }
+
+    /// The key new signatures are made with, if one is configured
+    pub fn active(&self) -> Option<&PlatformKey> {
+        self.active.map(|i| &self.keys[i])
+    }
+
+    /// Sign a payload with the active key
+    /// Returns the key id and the base64-encoded signature
+    pub fn sign(&self, payload: &[u8]) -> Option<(String, String)> {
+        self.active().map(|key| {
+            let signature = key.key.sign(payload);
+            (key.kid.clone(), STANDARD.encode(signature.to_bytes()))
+        })
+    }
+
+    /// Public keys as a JSON Web Key Set (RFC 8037 `OKP` keys)
+    pub fn jwks(&self) -> serde_json::Value {
+        let keys: Vec<serde_json::Value> = self
+            .keys
+            .iter()
+            .map(|key| {
+                json!({
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "alg": "EdDSA",
+                    "use": "sig",
+                    "kid": key.kid,
+                    "x": URL_SAFE_NO_PAD.encode(key.public_key()),
+                })
+            })
+            .collect();
+
+        json!({ "keys": keys })
+    }
+}
+
+/// Platform signing keys of this process
+pub fn signing_keys() -> &'static SigningKeys {
+    SIGNING_KEYS.get_or_init(SigningKeys::from_env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    fn seed(byte: u8) -> String {
+        STANDARD.encode([byte; 32])
+    }
+
+    #[test]
+    fn test_parse_picks_active_key() {
+        let spec = format!("2025-01:{}, 2025-06:{}, broken:abc", seed(1), seed(2));
+
+        let keys = SigningKeys::parse(&spec, None);
+        assert_eq!(keys.active().unwrap().kid, "2025-01");
+
+        let keys = SigningKeys::parse(&spec, Some("2025-06"));
+        assert_eq!(keys.active().unwrap().kid, "2025-06");
+        assert_eq!(keys.jwks()["keys"].as_array().unwrap().len(), 2);
+
+        assert!(
+            SigningKeys::parse(&spec, Some("unknown"))
+                .active()
+                .is_none()
+        );
+        assert!(SigningKeys::parse("", None).sign(b"payload").is_none());
+    }
+
+    #[test]
+    fn test_signature_verifies_with_published_key() {
+        let keys = SigningKeys::parse(&format!("k1:{}", seed(7)), None);
+        let (kid, signature) = keys.sign(b"{\"event\":\"transaction.debited\"}").unwrap();
+        assert_eq!(kid, "k1");
+
+        let jwk = &keys.jwks()["keys"][0];
+        assert_eq!(jwk["kid"], "k1");
+        let public: [u8; 32] = URL_SAFE_NO_PAD
+            .decode(jwk["x"].as_str().unwrap())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let signature: [u8; 64] = STANDARD.decode(signature).unwrap().try_into().unwrap();
+
+        let verifying_key = VerifyingKey::from_bytes(&public).unwrap();
+        assert!(
+            verifying_key
+                .verify(
+                    b"{\"event\":\"transaction.debited\"}",
+                    &Signature::from_bytes(&signature)
+                )
+                .is_ok()
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::services::{api_key_cache::ApiKeyCache, event_bus::EventBus};
 use redis::{Client, aio::ConnectionManager};
 use std::sync::Arc;
 
@@ -13,7 +14,13 @@ impl AppState {
     pub async fn new(redis_url: &str) -> Result<Self, Box<dyn std::error::Error>> {
         // Initialize Redis client
         let redis_client = Client::open(redis_url)?;
-        let redis_conn = ConnectionManager::new(redis_client).await?;
+        let redis_conn = ConnectionManager::new(redis_client.clone()).await?;
+
+        // Event streams fan out through Redis pub/sub on the same server
+        EventBus::init_global(redis_client.clone(), redis_conn.clone());
+
+        // Authentication caches validated API keys in-process and in Redis
+        ApiKeyCache::init_global(redis_client, redis_conn.clone());
 
         Ok(Self {
             redis: Arc::new(redis_conn),